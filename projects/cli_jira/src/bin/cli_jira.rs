@@ -1,12 +1,137 @@
-use anyhow::Error;
-use cli_jira::{db::*, io_utils::*, navigator::*};
+use anyhow::{anyhow, Context, Error};
+use clap::{Parser, Subcommand};
+use cli_jira::{cli, config::*, db::*, io_utils::*, navigator::*, tui};
 
+use std::path::PathBuf;
 use std::rc::Rc;
 
+#[derive(Parser)]
+#[command(about = "A tiny Jira clone for the terminal")]
+struct Cli {
+    /// Board to open, see `board list`
+    #[arg(long, global = true, env = "CLI_JIRA_BOARD")]
+    board: Option<String>,
+
+    /// Database file to open instead of the board's one
+    #[arg(long, global = true, env = "CLI_JIRA_DB")]
+    db: Option<PathBuf>,
+
+    /// Storage backend, overriding the board's one
+    #[arg(long, global = true, value_enum)]
+    backend: Option<Backend>,
+
+    /// Config file to use instead of the one in the config directory
+    #[arg(long, global = true, env = "CLI_JIRA_CONFIG")]
+    config: Option<PathBuf>,
+
+    /// Import an existing JSON board into the (SQLite) board and exit
+    #[arg(long, value_name = "JSON_FILE")]
+    import_json: Option<String>,
+
+    /// Print command output as JSON instead of a table
+    #[arg(long, global = true)]
+    json: bool,
+
+    /// Frontend of the interactive pages, overriding the config's one
+    #[arg(long, value_enum, env = "CLI_JIRA_UI")]
+    ui: Option<Frontend>,
+
+    /// Run a single command instead of the interactive pages
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Manage boards
+    Board {
+        #[command(subcommand)]
+        command: cli::BoardCommand,
+    },
+    #[command(flatten)]
+    Jira(cli::Command),
+}
+
 fn main() -> Result<(), Error> {
-    let db = JiraDatabase::new("data/db.json".to_owned());
+    let cli = Cli::parse();
+
+    let config_path = match cli.config {
+        Some(config_path) => config_path,
+        None => Config::default_path()?,
+    };
+    let mut config = Config::load(&config_path)?;
+
+    let command = match cli.command {
+        Some(Command::Board { command }) => {
+            return cli::run_board_command(
+                &mut config,
+                &config_path,
+                command,
+                cli.json,
+                &mut std::io::stdout(),
+            );
+        }
+        Some(Command::Jira(command)) => Some(command),
+        None => None,
+    };
+
+    let location = config.resolve(cli.board.as_deref(), cli.db.as_deref(), cli.backend)?;
+
+    if let Some(json_path) = cli.import_json {
+        if location.backend != Backend::Sqlite {
+            return Err(anyhow!(
+                "board '{}' is not a SQLite board, pass --backend sqlite",
+                location.name
+            ));
+        }
+
+        if let Some(parent) = location.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let sqlite = SqliteDatabase::open(&location.path.to_string_lossy())?;
+        // read as is, an import mustn't write to its source even if it's broken
+        let source = JSONFileDatabase::read_file(&json_path)
+            .with_context(|| format!("failed to read {}", json_path))?;
+        sqlite.import_state(&source)?;
+
+        let db_state = sqlite.read_db()?;
+        println!(
+            "Imported {} epics and {} stories from {}",
+            db_state.epics.len(),
+            db_state.stories.len(),
+            json_path
+        );
+
+        return Ok(());
+    }
+
+    let db = JiraDatabase::open(&location)?;
+
+    // `check` reports the problems itself
+    if !matches!(command, Some(cli::Command::Check { .. })) {
+        let problems = db.check()?;
+
+        if !problems.is_empty() {
+            eprintln!(
+                "warning: board '{}' has {} integrity problems, run `cli_jira check` for details",
+                location.name,
+                problems.len()
+            );
+        }
+    }
+
+    if let Some(command) = command {
+        return cli::run(&db, command, cli.json, &mut std::io::stdout());
+    }
+
+    let db = Rc::new(db);
+
+    if cli.ui.or(config.frontend).unwrap_or_default() == Frontend::Tui {
+        return tui::run(db);
+    }
 
-    let mut navigator = Navigator::new(Rc::new(db));
+    let mut navigator = Navigator::new(db);
 
     loop {
         clearscreen::clear().unwrap();
@@ -20,8 +145,13 @@ fn main() -> Result<(), Error> {
 
         let action = current_page.handle_input(get_user_input().as_str())?;
 
+        // failed actions, e.g. an undo of something changed elsewhere in the meantime,
+        // are shown like in the TUI instead of ending the session
         if let Some(action) = action {
-            navigator.handle_action(action)?;
+            if let Err(error) = navigator.handle_action(action) {
+                println!("{:#}, press enter to continue.", error);
+                get_user_input();
+            }
         }
     }
 
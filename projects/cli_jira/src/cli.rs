@@ -0,0 +1,956 @@
+use anyhow::{anyhow, Result};
+use chrono::{Duration, Utc};
+use clap::{ArgGroup, Args, Subcommand};
+use serde::Serialize;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::config::{Backend, BoardConfig, Config, DEFAULT_BOARD};
+use crate::db::JiraDatabase;
+use crate::export::{self, ExportFormat};
+use crate::import;
+use crate::models::{Epic, LinkKind, Priority, RecordStatus, Story, TrashedItem};
+use crate::search::SearchHit;
+use crate::ui::pages::page_helpers::{
+    format_checkbox, format_history_entry, format_labels, format_links, format_optional,
+    format_progress, format_timestamp,
+};
+use crate::ui::{terminal_width, Column, Table};
+
+#[derive(Subcommand, Debug, PartialEq)]
+pub enum Command {
+    /// Manage epics
+    Epic {
+        #[command(subcommand)]
+        command: EpicCommand,
+    },
+    /// Manage stories
+    Story {
+        #[command(subcommand)]
+        command: StoryCommand,
+    },
+    /// Find epics and stories whose name or description contains all terms
+    Search {
+        #[arg(required = true)]
+        terms: Vec<String>,
+    },
+    /// List, restore or purge deleted epics and stories
+    Trash {
+        #[command(subcommand)]
+        command: TrashCommand,
+    },
+    /// Export the board, or one epic, leaving out the trash
+    Export {
+        #[arg(long, value_enum)]
+        format: ExportFormat,
+        /// Export only this epic and its stories
+        #[arg(long)]
+        epic: Option<u32>,
+        /// File to write to instead of standard output
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Look for broken references and ids in the board, and optionally fix them
+    #[command(visible_alias = "fsck")]
+    Check {
+        /// Fix the problems found, see the README for how
+        #[arg(long)]
+        repair: bool,
+    },
+    /// Create epics and stories from a CSV file laid out like `export --format csv`
+    Import {
+        file: PathBuf,
+        /// Only check the file and list what would be created
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand, Debug, PartialEq)]
+pub enum EpicCommand {
+    /// Create an epic and print its id
+    Create {
+        #[arg(long)]
+        name: String,
+        #[arg(long, default_value = "")]
+        description: String,
+        #[arg(long = "label")]
+        labels: Vec<String>,
+    },
+    /// List all epics
+    List,
+    /// Show an epic and its stories
+    Show { epic_id: u32 },
+    /// Change the name, description and/or labels of an epic
+    Edit {
+        epic_id: u32,
+        #[arg(long)]
+        name: Option<String>,
+        #[arg(long)]
+        description: Option<String>,
+        /// Replaces all labels; repeat for several
+        #[arg(long = "label")]
+        labels: Vec<String>,
+        /// Removes all labels
+        #[arg(long, conflicts_with = "labels")]
+        clear_labels: bool,
+    },
+    /// Set the status of an epic, as allowed by the board's workflow
+    SetStatus { epic_id: u32, status: RecordStatus },
+    /// Move an epic together with its stories to the trash
+    Delete { epic_id: u32 },
+}
+
+#[derive(Subcommand, Debug, PartialEq)]
+pub enum StoryCommand {
+    /// Create a story in an epic and print its id
+    Create {
+        #[arg(long)]
+        epic: u32,
+        #[arg(long)]
+        name: String,
+        #[arg(long, default_value = "")]
+        description: String,
+        #[command(flatten)]
+        details: StoryDetailsArgs,
+    },
+    /// List the stories of an epic
+    List {
+        #[arg(long)]
+        epic: u32,
+    },
+    /// Show a story
+    Show { story_id: u32 },
+    /// Change the name, description and/or details of a story
+    Edit {
+        story_id: u32,
+        #[arg(long)]
+        name: Option<String>,
+        #[arg(long)]
+        description: Option<String>,
+        #[command(flatten)]
+        details: StoryDetailsArgs,
+        /// Removes the assignee
+        #[arg(long, conflicts_with = "assignee")]
+        clear_assignee: bool,
+        /// Removes all labels
+        #[arg(long, conflicts_with = "labels")]
+        clear_labels: bool,
+    },
+    /// Set the status of a story, as allowed by the board's workflow
+    SetStatus { story_id: u32, status: RecordStatus },
+    /// Move a story to the trash
+    Delete { story_id: u32 },
+    /// Move a story to another epic
+    Move {
+        story_id: u32,
+        #[arg(long)]
+        to: u32,
+    },
+    /// Show the change history of a story, oldest first
+    History { story_id: u32 },
+    /// Comment on a story
+    Comment { story_id: u32, text: String },
+    /// Show the comments on a story, oldest first
+    Comments { story_id: u32 },
+    /// Link a story to another one, e.g. `story link 12 blocks 15`
+    Link {
+        story_id: u32,
+        /// blocks, blocked-by, relates-to, duplicates or duplicated-by
+        kind: LinkKind,
+        other_id: u32,
+    },
+    /// Remove a link between two stories, given as for `link`
+    Unlink {
+        story_id: u32,
+        kind: LinkKind,
+        other_id: u32,
+    },
+    /// Show the stories linked to a story
+    Links { story_id: u32 },
+    /// Manage the checklist of a story
+    Checklist {
+        #[command(subcommand)]
+        command: ChecklistCommand,
+    },
+}
+
+#[derive(Subcommand, Debug, PartialEq)]
+pub enum ChecklistCommand {
+    /// Add an item to the end of the checklist and print its number
+    Add { story_id: u32, text: String },
+    /// Tick off an item, or untick it if it is done
+    Toggle { story_id: u32, number: usize },
+    /// Remove an item; the following ones move up a number
+    Remove { story_id: u32, number: usize },
+}
+
+#[derive(Subcommand, Debug, PartialEq)]
+pub enum TrashCommand {
+    /// List the epics and stories in the trash
+    List,
+    /// Take an epic or story out of the trash
+    Restore { item_id: u32 },
+    /// Delete one item, or everything older than some days, for good
+    #[command(group(ArgGroup::new("target").required(true).args(["item_id", "older_than"])))]
+    Purge {
+        item_id: Option<u32>,
+        /// Purge everything that has been in the trash for at least this many days
+        #[arg(long, value_name = "DAYS")]
+        older_than: Option<u32>,
+    },
+}
+
+#[derive(Args, Debug, PartialEq, Default)]
+pub struct StoryDetailsArgs {
+    /// low, medium, high or critical
+    #[arg(long)]
+    pub priority: Option<Priority>,
+    #[arg(long)]
+    pub assignee: Option<String>,
+    /// Replaces all labels; repeat for several
+    #[arg(long = "label")]
+    pub labels: Vec<String>,
+    /// Story points
+    #[arg(long)]
+    pub estimate: Option<u32>,
+}
+
+impl StoryDetailsArgs {
+    // Fields that were not passed keep the story's current value
+    fn apply(self, story: &Story) -> Story {
+        Story {
+            priority: self.priority.or(story.priority),
+            assignee: self.assignee.or_else(|| story.assignee.clone()),
+            labels: if self.labels.is_empty() {
+                story.labels.clone()
+            } else {
+                self.labels
+            },
+            estimate: self.estimate.or(story.estimate),
+            ..story.clone()
+        }
+    }
+}
+
+#[derive(Subcommand, Debug, PartialEq)]
+pub enum BoardCommand {
+    /// List the configured boards
+    List,
+    /// Register a new board
+    Add {
+        name: String,
+        /// Database file of the board; defaults to the data directory
+        #[arg(long)]
+        path: Option<PathBuf>,
+        #[arg(long, value_enum)]
+        backend: Option<Backend>,
+    },
+    /// Make a board the default one
+    Use { name: String },
+    /// Forget a board (its database file is kept)
+    Remove { name: String },
+}
+
+#[derive(Serialize)]
+struct Entry<'a, T> {
+    id: u32,
+    #[serde(flatten)]
+    item: &'a T,
+}
+
+/// Runs a single command against the database, printing a human readable table or
+/// JSON (`json = true`) to `out`.
+pub fn run(db: &JiraDatabase, command: Command, json: bool, out: &mut impl Write) -> Result<()> {
+    match command {
+        Command::Epic { command } => run_epic_command(db, command, json, out),
+        Command::Story { command } => run_story_command(db, command, json, out),
+        Command::Trash { command } => run_trash_command(db, command, json, out),
+        Command::Export {
+            format,
+            epic,
+            output,
+        } => {
+            let db_state = db.export_state(epic)?;
+            let exported = export::export(&db_state, &db.workflow, format)?;
+
+            match output {
+                Some(path) => std::fs::write(&path, exported)
+                    .map_err(|error| anyhow!("failed to write {}: {}", path.display(), error)),
+                None => {
+                    out.write_all(exported.as_bytes())?;
+                    Ok(())
+                }
+            }
+        }
+        Command::Check { repair } => {
+            let problems = if repair { db.repair()? } else { db.check()? };
+
+            if json {
+                print_json(out, &problems)?;
+            } else {
+                for problem in &problems {
+                    writeln!(out, "{}", problem)?;
+                }
+            }
+
+            if problems.is_empty() {
+                if !json {
+                    writeln!(out, "No problems found")?;
+                }
+            } else if repair {
+                if !json {
+                    writeln!(out, "Repaired {} problems", problems.len())?;
+                }
+            } else {
+                return Err(anyhow!(
+                    "found {} problems, run `check --repair` to fix them",
+                    problems.len()
+                ));
+            }
+
+            Ok(())
+        }
+        Command::Import { file, dry_run } => {
+            let input = std::fs::read_to_string(&file)
+                .map_err(|error| anyhow!("failed to read {}: {}", file.display(), error))?;
+            let summary = import::import_csv(db, &input, dry_run)?;
+
+            if json {
+                return print_json(out, &summary);
+            }
+
+            for item in &summary.items {
+                let id = item.id.map_or("-".to_owned(), |id| id.to_string());
+                let epic = item
+                    .epic
+                    .as_ref()
+                    .map_or(String::new(), |epic| format!(" in epic '{}'", epic));
+
+                writeln!(out, "{} {} '{}'{}", item.kind, id, item.name, epic)?;
+            }
+
+            let verb = if summary.dry_run {
+                "Would create"
+            } else {
+                "Created"
+            };
+            writeln!(
+                out,
+                "{} {} epics and {} stories",
+                verb,
+                summary.epic_count(),
+                summary.story_count()
+            )?;
+
+            Ok(())
+        }
+        Command::Search { terms } => {
+            let hits = db.search(&terms.join(" "))?;
+
+            if json {
+                return print_json(out, &hits);
+            }
+
+            let mut table = Table::new(vec![
+                Column::fixed("id", 6),
+                Column::fixed("type", 5),
+                Column::fill("name", 12),
+                Column::fill("status", 8),
+            ]);
+
+            for hit in &hits {
+                let (kind, status) = match hit {
+                    SearchHit::Epic { epic, .. } => ("epic", &epic.status),
+                    SearchHit::Story { story, .. } => ("story", &story.status),
+                };
+
+                table.add_row(vec![
+                    hit.id().to_string(),
+                    kind.to_owned(),
+                    hit.name().to_owned(),
+                    status.to_string(),
+                ]);
+            }
+
+            print_table(out, &table)
+        }
+    }
+}
+
+/// Runs a board command, saving the config to `config_path` when it changes.
+pub fn run_board_command(
+    config: &mut Config,
+    config_path: &Path,
+    command: BoardCommand,
+    json: bool,
+    out: &mut impl Write,
+) -> Result<()> {
+    match command {
+        BoardCommand::List => {
+            let mut names: Vec<&str> = config.boards.keys().map(String::as_str).collect();
+
+            if !config.boards.contains_key(DEFAULT_BOARD) {
+                names.insert(0, DEFAULT_BOARD);
+            }
+
+            let mut boards = vec![];
+
+            for name in names {
+                let location = config.resolve(Some(name), None, None)?;
+
+                boards.push(serde_json::json!({
+                    "name": location.name,
+                    "path": location.path,
+                    "backend": location.backend,
+                    "current": name == config.current_board(),
+                }));
+            }
+
+            if json {
+                return print_json(out, &boards);
+            }
+
+            for board in boards {
+                let marker = if board["current"] == true { "*" } else { " " };
+
+                writeln!(
+                    out,
+                    "{} {} ({})",
+                    marker,
+                    board["name"].as_str().unwrap_or_default(),
+                    board["path"].as_str().unwrap_or_default()
+                )?;
+            }
+
+            Ok(())
+        }
+        BoardCommand::Add {
+            name,
+            path,
+            backend,
+        } => {
+            if config.boards.contains_key(&name) {
+                return Err(anyhow!("board '{}' already exists", name));
+            }
+
+            let backend = backend.unwrap_or_default();
+            config.boards.insert(
+                name.clone(),
+                BoardConfig {
+                    path,
+                    backend,
+                    ..Default::default()
+                },
+            );
+            config.save(config_path)?;
+
+            writeln!(out, "Added board {}", name)?;
+            Ok(())
+        }
+        BoardCommand::Use { name } => {
+            config.resolve(Some(&name), None, None)?;
+            config.default_board = Some(name.clone());
+            config.save(config_path)?;
+
+            writeln!(out, "Switched to board {}", name)?;
+            Ok(())
+        }
+        BoardCommand::Remove { name } => {
+            config
+                .boards
+                .remove(&name)
+                .ok_or_else(|| anyhow!("unknown board '{}'", name))?;
+
+            if config.default_board.as_deref() == Some(name.as_str()) {
+                config.default_board = None;
+            }
+
+            config.save(config_path)?;
+
+            writeln!(out, "Removed board {}", name)?;
+            Ok(())
+        }
+    }
+}
+
+fn run_epic_command(
+    db: &JiraDatabase,
+    command: EpicCommand,
+    json: bool,
+    out: &mut impl Write,
+) -> Result<()> {
+    match command {
+        EpicCommand::Create {
+            name,
+            description,
+            labels,
+        } => {
+            let epic_id = db.create_epic(Epic {
+                labels,
+                ..Epic::new(name, description)
+            })?;
+
+            print_id(out, "Created epic", epic_id, json)
+        }
+        EpicCommand::List => {
+            let epics = db.list_epics()?;
+
+            print_list(out, &epics, json, |epic| (&epic.name, &epic.status))
+        }
+        EpicCommand::Show { epic_id } => {
+            let epic = find_epic(db, epic_id)?;
+
+            if json {
+                return print_json(
+                    out,
+                    &Entry {
+                        id: epic_id,
+                        item: &epic,
+                    },
+                );
+            }
+
+            writeln!(out, "{} - {} [{}]", epic_id, epic.name, epic.status)?;
+            writeln!(out, "{}", epic.description)?;
+            let (done, total) = db.epic_progress(epic_id)?;
+
+            writeln!(out, "labels: {}", format_labels(&epic.labels))?;
+            writeln!(out, "progress: {}", format_progress(done, total))?;
+            writeln!(
+                out,
+                "created: {} | updated: {}",
+                format_timestamp(epic.created_at),
+                format_timestamp(epic.updated_at)
+            )?;
+            writeln!(out)?;
+
+            let stories = db.list_stories_of_epic(epic_id)?;
+
+            print_list(out, &stories, false, |story| (&story.name, &story.status))
+        }
+        EpicCommand::Edit {
+            epic_id,
+            name,
+            description,
+            labels,
+            clear_labels,
+        } => {
+            db.edit_epic(epic_id, |epic| {
+                if let Some(name) = name {
+                    epic.name = name;
+                }
+                if let Some(description) = description {
+                    epic.description = description;
+                }
+                if !labels.is_empty() || clear_labels {
+                    epic.labels = labels;
+                }
+            })?;
+
+            print_id(out, "Updated epic", epic_id, json)
+        }
+        EpicCommand::SetStatus { epic_id, status } => {
+            db.update_epic_status(epic_id, status)?;
+
+            print_id(out, "Updated epic", epic_id, json)
+        }
+        EpicCommand::Delete { epic_id } => {
+            db.delete_epic(epic_id)?;
+
+            print_id(out, "Deleted epic", epic_id, json)
+        }
+    }
+}
+
+fn run_story_command(
+    db: &JiraDatabase,
+    command: StoryCommand,
+    json: bool,
+    out: &mut impl Write,
+) -> Result<()> {
+    match command {
+        StoryCommand::Create {
+            epic,
+            name,
+            description,
+            details,
+        } => {
+            let story = details.apply(&Story::new(name, description));
+            let story_id = db.create_story(story, epic)?;
+
+            print_id(out, "Created story", story_id, json)
+        }
+        StoryCommand::List { epic } => {
+            let stories = db.list_stories_of_epic(epic)?;
+
+            print_list(out, &stories, json, |story| (&story.name, &story.status))
+        }
+        StoryCommand::Show { story_id } => {
+            let story = find_story(db, story_id)?;
+
+            if json {
+                return print_json(
+                    out,
+                    &Entry {
+                        id: story_id,
+                        item: &story,
+                    },
+                );
+            }
+
+            writeln!(out, "{} - {} [{}]", story_id, story.name, story.status)?;
+            writeln!(out, "{}", story.description)?;
+            writeln!(
+                out,
+                "priority: {} | assignee: {} | points: {}",
+                format_optional(story.priority),
+                format_optional(story.assignee.as_ref()),
+                format_optional(story.estimate)
+            )?;
+            writeln!(out, "labels: {}", format_labels(&story.labels))?;
+            writeln!(out, "links: {}", format_links(&db.links_of(story_id)?))?;
+            writeln!(
+                out,
+                "created: {} | updated: {}",
+                format_timestamp(story.created_at),
+                format_timestamp(story.updated_at)
+            )?;
+
+            for (index, item) in story.checklist.iter().enumerate() {
+                writeln!(
+                    out,
+                    "{} {}. {}",
+                    format_checkbox(item.done),
+                    index + 1,
+                    item.text
+                )?;
+            }
+
+            Ok(())
+        }
+        StoryCommand::Edit {
+            story_id,
+            name,
+            description,
+            details,
+            clear_assignee,
+            clear_labels,
+        } => {
+            db.edit_story(story_id, |story| {
+                *story = details.apply(story);
+
+                if let Some(name) = name {
+                    story.name = name;
+                }
+                if let Some(description) = description {
+                    story.description = description;
+                }
+                if clear_assignee {
+                    story.assignee = None;
+                }
+                if clear_labels {
+                    story.labels.clear();
+                }
+            })?;
+
+            print_id(out, "Updated story", story_id, json)
+        }
+        StoryCommand::SetStatus { story_id, status } => {
+            // starting a blocked story is only warned about, on stderr
+            let blocked_by: Vec<u32> = db
+                .blockers_on_start(story_id, status.clone())?
+                .into_iter()
+                .map(|(blocker_id, _)| blocker_id)
+                .collect();
+            db.update_story_status(story_id, status)?;
+
+            if blocked_by.is_empty() {
+                return print_id(out, "Updated story", story_id, json);
+            }
+
+            if json {
+                return print_json(
+                    out,
+                    &serde_json::json!({ "id": story_id, "blocked_by": blocked_by }),
+                );
+            }
+
+            eprintln!(
+                "Warning: story {} is blocked by unfinished stories {}",
+                story_id,
+                blocked_by
+                    .iter()
+                    .map(u32::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+            print_id(out, "Updated story", story_id, json)
+        }
+        StoryCommand::Delete { story_id } => {
+            let epic_id = db.epic_id_of_story(story_id)?;
+            db.delete_story(epic_id, story_id)?;
+
+            print_id(out, "Deleted story", story_id, json)
+        }
+        StoryCommand::Move { story_id, to } => {
+            let epic_id = db.epic_id_of_story(story_id)?;
+            db.move_story(story_id, epic_id, to)?;
+
+            print_id(out, "Moved story", story_id, json)
+        }
+        StoryCommand::History { story_id } => {
+            find_story(db, story_id)?;
+            let history = db.history_of(story_id)?;
+
+            if json {
+                return print_json(out, &history);
+            }
+
+            for entry in &history {
+                writeln!(out, "{}", format_history_entry(entry))?;
+            }
+
+            Ok(())
+        }
+        StoryCommand::Comment { story_id, text } => {
+            db.add_comment(story_id, text)?;
+
+            print_id(out, "Commented on story", story_id, json)
+        }
+        StoryCommand::Comments { story_id } => {
+            find_story(db, story_id)?;
+            let comments = db.comments_of(story_id)?;
+
+            if json {
+                return print_json(out, &comments);
+            }
+
+            for comment in &comments {
+                writeln!(
+                    out,
+                    "{} {}: {}",
+                    format_timestamp(Some(comment.timestamp)),
+                    comment.author,
+                    comment.text
+                )?;
+            }
+
+            Ok(())
+        }
+        StoryCommand::Link {
+            story_id,
+            kind,
+            other_id,
+        } => {
+            db.link_stories(story_id, kind, other_id)?;
+
+            print_id(out, "Linked story", story_id, json)
+        }
+        StoryCommand::Unlink {
+            story_id,
+            kind,
+            other_id,
+        } => {
+            db.unlink_stories(story_id, kind, other_id)?;
+
+            print_id(out, "Unlinked story", story_id, json)
+        }
+        StoryCommand::Links { story_id } => {
+            find_story(db, story_id)?;
+            let links = db.links_of(story_id)?;
+
+            if json {
+                return print_json(out, &links);
+            }
+
+            for linked in &links {
+                writeln!(
+                    out,
+                    "{} {} - {} [{}]",
+                    linked.kind, linked.story_id, linked.story.name, linked.story.status
+                )?;
+            }
+
+            Ok(())
+        }
+        StoryCommand::Checklist { command } => run_checklist_command(db, command, json, out),
+    }
+}
+
+// Items are numbered from 1, as on the story page
+fn run_checklist_command(
+    db: &JiraDatabase,
+    command: ChecklistCommand,
+    json: bool,
+    out: &mut impl Write,
+) -> Result<()> {
+    let (story_id, message, number) = match command {
+        ChecklistCommand::Add { story_id, text } => {
+            let index = db.add_checklist_item(story_id, text)?;
+
+            (story_id, "Added checklist item", index + 1)
+        }
+        ChecklistCommand::Toggle { story_id, number } => {
+            let done = db.toggle_checklist_item(story_id, checklist_index(story_id, number)?)?;
+
+            let message = if done {
+                "Ticked off checklist item"
+            } else {
+                "Unticked checklist item"
+            };
+            (story_id, message, number)
+        }
+        ChecklistCommand::Remove { story_id, number } => {
+            db.remove_checklist_item(story_id, checklist_index(story_id, number)?)?;
+
+            (story_id, "Removed checklist item", number)
+        }
+    };
+
+    if json {
+        return print_json(
+            out,
+            &serde_json::json!({ "story_id": story_id, "number": number }),
+        );
+    }
+
+    writeln!(out, "{} {} of story {}", message, number, story_id)?;
+    Ok(())
+}
+
+fn checklist_index(story_id: u32, number: usize) -> Result<usize> {
+    number
+        .checked_sub(1)
+        .ok_or_else(|| anyhow!("Checklist item 0 of story {} not found", story_id))
+}
+
+fn run_trash_command(
+    db: &JiraDatabase,
+    command: TrashCommand,
+    json: bool,
+    out: &mut impl Write,
+) -> Result<()> {
+    match command {
+        TrashCommand::List => {
+            let items = db.list_trash()?;
+
+            if json {
+                return print_json(out, &items);
+            }
+
+            let mut table = Table::new(vec![
+                Column::fixed("id", 6),
+                Column::fixed("type", 5),
+                Column::fill("name", 12),
+                Column::fixed("deleted", 16),
+            ]);
+
+            for item in &items {
+                let kind = match item {
+                    TrashedItem::Epic { .. } => "epic",
+                    TrashedItem::Story { .. } => "story",
+                };
+
+                table.add_row(vec![
+                    item.id().to_string(),
+                    kind.to_owned(),
+                    item.name().to_owned(),
+                    format_timestamp(item.deleted_at()),
+                ]);
+            }
+
+            print_table(out, &table)
+        }
+        TrashCommand::Restore { item_id } => {
+            db.restore_from_trash(item_id)?;
+
+            print_id(out, "Restored", item_id, json)
+        }
+        TrashCommand::Purge {
+            item_id: Some(item_id),
+            ..
+        } => {
+            db.purge_from_trash(item_id)?;
+
+            print_id(out, "Purged", item_id, json)
+        }
+        TrashCommand::Purge {
+            older_than: Some(days),
+            ..
+        } => {
+            let purged = db.purge_trash(Utc::now() - Duration::days(days.into()))?;
+
+            if json {
+                return print_json(out, &serde_json::json!({ "purged": purged }));
+            }
+
+            writeln!(out, "Purged {} items", purged.len())?;
+            Ok(())
+        }
+        TrashCommand::Purge { .. } => Err(anyhow!("pass an item id or --older-than")),
+    }
+}
+
+fn find_epic(db: &JiraDatabase, epic_id: u32) -> Result<Epic> {
+    db.get_epic(epic_id)?
+        .ok_or_else(|| anyhow!("Epic of id {} not found", epic_id))
+}
+
+fn find_story(db: &JiraDatabase, story_id: u32) -> Result<Story> {
+    db.get_story(story_id)?
+        .ok_or_else(|| anyhow!("Story of id {} not found", story_id))
+}
+
+// Sized like the pages' tables, or to `COLUMNS` when the output isn't a terminal
+fn print_table(out: &mut impl Write, table: &Table) -> Result<()> {
+    for line in table.render(terminal_width()) {
+        writeln!(out, "{}", line)?;
+    }
+
+    Ok(())
+}
+
+fn print_json(out: &mut impl Write, value: &impl Serialize) -> Result<()> {
+    writeln!(out, "{}", serde_json::to_string(value)?)?;
+    Ok(())
+}
+
+fn print_id(out: &mut impl Write, message: &str, id: u32, json: bool) -> Result<()> {
+    if json {
+        return print_json(out, &serde_json::json!({ "id": id }));
+    }
+
+    writeln!(out, "{} {}", message, id)?;
+    Ok(())
+}
+
+fn print_list<T: Serialize>(
+    out: &mut impl Write,
+    items: &[(u32, T)],
+    json: bool,
+    columns: impl Fn(&T) -> (&String, &RecordStatus),
+) -> Result<()> {
+    if json {
+        let entries: Vec<_> = items
+            .iter()
+            .map(|(id, item)| Entry { id: *id, item })
+            .collect();
+
+        return print_json(out, &entries);
+    }
+
+    let mut table = Table::new(vec![
+        Column::fixed("id", 6),
+        Column::fill("name", 12),
+        Column::fill("status", 8),
+    ]);
+
+    for (id, item) in items {
+        let (name, status) = columns(item);
+
+        table.add_row(vec![id.to_string(), name.to_owned(), status.to_string()]);
+    }
+
+    print_table(out, &table)
+}
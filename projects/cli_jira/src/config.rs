@@ -0,0 +1,184 @@
+use anyhow::{anyhow, Context, Result};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::workflow::Workflow;
+
+pub const DEFAULT_BOARD: &str = "default";
+/// Where the default board was stored, relative to the working directory, before
+/// boards moved to the data directory.
+pub const LEGACY_DB_PATH: &str = "data/db.json";
+
+#[derive(ValueEnum, Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    #[default]
+    Json,
+    Sqlite,
+}
+
+impl Backend {
+    fn extension(&self) -> &'static str {
+        match self {
+            Backend::Json => "json",
+            Backend::Sqlite => "sqlite",
+        }
+    }
+
+    fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("sqlite" | "sqlite3" | "db") => Backend::Sqlite,
+            _ => Backend::Json,
+        }
+    }
+}
+
+/// How the interactive pages are shown.
+#[derive(ValueEnum, Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Frontend {
+    /// Printed tables, commands are entered as lines
+    #[default]
+    Plain,
+    /// Full-screen, driven by single key presses
+    Tui,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Default)]
+pub struct BoardConfig {
+    /// Where the board is stored; defaults to `<data dir>/cli_jira/<board>.<backend>`.
+    pub path: Option<PathBuf>,
+    #[serde(default)]
+    pub backend: Backend,
+    /// Overrides the top-level workflow for this board.
+    pub workflow: Option<Workflow>,
+}
+
+/// Contents of `<config dir>/cli_jira/config.toml`.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Default)]
+pub struct Config {
+    pub default_board: Option<String>,
+    /// Status workflow of all boards without their own; the built-in one if unset.
+    pub workflow: Option<Workflow>,
+    /// Frontend of the interactive pages, unless `--ui` is passed.
+    pub frontend: Option<Frontend>,
+    /// Name recorded as the author of changes and comments, instead of `CLI_JIRA_USER`
+    /// or the login name.
+    pub author: Option<String>,
+    #[serde(default)]
+    pub boards: BTreeMap<String, BoardConfig>,
+}
+
+/// A board resolved from the config and command-line overrides.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct BoardLocation {
+    pub name: String,
+    pub path: PathBuf,
+    pub backend: Backend,
+    pub workflow: Workflow,
+    /// `author` of the config, see `JiraDatabase::author`.
+    pub author: Option<String>,
+}
+
+impl Config {
+    /// `$XDG_CONFIG_HOME/cli_jira/config.toml` (or the platform equivalent).
+    pub fn default_path() -> Result<PathBuf> {
+        let config_dir =
+            dirs::config_dir().ok_or_else(|| anyhow!("could not find the config directory"))?;
+
+        Ok(config_dir.join("cli_jira").join("config.toml"))
+    }
+
+    /// Loads the config, falling back to an empty one if the file doesn't exist yet.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Config::default());
+        }
+
+        let content = fs::read_to_string(path)?;
+        toml::from_str(&content).with_context(|| anyhow!("invalid config file {}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(path, toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn current_board(&self) -> &str {
+        self.default_board.as_deref().unwrap_or(DEFAULT_BOARD)
+    }
+
+    /// Picks the board to open. `board` and `db_path` come from the command line or
+    /// environment and win over the config; `backend` wins over everything else.
+    pub fn resolve(
+        &self,
+        board: Option<&str>,
+        db_path: Option<&Path>,
+        backend: Option<Backend>,
+    ) -> Result<BoardLocation> {
+        let name = board.unwrap_or_else(|| self.current_board()).to_owned();
+
+        let board_config = match self.boards.get(&name) {
+            Some(board_config) => board_config.clone(),
+            None if name == DEFAULT_BOARD || db_path.is_some() => BoardConfig::default(),
+            None => {
+                return Err(anyhow!(
+                    "unknown board '{}', add it with `cli_jira board add {}`",
+                    name,
+                    name
+                ))
+            }
+        };
+
+        let (path, backend) = match (db_path, &board_config.path) {
+            (Some(path), _) => (
+                path.to_path_buf(),
+                backend.unwrap_or_else(|| Backend::from_path(path)),
+            ),
+            (None, Some(path)) => (path.clone(), backend.unwrap_or(board_config.backend)),
+            (None, None) => {
+                let backend = backend.unwrap_or(board_config.backend);
+                (default_board_path(&name, backend)?, backend)
+            }
+        };
+
+        let workflow = board_config
+            .workflow
+            .or_else(|| self.workflow.clone())
+            .unwrap_or_default();
+        workflow
+            .validate()
+            .with_context(|| anyhow!("invalid workflow for board '{}'", name))?;
+
+        Ok(BoardLocation {
+            name,
+            path,
+            backend,
+            workflow,
+            author: self.author.clone(),
+        })
+    }
+}
+
+// Boards live in the data directory, except that a default JSON board that is still
+// at `LEGACY_DB_PATH` keeps being used from there until it's moved.
+fn default_board_path(name: &str, backend: Backend) -> Result<PathBuf> {
+    let data_dir = dirs::data_dir().ok_or_else(|| anyhow!("could not find the data directory"))?;
+    let path = data_dir
+        .join("cli_jira")
+        .join(format!("{}.{}", name, backend.extension()));
+    let legacy_path = Path::new(LEGACY_DB_PATH);
+
+    if name == DEFAULT_BOARD && backend == Backend::Json && !path.exists() && legacy_path.exists() {
+        return Ok(legacy_path.to_path_buf());
+    }
+
+    Ok(path)
+}
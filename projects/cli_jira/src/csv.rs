@@ -0,0 +1,106 @@
+//! Just enough CSV (RFC 4180) for the exports and imports: comma separated fields,
+//! quoted with `"` when they contain a comma, quote or line break.
+
+use anyhow::{anyhow, Result};
+
+/// A parsed line of a CSV file, with the line it starts on for error messages.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Record {
+    pub line: usize,
+    pub fields: Vec<String>,
+}
+
+/// Formats one record, without the line break.
+pub fn write_record<S: AsRef<str>>(fields: &[S]) -> String {
+    fields
+        .iter()
+        .map(|field| quote(field.as_ref()))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn quote(field: &str) -> String {
+    let needs_quotes =
+        field.contains([',', '"', '\n', '\r']) || field.starts_with(' ') || field.ends_with(' ');
+
+    if !needs_quotes {
+        return field.to_owned();
+    }
+
+    format!("\"{}\"", field.replace('"', "\"\""))
+}
+
+/// Parses all records of `input`, skipping blank lines. Line breaks may be `\n` or `\r\n`.
+pub fn parse(input: &str) -> Result<Vec<Record>> {
+    let mut records = vec![];
+    let mut fields = vec![];
+    let mut field = String::new();
+    let mut in_quotes = false;
+    // whether the current field was quoted, so that `""` isn't taken for a blank line
+    let mut quoted = false;
+    let mut line = 1;
+    let mut record_line = 1;
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                _ => {
+                    if c == '\n' {
+                        line += 1;
+                    }
+
+                    field.push(c);
+                }
+            }
+
+            continue;
+        }
+
+        match c {
+            '"' if field.is_empty() && !quoted => {
+                in_quotes = true;
+                quoted = true;
+            }
+            '"' => return Err(anyhow!("line {}: unexpected quote", line)),
+            ',' => {
+                fields.push(std::mem::take(&mut field));
+                quoted = false;
+            }
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                if !fields.is_empty() || !field.is_empty() || quoted {
+                    fields.push(std::mem::take(&mut field));
+                    records.push(Record {
+                        line: record_line,
+                        fields: std::mem::take(&mut fields),
+                    });
+                }
+
+                quoted = false;
+                line += 1;
+                record_line = line;
+            }
+            _ => field.push(c),
+        }
+    }
+
+    if in_quotes {
+        return Err(anyhow!("line {}: unterminated quote", record_line));
+    }
+
+    if !fields.is_empty() || !field.is_empty() || quoted {
+        fields.push(field);
+        records.push(Record {
+            line: record_line,
+            fields,
+        });
+    }
+
+    Ok(records)
+}
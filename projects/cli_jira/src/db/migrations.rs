@@ -0,0 +1,100 @@
+//! Layout of the JSON database file and the steps that upgrade older files to it.
+//!
+//! The file is `DBState` with a `version` field next to its own fields. To change the
+//! layout, raise `SCHEMA_VERSION` and add a step to `MIGRATIONS` that rewrites a file of
+//! the previous version, instead of teaching `DBState` to read both layouts.
+//!
+//! So far every version only added fields. Those are left out of the file while they
+//! are empty and read back as empty when missing (`#[serde(default)]`), so the steps
+//! below rewrite nothing and only stamp the version. The version is raised all the same
+//! so that older builds refuse the file instead of dropping the new fields the next
+//! time they write it.
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::models::DBState;
+
+/// Version of the layout written by this build.
+pub const SCHEMA_VERSION: u32 = 4;
+
+type Migration = fn(&mut Map<String, Value>) -> Result<()>;
+
+// Step `i` upgrades a file of version `i` to version `i + 1`.
+const MIGRATIONS: [Migration; SCHEMA_VERSION as usize] =
+    [from_unversioned, from_v1, from_v2, from_v3];
+
+#[derive(Serialize)]
+struct VersionedState<'a> {
+    version: u32,
+    #[serde(flatten)]
+    board: &'a DBState,
+}
+
+/// The board as the file content of the current version.
+pub fn to_json(db_state: &DBState) -> Result<String> {
+    Ok(serde_json::to_string(&VersionedState {
+        version: SCHEMA_VERSION,
+        board: db_state,
+    })?)
+}
+
+/// Version of a parsed file; files written before versioning have none and count as 0.
+pub fn version_of(value: &Value) -> Result<u32> {
+    match value.get("version") {
+        None => Ok(0),
+        Some(version) => version
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| anyhow!("invalid database version {}", version)),
+    }
+}
+
+/// Runs every step from the file's version up to `SCHEMA_VERSION` and reads the result.
+pub fn migrate(value: Value) -> Result<DBState> {
+    let version = version_of(&value)?;
+
+    if version > SCHEMA_VERSION {
+        return Err(anyhow!(
+            "the database was written by a newer version of cli_jira (version {}, this one reads up to {})",
+            version,
+            SCHEMA_VERSION
+        ));
+    }
+
+    let Value::Object(mut fields) = value else {
+        return Err(anyhow!("the database is not a JSON object"));
+    };
+
+    for (from, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        migration(&mut fields)
+            .map_err(|error| error.context(format!("failed to upgrade from version {}", from)))?;
+        fields.insert("version".to_owned(), Value::from(from + 1));
+    }
+
+    fields.remove("version");
+
+    Ok(serde_json::from_value(Value::Object(fields))?)
+}
+
+// Files from before versioning gained story details, then timestamps and history, then
+// `deleted_at` of the trash.
+fn from_unversioned(_fields: &mut Map<String, Value>) -> Result<()> {
+    Ok(())
+}
+
+// Version 2 added story checklists and their history entries.
+fn from_v1(_fields: &mut Map<String, Value>) -> Result<()> {
+    Ok(())
+}
+
+// Version 3 added comments on stories.
+fn from_v2(_fields: &mut Map<String, Value>) -> Result<()> {
+    Ok(())
+}
+
+// Version 4 added links between stories and their history entries.
+fn from_v3(_fields: &mut Map<String, Value>) -> Result<()> {
+    Ok(())
+}
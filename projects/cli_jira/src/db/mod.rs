@@ -0,0 +1,1906 @@
+use anyhow::{anyhow, Ok, Result};
+use chrono::{DateTime, Utc};
+use itertools::Itertools;
+use std::cell::RefCell;
+use std::env;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::Write;
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::config::{Backend, BoardLocation};
+use crate::fsck::{self, Problem};
+use crate::models::{
+    Change, ChecklistItem, Comment, DBState, Epic, HistoryEntry, LinkKind, LinkedStory, Priority,
+    RecordStatus, Story, StoryLink, TrashedItem,
+};
+use crate::search::{self, SearchHit};
+use crate::undo::{Revision, Snapshot};
+use crate::workflow::Workflow;
+
+mod migrations;
+mod sqlite;
+pub use migrations::SCHEMA_VERSION;
+pub use sqlite::SqliteDatabase;
+
+pub struct JiraDatabase {
+    pub database: Box<dyn Database>,
+    /// Status changes are checked against this.
+    pub workflow: Workflow,
+    /// Name recorded as the author of changes and comments, see `author`.
+    pub author: Option<String>,
+    // the items touched since `start_journal`, as they were before
+    journal: RefCell<Option<Snapshot>>,
+}
+
+impl JiraDatabase {
+    pub fn new(file_path: String) -> Self {
+        Self::with_database(
+            Box::new(JSONFileDatabase::new(file_path)),
+            Workflow::default(),
+        )
+    }
+
+    pub fn new_sqlite(file_path: String) -> Result<Self> {
+        Ok(Self::with_database(
+            Box::new(SqliteDatabase::open(&file_path)?),
+            Workflow::default(),
+        ))
+    }
+
+    pub fn with_database(database: Box<dyn Database>, workflow: Workflow) -> Self {
+        JiraDatabase {
+            database,
+            workflow,
+            author: None,
+            journal: RefCell::new(None),
+        }
+    }
+
+    /// Opens the board, creating its directory and an empty database on first run.
+    pub fn open(location: &BoardLocation) -> Result<Self> {
+        if let Some(parent) = location.path.parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent)?;
+            }
+        }
+
+        let file_path = location
+            .path
+            .to_str()
+            .ok_or_else(|| anyhow!("invalid database path {}", location.path.display()))?
+            .to_owned();
+
+        let database: Box<dyn Database> = match location.backend {
+            Backend::Json => {
+                let database = JSONFileDatabase::new(file_path);
+                database.create_if_missing()?;
+                database.upgrade()?;
+
+                Box::new(database)
+            }
+            Backend::Sqlite => Box::new(SqliteDatabase::open(&file_path)?),
+        };
+
+        let mut db = Self::with_database(database, location.workflow.clone());
+        db.author = location.author.clone();
+
+        Ok(db)
+    }
+
+    /// The whole board, including the items in the trash.
+    pub fn read_db(&self) -> Result<DBState> {
+        self.database.read_db()
+    }
+
+    // Reads below leave out the items in the trash, see `list_trash`.
+
+    pub fn get_epic(&self, epic_id: u32) -> Result<Option<Epic>> {
+        let Some(epic) = self.database.get_epic(epic_id)? else {
+            return Ok(None);
+        };
+
+        if epic.deleted_at.is_some() {
+            return Ok(None);
+        }
+
+        Ok(Some(self.without_trashed_stories(epic_id, epic)?))
+    }
+
+    pub fn get_story(&self, story_id: u32) -> Result<Option<Story>> {
+        let Some(story) = self.database.get_story(story_id)? else {
+            return Ok(None);
+        };
+
+        if story.deleted_at.is_some() || self.is_epic_trashed(self.epic_id_of_story(story_id)?)? {
+            return Ok(None);
+        }
+
+        Ok(Some(story))
+    }
+
+    pub fn list_epics(&self) -> Result<Vec<(u32, Epic)>> {
+        self.database
+            .list_epics()?
+            .into_iter()
+            .filter(|(_, epic)| epic.deleted_at.is_none())
+            .map(|(epic_id, epic)| Ok((epic_id, self.without_trashed_stories(epic_id, epic)?)))
+            .collect()
+    }
+
+    pub fn list_stories_of_epic(&self, epic_id: u32) -> Result<Vec<(u32, Story)>> {
+        self.find_epic(epic_id)?;
+
+        Ok(self
+            .database
+            .list_stories_of_epic(epic_id)?
+            .into_iter()
+            .filter(|(_, story)| story.deleted_at.is_none())
+            .collect())
+    }
+
+    /// Epic holding the story, also for stories in the trash.
+    pub fn epic_id_of_story(&self, story_id: u32) -> Result<u32> {
+        self.database
+            .list_epics()?
+            .into_iter()
+            .find(|(_, epic)| epic.stories.contains(&story_id))
+            .map(|(epic_id, _)| epic_id)
+            .ok_or_else(|| anyhow!("Story of id {} not found", story_id))
+    }
+
+    /// Epics and stories matching all terms of `query`, see `search::search`.
+    pub fn search(&self, query: &str) -> Result<Vec<SearchHit>> {
+        Ok(search::search(
+            &without_trash(self.database.read_db()?),
+            query,
+        ))
+    }
+
+    /// The board without the trash, or only one epic with its stories, e.g. to export it.
+    /// The history and comments are kept for the items left.
+    pub fn export_state(&self, epic_id: Option<u32>) -> Result<DBState> {
+        let mut db_state = without_trash(self.database.read_db()?);
+
+        if let Some(epic_id) = epic_id {
+            let epic = db_state
+                .epics
+                .remove(&epic_id)
+                .ok_or_else(|| anyhow!("Epic of id {} not found", epic_id))?;
+
+            db_state
+                .stories
+                .retain(|story_id, _| epic.stories.contains(story_id));
+            db_state.epics = [(epic_id, epic)].into();
+        }
+
+        db_state.history.retain(|entry| {
+            db_state.epics.contains_key(&entry.item_id)
+                || db_state.stories.contains_key(&entry.item_id)
+        });
+        db_state
+            .comments
+            .retain(|comment| db_state.stories.contains_key(&comment.story_id));
+        db_state.links.retain(|link| {
+            db_state.stories.contains_key(&link.from) && db_state.stories.contains_key(&link.to)
+        });
+
+        Ok(db_state)
+    }
+
+    /// Problems of the stored board, including the trash, see `fsck::check`.
+    pub fn check(&self) -> Result<Vec<Problem>> {
+        Ok(fsck::check(&self.database.read_db()?))
+    }
+
+    /// Fixes the problems found by `check` as described in `fsck::repair`, and
+    /// returns them.
+    pub fn repair(&self) -> Result<Vec<Problem>> {
+        self.change(|| {
+            let db_state = self.database.read_db()?;
+            let problems = fsck::check(&db_state);
+
+            if !problems.is_empty() {
+                let repaired = fsck::repair(&db_state);
+
+                if let Some(revision) = Revision::between(&db_state, &repaired) {
+                    self.note(&revision.before)?;
+                }
+
+                self.database.write_db(&repaired)?;
+            }
+
+            Ok(problems)
+        })
+    }
+
+    /// Change log of an epic or story, oldest first.
+    pub fn history_of(&self, item_id: u32) -> Result<Vec<HistoryEntry>> {
+        self.database.history_of(item_id)
+    }
+
+    /// Comments on a story, oldest first.
+    pub fn comments_of(&self, story_id: u32) -> Result<Vec<Comment>> {
+        self.database.comments_of(story_id)
+    }
+
+    /// Comments on a story in the name of `author`.
+    pub fn add_comment(&self, story_id: u32, text: String) -> Result<Comment> {
+        self.change(|| {
+            let text = text.trim().to_owned();
+
+            if text.is_empty() {
+                return Err(anyhow!("Comment can't be empty"));
+            }
+
+            self.find_story(story_id)?;
+
+            let comment = Comment {
+                story_id,
+                timestamp: Utc::now(),
+                author: self.author(),
+                text,
+            };
+            self.note(&Snapshot::of_comment(&comment))?;
+            self.database.append_comment(&comment)?;
+
+            Ok(comment)
+        })
+    }
+
+    pub fn create_epic(&self, epic: Epic) -> Result<u32> {
+        self.change(|| self.add_epic(epic))
+    }
+
+    pub fn create_story(&self, story: Story, epic_id: u32) -> Result<u32> {
+        self.change(|| self.add_story(story, epic_id))
+    }
+
+    /// Creates `epics` and then `stories` as one change, so that either all of them
+    /// are created or, if one fails, none. Returns the new ids of both, in order.
+    /// Epics whose stories are all done are resolved as after a status change.
+    pub fn create_items(
+        &self,
+        epics: Vec<Epic>,
+        stories: Vec<(EpicRef, Story)>,
+    ) -> Result<(Vec<u32>, Vec<u32>)> {
+        self.change(|| {
+            let mut epic_ids = vec![];
+            let mut story_ids = vec![];
+            // epics that got stories, which may be resolved now
+            let mut touched = vec![];
+
+            for epic in epics {
+                epic_ids.push(self.add_epic(epic)?);
+            }
+
+            for (epic, story) in stories {
+                let epic_id = match epic {
+                    EpicRef::New(index) => *epic_ids
+                        .get(index)
+                        .ok_or_else(|| anyhow!("no new epic number {}", index))?,
+                    EpicRef::Existing(epic_id) => epic_id,
+                };
+
+                story_ids.push(self.add_story(story, epic_id)?);
+                touched.push(epic_id);
+            }
+
+            for epic_id in touched.into_iter().unique() {
+                self.auto_resolve_epic(epic_id)?;
+            }
+
+            Ok((epic_ids, story_ids))
+        })
+    }
+
+    /// Moves the epic, and with it its stories, to the trash.
+    pub fn delete_epic(&self, epic_id: u32) -> Result<()> {
+        self.change(|| {
+            let mut epic = self.find_epic(epic_id)?;
+            epic.deleted_at = Some(Utc::now());
+
+            self.save_epic(epic_id, epic, vec![Change::Trashed])
+        })
+    }
+
+    /// Moves the story to the trash.
+    pub fn delete_story(&self, epic_id: u32, story_id: u32) -> Result<()> {
+        self.change(|| {
+            self.find_epic(epic_id)?;
+            let mut story = self.find_story(story_id)?;
+
+            if self.epic_id_of_story(story_id)? != epic_id {
+                return Err(anyhow!(
+                    "Story of id {} does not belong to epic {}",
+                    story_id,
+                    epic_id
+                ));
+            }
+
+            story.deleted_at = Some(Utc::now());
+            self.save_story(story_id, story, vec![Change::Trashed])?;
+
+            self.auto_resolve_epic(epic_id)
+        })
+    }
+
+    /// Epics and stories in the trash, sorted by id. Stories of a trashed epic are
+    /// not listed on their own, they come back with it.
+    pub fn list_trash(&self) -> Result<Vec<TrashedItem>> {
+        let db_state = self.database.read_db()?;
+        let mut items = vec![];
+
+        for (epic_id, epic) in &db_state.epics {
+            if epic.deleted_at.is_some() {
+                items.push(TrashedItem::Epic {
+                    epic_id: *epic_id,
+                    epic: epic.clone(),
+                });
+                continue;
+            }
+
+            for story_id in &epic.stories {
+                let Some(story) = db_state.stories.get(story_id) else {
+                    continue;
+                };
+
+                if story.deleted_at.is_some() {
+                    items.push(TrashedItem::Story {
+                        epic_id: *epic_id,
+                        story_id: *story_id,
+                        story: story.clone(),
+                    });
+                }
+            }
+        }
+
+        items.sort_by_key(TrashedItem::id);
+        Ok(items)
+    }
+
+    /// Takes an epic or story out of the trash. A story can only be restored while
+    /// its epic isn't in the trash itself.
+    pub fn restore_from_trash(&self, item_id: u32) -> Result<()> {
+        self.change(|| match self.find_trashed(item_id)? {
+            TrashedItem::Epic { epic_id, mut epic } => {
+                epic.deleted_at = None;
+                self.save_epic(epic_id, epic, vec![Change::Restored])
+            }
+            TrashedItem::Story {
+                epic_id,
+                story_id,
+                mut story,
+            } => {
+                story.deleted_at = None;
+                self.save_story(story_id, story, vec![Change::Restored])?;
+
+                self.auto_resolve_epic(epic_id)
+            }
+        })
+    }
+
+    /// Deletes an epic or story in the trash for good.
+    pub fn purge_from_trash(&self, item_id: u32) -> Result<()> {
+        self.change(|| self.purge(item_id))
+    }
+
+    /// Deletes everything that was moved to the trash at or before `cutoff` for good,
+    /// returning the ids of the purged items.
+    pub fn purge_trash(&self, cutoff: DateTime<Utc>) -> Result<Vec<u32>> {
+        self.change(|| {
+            let mut purged = vec![];
+
+            for item in self.list_trash()? {
+                if item
+                    .deleted_at()
+                    .is_some_and(|deleted_at| deleted_at <= cutoff)
+                {
+                    self.purge(item.id())?;
+                    purged.push(item.id());
+                }
+            }
+
+            Ok(purged)
+        })
+    }
+
+    pub fn move_story(&self, story_id: u32, from_epic_id: u32, to_epic_id: u32) -> Result<()> {
+        self.change(|| {
+            let story = self.find_story(story_id)?;
+
+            if !self.find_epic(from_epic_id)?.stories.contains(&story_id) {
+                return Err(anyhow!(
+                    "Story of id {} does not belong to epic {}",
+                    story_id,
+                    from_epic_id
+                ));
+            }
+
+            self.find_epic(to_epic_id)?;
+
+            if from_epic_id == to_epic_id {
+                return Err(anyhow!(
+                    "Story of id {} is already in epic {}",
+                    story_id,
+                    to_epic_id
+                ));
+            }
+
+            self.note(&Snapshot::of_story(story_id))?;
+            self.database.move_story(story_id, to_epic_id)?;
+            self.save_story(
+                story_id,
+                story,
+                vec![Change::Moved {
+                    from_epic_id,
+                    to_epic_id,
+                }],
+            )?;
+
+            self.auto_resolve_epic(from_epic_id)
+        })
+    }
+
+    pub fn update_epic_status(&self, epic_id: u32, status: RecordStatus) -> Result<()> {
+        self.change(|| {
+            let mut epic = self.find_epic(epic_id)?;
+            let status = self.workflow.resolve(status);
+            self.workflow.check_transition(&epic.status, &status)?;
+
+            if self.workflow.is_done(&status) && !self.workflow.allow_unfinished_epics {
+                let (done, total) = self.epic_progress(epic_id)?;
+
+                if done < total {
+                    return Err(anyhow!(
+                        "Epic of id {} still has {} unfinished stories, finish them before marking it {}",
+                        epic_id,
+                        total - done,
+                        status
+                    ));
+                }
+            }
+
+            let changes = status_change(&epic.status, &status);
+            epic.status = status;
+
+            self.save_epic(epic_id, epic, changes)
+        })
+    }
+
+    pub fn update_story_status(&self, story_id: u32, status: RecordStatus) -> Result<()> {
+        self.change(|| {
+            let mut story = self.find_story(story_id)?;
+            let status = self.workflow.resolve(status);
+            self.workflow.check_transition(&story.status, &status)?;
+
+            let changes = status_change(&story.status, &status);
+            story.status = status;
+
+            self.save_story(story_id, story, changes)?;
+
+            self.auto_resolve_epic(self.epic_id_of_story(story_id)?)
+        })
+    }
+
+    /// Statuses the epic can be moved to right now: the workflow's next statuses,
+    /// minus the finished ones while some of its stories aren't.
+    pub fn next_epic_statuses(&self, epic_id: u32) -> Result<Vec<RecordStatus>> {
+        let epic = self.find_epic(epic_id)?;
+        let (done, total) = self.epic_progress(epic_id)?;
+        let unfinished = done < total && !self.workflow.allow_unfinished_epics;
+
+        Ok(self
+            .workflow
+            .next_statuses(&epic.status)
+            .into_iter()
+            .filter(|status| !(unfinished && self.workflow.is_done(status)))
+            .collect())
+    }
+
+    /// Number of finished stories of the epic, and of all its stories.
+    pub fn epic_progress(&self, epic_id: u32) -> Result<(usize, usize)> {
+        let stories = self.list_stories_of_epic(epic_id)?;
+        let done = stories
+            .iter()
+            .filter(|(_, story)| self.workflow.is_done(&story.status))
+            .count();
+
+        Ok((done, stories.len()))
+    }
+
+    pub fn update_epic(&self, epic_id: u32, name: String, description: String) -> Result<()> {
+        self.edit_epic(epic_id, |epic| {
+            epic.name = name;
+            epic.description = description;
+        })
+    }
+
+    pub fn update_story(&self, story_id: u32, name: String, description: String) -> Result<()> {
+        self.edit_story(story_id, |story| {
+            story.name = name;
+            story.description = description;
+        })
+    }
+
+    pub fn update_epic_labels(&self, epic_id: u32, labels: Vec<String>) -> Result<()> {
+        self.edit_epic(epic_id, |epic| epic.labels = labels)
+    }
+
+    pub fn update_story_details(
+        &self,
+        story_id: u32,
+        priority: Option<Priority>,
+        assignee: Option<String>,
+        labels: Vec<String>,
+        estimate: Option<u32>,
+    ) -> Result<()> {
+        self.edit_story(story_id, |story| {
+            story.priority = priority;
+            story.assignee = assignee;
+            story.labels = labels;
+            story.estimate = estimate;
+        })
+    }
+
+    /// Changes the name, description and labels of an epic in one go, e.g.
+    /// `edit_epic(1, |epic| epic.labels.clear())`. Edits of its other fields are ignored.
+    pub fn edit_epic(&self, epic_id: u32, edit: impl FnOnce(&mut Epic)) -> Result<()> {
+        self.change(|| {
+            let epic = self.find_epic(epic_id)?;
+            let mut edited = epic.clone();
+            edit(&mut edited);
+
+            let changes = [
+                field_change("name", &epic.name, &edited.name),
+                field_change("description", &epic.description, &edited.description),
+                field_change("labels", &epic.labels.join(", "), &edited.labels.join(", ")),
+            ]
+            .into_iter()
+            .flatten()
+            .collect();
+
+            let epic = Epic {
+                name: edited.name,
+                description: edited.description,
+                labels: edited.labels,
+                ..epic
+            };
+
+            self.save_epic(epic_id, epic, changes)
+        })
+    }
+
+    /// Changes the name, description, priority, assignee, labels and estimate of a
+    /// story in one go. Edits of its other fields are ignored.
+    pub fn edit_story(&self, story_id: u32, edit: impl FnOnce(&mut Story)) -> Result<()> {
+        self.change(|| {
+            let story = self.find_story(story_id)?;
+            let mut edited = story.clone();
+            edit(&mut edited);
+
+            let changes = [
+                field_change("name", &story.name, &edited.name),
+                field_change("description", &story.description, &edited.description),
+                field_change(
+                    "priority",
+                    &optional_to_string(story.priority),
+                    &optional_to_string(edited.priority),
+                ),
+                field_change(
+                    "assignee",
+                    &optional_to_string(story.assignee.as_ref()),
+                    &optional_to_string(edited.assignee.as_ref()),
+                ),
+                field_change(
+                    "labels",
+                    &story.labels.join(", "),
+                    &edited.labels.join(", "),
+                ),
+                field_change(
+                    "estimate",
+                    &optional_to_string(story.estimate),
+                    &optional_to_string(edited.estimate),
+                ),
+            ]
+            .into_iter()
+            .flatten()
+            .collect();
+
+            let story = Story {
+                name: edited.name,
+                description: edited.description,
+                priority: edited.priority,
+                assignee: edited.assignee,
+                labels: edited.labels,
+                estimate: edited.estimate,
+                ..story
+            };
+
+            self.save_story(story_id, story, changes)
+        })
+    }
+
+    /// Adds an item to the end of a story's checklist and returns its index.
+    pub fn add_checklist_item(&self, story_id: u32, text: String) -> Result<usize> {
+        self.change(|| {
+            let text = text.trim().to_owned();
+
+            if text.is_empty() {
+                return Err(anyhow!("Checklist item can't be empty"));
+            }
+
+            let mut story = self.find_story(story_id)?;
+            let changes = vec![Change::ChecklistItemAdded { text: text.clone() }];
+            story.checklist.push(ChecklistItem::new(text));
+            let index = story.checklist.len() - 1;
+
+            self.save_story(story_id, story, changes)?;
+            Ok(index)
+        })
+    }
+
+    /// Ticks off the item at `index` of a story's checklist, or unticks it if it was
+    /// done, and returns whether it is done now.
+    pub fn toggle_checklist_item(&self, story_id: u32, index: usize) -> Result<bool> {
+        self.change(|| {
+            let mut story = self.find_story(story_id)?;
+            let item = story
+                .checklist
+                .get_mut(index)
+                .ok_or_else(|| checklist_item_not_found(story_id, index))?;
+            item.done = !item.done;
+
+            let done = item.done;
+            let changes = vec![Change::ChecklistItemToggled {
+                text: item.text.clone(),
+                done,
+            }];
+
+            self.save_story(story_id, story, changes)?;
+            Ok(done)
+        })
+    }
+
+    pub fn remove_checklist_item(&self, story_id: u32, index: usize) -> Result<()> {
+        self.change(|| {
+            let mut story = self.find_story(story_id)?;
+
+            if index >= story.checklist.len() {
+                return Err(checklist_item_not_found(story_id, index));
+            }
+
+            let item = story.checklist.remove(index);
+            let changes = vec![Change::ChecklistItemRemoved { text: item.text }];
+
+            self.save_story(story_id, story, changes)
+        })
+    }
+
+    /// Stories linked to a story, with each link read from `story_id`, e.g. "blocked
+    /// by 12". Links to stories in the trash are left out while they are there.
+    pub fn links_of(&self, story_id: u32) -> Result<Vec<LinkedStory>> {
+        let mut linked = vec![];
+
+        for link in self.database.list_links()? {
+            let Some((kind, other_id)) = link.seen_from(story_id) else {
+                continue;
+            };
+
+            if let Some(story) = self.get_story(other_id)? {
+                linked.push(LinkedStory {
+                    kind,
+                    story_id: other_id,
+                    story,
+                });
+            }
+        }
+
+        linked.sort_by_key(|linked| (linked.kind, linked.story_id));
+        Ok(linked)
+    }
+
+    /// Unfinished stories blocking `story_id`, if setting it to `status` starts it.
+    /// Starting a blocked story is allowed, callers only warn about it.
+    pub fn blockers_on_start(
+        &self,
+        story_id: u32,
+        status: RecordStatus,
+    ) -> Result<Vec<(u32, Story)>> {
+        let story = self.find_story(story_id)?;
+
+        if self.workflow.resolve(status) != RecordStatus::InProgress
+            || story.status == RecordStatus::InProgress
+        {
+            return Ok(vec![]);
+        }
+
+        Ok(self
+            .links_of(story_id)?
+            .into_iter()
+            .filter(|linked| {
+                linked.kind == LinkKind::BlockedBy && !self.workflow.is_done(&linked.story.status)
+            })
+            .map(|linked| (linked.story_id, linked.story))
+            .collect())
+    }
+
+    /// Fails with the reason `link_stories` would refuse the link, if it would.
+    pub fn check_link(&self, story_id: u32, kind: LinkKind, other_id: u32) -> Result<()> {
+        if story_id == other_id {
+            return Err(anyhow!(
+                "Story of id {} can't be linked to itself",
+                story_id
+            ));
+        }
+
+        self.find_story(story_id)?;
+        self.find_story(other_id)?;
+
+        let link = StoryLink::new(story_id, kind, other_id);
+        let links = self.database.list_links()?;
+
+        if links.contains(&link) {
+            return Err(anyhow!(
+                "Link \"{} {} {}\" already exists",
+                story_id,
+                kind,
+                other_id
+            ));
+        }
+
+        if link.kind != LinkKind::RelatesTo && reaches(&links, link.kind, link.to, link.from) {
+            return Err(anyhow!(
+                "Link \"{} {} {}\" would make a cycle",
+                story_id,
+                kind,
+                other_id
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Links two stories, e.g. `link_stories(12, LinkKind::Blocks, 15)`. A story can't
+    /// block or duplicate itself, not even through other stories.
+    pub fn link_stories(&self, story_id: u32, kind: LinkKind, other_id: u32) -> Result<()> {
+        self.change(|| {
+            self.check_link(story_id, kind, other_id)?;
+
+            let link = StoryLink::new(story_id, kind, other_id);
+            self.note(&Snapshot::of_link(&link))?;
+            self.database.insert_link(&link)?;
+
+            self.record(
+                story_id,
+                vec![Change::Linked {
+                    kind,
+                    story_id: other_id,
+                }],
+            )?;
+            self.record(
+                other_id,
+                vec![Change::Linked {
+                    kind: kind.inverse(),
+                    story_id,
+                }],
+            )
+        })
+    }
+
+    pub fn unlink_stories(&self, story_id: u32, kind: LinkKind, other_id: u32) -> Result<()> {
+        self.change(|| {
+            let link = StoryLink::new(story_id, kind, other_id);
+            self.note(&Snapshot::of_link(&link))?;
+            self.database.delete_link(&link)?;
+
+            self.record(
+                story_id,
+                vec![Change::Unlinked {
+                    kind,
+                    story_id: other_id,
+                }],
+            )?;
+            self.record(
+                other_id,
+                vec![Change::Unlinked {
+                    kind: kind.inverse(),
+                    story_id,
+                }],
+            )
+        })
+    }
+
+    /// Puts the items of `target` back the way they are there, deleting the ones that
+    /// didn't exist. Fails without changing anything if the items no longer match
+    /// `expected`, e.g. because another process edited them in the meantime.
+    pub fn restore(&self, expected: &Snapshot, target: &Snapshot, change: Change) -> Result<()> {
+        self.change(|| {
+            // from here on, `expected` tells what's there
+            if self.read_items(expected)? != *expected {
+                return Err(anyhow!(
+                    "The board was changed in the meantime, the change can't be reverted"
+                ));
+            }
+
+            for (comment, present) in &target.comments {
+                if !present && expected.comments.get(comment) == Some(&true) {
+                    self.database.delete_comment(comment)?;
+                }
+            }
+
+            // before the stories, whose deletion would take the links along
+            for (link, present) in &target.links {
+                if !present && expected.links.get(link) == Some(&true) {
+                    self.database.delete_link(link)?;
+                }
+            }
+
+            for (story_id, story) in &target.stories {
+                if story.is_none() && matches!(expected.stories.get(story_id), Some(Some(_))) {
+                    self.database.delete_story(*story_id)?;
+                }
+            }
+
+            for (epic_id, epic) in &target.epics {
+                let exists = matches!(expected.epics.get(epic_id), Some(Some(_)));
+
+                match epic {
+                    Some(epic) if exists => self.database.update_epic(*epic_id, epic)?,
+                    Some(epic) => self.database.insert_epic(*epic_id, epic)?,
+                    None if exists => self.database.delete_epic(*epic_id)?,
+                    None => {}
+                }
+            }
+
+            for (story_id, story) in &target.stories {
+                let Some((epic_id, story)) = story else {
+                    continue;
+                };
+
+                match expected.stories.get(story_id) {
+                    Some(Some((current_epic_id, _))) => {
+                        self.database.update_story(*story_id, story)?;
+
+                        if current_epic_id != epic_id {
+                            self.database.move_story(*story_id, *epic_id)?;
+                        }
+                    }
+                    _ => self.database.insert_story(*epic_id, *story_id, story)?,
+                }
+            }
+
+            for (comment, present) in &target.comments {
+                if *present && expected.comments.get(comment) != Some(&true) {
+                    self.database.append_comment(comment)?;
+                }
+            }
+
+            for (link, present) in &target.links {
+                if *present && expected.links.get(link) != Some(&true) {
+                    self.database.insert_link(link)?;
+                }
+            }
+
+            for item_id in target.item_ids() {
+                self.record(item_id, vec![change.clone()])?;
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Name recorded as the author of changes: `self.author` unless it's empty, else
+    /// `CLI_JIRA_USER`, falling back to the login name of the current user.
+    pub fn author(&self) -> String {
+        if let Some(author) = self
+            .author
+            .as_ref()
+            .filter(|author| !author.trim().is_empty())
+        {
+            return author.clone();
+        }
+
+        ["CLI_JIRA_USER", "USER", "USERNAME"]
+            .into_iter()
+            .find_map(|key| env::var(key).ok().filter(|value| !value.is_empty()))
+            .unwrap_or_else(|| "unknown".to_owned())
+    }
+
+    /// Starts remembering the epics, stories, comments and links that the following
+    /// changes touch, as they are now, so that `take_revision` can tell how to revert
+    /// them without reading the whole board.
+    pub fn start_journal(&self) {
+        *self.journal.borrow_mut() = Some(Snapshot::default());
+    }
+
+    /// Stops the journal and returns the items touched since `start_journal`, before
+    /// and after; `None` if nothing changed or no journal was running.
+    pub fn take_revision(&self) -> Result<Option<Revision>> {
+        let Some(before) = self.journal.borrow_mut().take() else {
+            return Ok(None);
+        };
+
+        let after = self.read_items(&before)?;
+        Ok(Revision::new(before, after))
+    }
+
+    /// The same epics, stories, comments and links as in `items`, as they are stored
+    /// now; reads only those.
+    pub fn read_items(&self, items: &Snapshot) -> Result<Snapshot> {
+        let epics = if items.stories.is_empty() {
+            vec![]
+        } else {
+            self.database.list_epics()?
+        };
+        let links = if items.links.is_empty() {
+            vec![]
+        } else {
+            self.database.list_links()?
+        };
+
+        let mut current = Snapshot::default();
+
+        for &epic_id in items.epics.keys() {
+            let epic = self.database.get_epic(epic_id)?.map(|epic| Epic {
+                stories: vec![],
+                ..epic
+            });
+            current.epics.insert(epic_id, epic);
+        }
+
+        for &story_id in items.stories.keys() {
+            let story = self.database.get_story(story_id)?.and_then(|story| {
+                let (epic_id, _) = epics
+                    .iter()
+                    .find(|(_, epic)| epic.stories.contains(&story_id))?;
+                Some((*epic_id, story))
+            });
+            current.stories.insert(story_id, story);
+        }
+
+        for comment in items.comments.keys() {
+            let present = self
+                .database
+                .comments_of(comment.story_id)?
+                .contains(comment);
+            current.comments.insert(comment.clone(), present);
+        }
+
+        for link in items.links.keys() {
+            current.links.insert(link.clone(), links.contains(link));
+        }
+
+        Ok(current)
+    }
+
+    // Adds the items to the journal as they are before being changed, unless they
+    // were touched before or no journal is running.
+    fn note(&self, items: &Snapshot) -> Result<()> {
+        if self.journal.borrow().is_none() {
+            return Ok(());
+        }
+
+        let current = self.read_items(items)?;
+
+        if let Some(journal) = self.journal.borrow_mut().as_mut() {
+            journal.add_missing(current);
+        }
+
+        Ok(())
+    }
+
+    // Notes the story together with its links, which are deleted with it.
+    fn note_story_with_links(&self, story_id: u32) -> Result<()> {
+        let mut items = Snapshot::of_story(story_id);
+
+        for link in self.database.list_links()? {
+            if link.from == story_id || link.to == story_id {
+                items.links.insert(link, false);
+            }
+        }
+
+        self.note(&items)
+    }
+
+    // Runs `operation` under the database lock as one transaction, so that the board
+    // is written once, and not at all if the operation fails halfway.
+    fn change<T>(&self, operation: impl FnOnce() -> Result<T>) -> Result<T> {
+        let _lock = self.database.lock_db()?;
+
+        let mut operation = Some(operation);
+        let mut output = None;
+
+        self.database.transaction(&mut || {
+            if let Some(operation) = operation.take() {
+                output = Some(operation()?);
+            }
+
+            Ok(())
+        })?;
+
+        output.ok_or_else(|| anyhow!("the transaction didn't run its operation"))
+    }
+
+    fn add_epic(&self, epic: Epic) -> Result<u32> {
+        let now = Some(Utc::now());
+        let epic = Epic {
+            status: self.initial_status(epic.status),
+            created_at: now,
+            updated_at: now,
+            ..epic
+        };
+
+        let new_id = self.database.next_item_id()?;
+        self.note(&Snapshot::of_epic(new_id))?;
+        self.database.insert_epic(new_id, &epic)?;
+        self.record(new_id, vec![Change::Created])?;
+
+        Ok(new_id)
+    }
+
+    fn add_story(&self, story: Story, epic_id: u32) -> Result<u32> {
+        self.find_epic(epic_id)?;
+
+        let now = Some(Utc::now());
+        let story = Story {
+            status: self.initial_status(story.status),
+            created_at: now,
+            updated_at: now,
+            ..story
+        };
+
+        let new_id = self.database.next_item_id()?;
+        self.note(&Snapshot::of_story(new_id))?;
+        self.database.insert_story(epic_id, new_id, &story)?;
+        self.record(new_id, vec![Change::Created])?;
+
+        Ok(new_id)
+    }
+
+    fn purge(&self, item_id: u32) -> Result<()> {
+        match self.find_trashed(item_id)? {
+            TrashedItem::Epic { epic_id, epic } => {
+                self.note(&Snapshot::of_epic(epic_id))?;
+
+                for story_id in epic.stories {
+                    self.note_story_with_links(story_id)?;
+                }
+
+                self.database.delete_epic(epic_id)
+            }
+            TrashedItem::Story { story_id, .. } => {
+                self.note_story_with_links(story_id)?;
+                self.database.delete_story(story_id)
+            }
+        }
+    }
+
+    // New items start in the workflow's first status unless created with another
+    // status that the workflow knows about.
+    fn initial_status(&self, status: RecordStatus) -> RecordStatus {
+        let status = self.workflow.resolve(status);
+
+        if self.workflow.statuses.contains(&status) {
+            status
+        } else {
+            self.workflow.initial_status()
+        }
+    }
+
+    // With `auto_resolve_epics`, finishes the epic once its last story is done.
+    fn auto_resolve_epic(&self, epic_id: u32) -> Result<()> {
+        if !self.workflow.auto_resolve_epics {
+            return Ok(());
+        }
+
+        let mut epic = self.find_epic(epic_id)?;
+        let (done, total) = self.epic_progress(epic_id)?;
+
+        if total == 0 || done < total || self.workflow.is_done(&epic.status) {
+            return Ok(());
+        }
+
+        let Some(status) = self.workflow.auto_resolve_status(&epic.status) else {
+            return Ok(());
+        };
+
+        let changes = status_change(&epic.status, &status);
+        epic.status = status;
+
+        self.save_epic(epic_id, epic, changes)
+    }
+
+    // Stores the edited epic and records `changes`; does nothing if there are none,
+    // so that no-op edits don't bump `updated_at`.
+    fn save_epic(&self, epic_id: u32, mut epic: Epic, changes: Vec<Change>) -> Result<()> {
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        epic.updated_at = Some(Utc::now());
+
+        self.note(&Snapshot::of_epic(epic_id))?;
+        self.database.update_epic(epic_id, &epic)?;
+        self.record(epic_id, changes)
+    }
+
+    fn save_story(&self, story_id: u32, mut story: Story, changes: Vec<Change>) -> Result<()> {
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        story.updated_at = Some(Utc::now());
+
+        self.note(&Snapshot::of_story(story_id))?;
+        self.database.update_story(story_id, &story)?;
+        self.record(story_id, changes)
+    }
+
+    fn record(&self, item_id: u32, changes: Vec<Change>) -> Result<()> {
+        let timestamp = Utc::now();
+        let author = self.author();
+
+        for change in changes {
+            self.database.append_history(&HistoryEntry {
+                item_id,
+                timestamp,
+                author: author.clone(),
+                change,
+            })?;
+        }
+
+        Ok(())
+    }
+
+    fn find_epic(&self, epic_id: u32) -> Result<Epic> {
+        self.get_epic(epic_id)?
+            .ok_or_else(|| anyhow!("Epic of id {} not found", epic_id))
+    }
+
+    fn find_story(&self, story_id: u32) -> Result<Story> {
+        self.get_story(story_id)?
+            .ok_or_else(|| anyhow!("Story of id {} not found", story_id))
+    }
+
+    fn find_trashed(&self, item_id: u32) -> Result<TrashedItem> {
+        if let Some(epic) = self.database.get_epic(item_id)? {
+            if epic.deleted_at.is_some() {
+                return Ok(TrashedItem::Epic {
+                    epic_id: item_id,
+                    epic,
+                });
+            }
+        } else if let Some(story) = self.database.get_story(item_id)? {
+            let epic_id = self.epic_id_of_story(item_id)?;
+
+            if self.is_epic_trashed(epic_id)? {
+                return Err(anyhow!(
+                    "Story of id {} belongs to epic {} which is in the trash, restore or purge the epic instead",
+                    item_id,
+                    epic_id
+                ));
+            }
+
+            if story.deleted_at.is_some() {
+                return Ok(TrashedItem::Story {
+                    epic_id,
+                    story_id: item_id,
+                    story,
+                });
+            }
+        }
+
+        Err(anyhow!("Item of id {} is not in the trash", item_id))
+    }
+
+    fn is_epic_trashed(&self, epic_id: u32) -> Result<bool> {
+        Ok(self
+            .database
+            .get_epic(epic_id)?
+            .is_some_and(|epic| epic.deleted_at.is_some()))
+    }
+
+    // `Epic::stories` keeps the ids of trashed stories so they can be restored
+    fn without_trashed_stories(&self, epic_id: u32, mut epic: Epic) -> Result<Epic> {
+        let trashed: Vec<u32> = self
+            .database
+            .list_stories_of_epic(epic_id)?
+            .into_iter()
+            .filter(|(_, story)| story.deleted_at.is_some())
+            .map(|(story_id, _)| story_id)
+            .collect();
+
+        epic.stories.retain(|story_id| !trashed.contains(story_id));
+        Ok(epic)
+    }
+}
+
+// Drops trashed epics with all their stories, and trashed stories
+fn without_trash(mut db_state: DBState) -> DBState {
+    let trashed_epics: Vec<u32> = db_state
+        .epics
+        .iter()
+        .filter(|(_, epic)| epic.deleted_at.is_some())
+        .map(|(epic_id, _)| *epic_id)
+        .collect();
+
+    for epic_id in trashed_epics {
+        if let Some(epic) = db_state.epics.remove(&epic_id) {
+            for story_id in epic.stories {
+                db_state.stories.remove(&story_id);
+            }
+        }
+    }
+
+    db_state
+        .stories
+        .retain(|_, story| story.deleted_at.is_none());
+
+    for epic in db_state.epics.values_mut() {
+        epic.stories
+            .retain(|story_id| db_state.stories.contains_key(story_id));
+    }
+
+    db_state
+}
+
+/// Epic of a story created by `JiraDatabase::create_items`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum EpicRef {
+    /// One of the epics created along with the story, by index
+    New(usize),
+    Existing(u32),
+}
+
+// Whether `to` can be reached from `from` by following links of `kind`
+fn reaches(links: &[StoryLink], kind: LinkKind, from: u32, to: u32) -> bool {
+    let mut seen = vec![from];
+    let mut pending = vec![from];
+
+    while let Some(story_id) = pending.pop() {
+        if story_id == to {
+            return true;
+        }
+
+        for link in links {
+            if link.kind == kind && link.from == story_id && !seen.contains(&link.to) {
+                seen.push(link.to);
+                pending.push(link.to);
+            }
+        }
+    }
+
+    false
+}
+
+// Items are numbered from 1 wherever they are shown
+fn checklist_item_not_found(story_id: u32, index: usize) -> anyhow::Error {
+    anyhow!(
+        "Checklist item {} of story {} not found",
+        index + 1,
+        story_id
+    )
+}
+
+fn comment_not_found(comment: &Comment) -> anyhow::Error {
+    anyhow!(
+        "Comment of {} on story {} not found",
+        comment.author,
+        comment.story_id
+    )
+}
+
+fn link_not_found(link: &StoryLink) -> anyhow::Error {
+    anyhow!("Link \"{} {} {}\" not found", link.from, link.kind, link.to)
+}
+
+fn status_change(from: &RecordStatus, to: &RecordStatus) -> Vec<Change> {
+    if from == to {
+        return vec![];
+    }
+
+    vec![Change::StatusChanged {
+        from: from.clone(),
+        to: to.clone(),
+    }]
+}
+
+fn field_change(field: &str, from: &str, to: &str) -> Option<Change> {
+    (from != to).then(|| Change::FieldChanged {
+        field: field.to_owned(),
+        from: from.to_owned(),
+        to: to.to_owned(),
+    })
+}
+
+fn optional_to_string<T: ToString>(value: Option<T>) -> String {
+    value.map_or(String::new(), |value| value.to_string())
+}
+
+/// Storage backend for the board, addressed one epic or story at a time so that
+/// backends can read and write only what an operation touches.
+pub trait Database {
+    /// Loads the whole board, for views that really need all of it.
+    fn read_db(&self) -> Result<DBState>;
+    /// Replaces the whole board, e.g. with a repaired copy of it.
+    fn write_db(&self, db_state: &DBState) -> Result<()>;
+
+    /// Reserves and returns a new, unused item id.
+    fn next_item_id(&self) -> Result<u32>;
+
+    fn get_epic(&self, epic_id: u32) -> Result<Option<Epic>>;
+    fn get_story(&self, story_id: u32) -> Result<Option<Story>>;
+
+    /// All epics, sorted by id.
+    fn list_epics(&self) -> Result<Vec<(u32, Epic)>>;
+    /// Stories of an epic, sorted by id.
+    fn list_stories_of_epic(&self, epic_id: u32) -> Result<Vec<(u32, Story)>>;
+
+    fn insert_epic(&self, epic_id: u32, epic: &Epic) -> Result<()>;
+    /// Replaces the epic's fields. `Epic::stories` is ignored, membership is managed
+    /// through `insert_story`/`delete_story`.
+    fn update_epic(&self, epic_id: u32, epic: &Epic) -> Result<()>;
+    /// Deletes the epic together with all of its stories and their links.
+    fn delete_epic(&self, epic_id: u32) -> Result<()>;
+
+    /// Inserts the story and appends it to the epic's story list.
+    fn insert_story(&self, epic_id: u32, story_id: u32, story: &Story) -> Result<()>;
+    fn update_story(&self, story_id: u32, story: &Story) -> Result<()>;
+    /// Deletes the story and its links, and removes it from whichever epic holds it.
+    fn delete_story(&self, story_id: u32) -> Result<()>;
+    /// Takes the story out of its epic and appends it to `to_epic_id`'s story list.
+    fn move_story(&self, story_id: u32, to_epic_id: u32) -> Result<()>;
+
+    /// Appends to the change log. Entries are never modified, and are kept when
+    /// their item is deleted.
+    fn append_history(&self, entry: &HistoryEntry) -> Result<()>;
+    /// Change log of an item, oldest first.
+    fn history_of(&self, item_id: u32) -> Result<Vec<HistoryEntry>>;
+
+    /// Appends a comment. Like history entries, comments are kept when their story
+    /// is deleted.
+    fn append_comment(&self, comment: &Comment) -> Result<()>;
+    /// Comments on a story, oldest first.
+    fn comments_of(&self, story_id: u32) -> Result<Vec<Comment>>;
+    /// Removes a comment again, for undoing it.
+    fn delete_comment(&self, comment: &Comment) -> Result<()>;
+
+    /// All links between stories, including those of the trash. Deleting a story
+    /// deletes its links too.
+    fn list_links(&self) -> Result<Vec<StoryLink>>;
+    fn insert_link(&self, link: &StoryLink) -> Result<()>;
+    fn delete_link(&self, link: &StoryLink) -> Result<()>;
+
+    /// Runs `operation` as one change of the board: what it writes is stored once it
+    /// succeeds, and dropped if it fails. Nested calls join the running transaction.
+    fn transaction(&self, operation: &mut dyn FnMut() -> Result<()>) -> Result<()> {
+        operation()
+    }
+
+    /// Takes an exclusive lock around a read-modify-write cycle. The lock is held
+    /// until the returned guard is dropped. Backends that can't be shared don't need it.
+    fn lock_db(&self) -> Result<DBLock> {
+        Ok(DBLock::default())
+    }
+}
+
+/// Backend that can only load and store the whole `DBState` at once. Every such
+/// backend gets a `Database` implementation that does a full read-modify-write per operation,
+/// or per `batch` of them.
+pub trait StateDatabase {
+    fn load_state(&self) -> Result<DBState>;
+    fn save_state(&self, db_state: &DBState) -> Result<()>;
+
+    fn lock_state(&self) -> Result<DBLock> {
+        Ok(DBLock::default())
+    }
+
+    /// Runs `operation` with loads and saves going to one copy of the state in memory,
+    /// which is saved once at the end, see `Database::transaction`. Backends that
+    /// keep the state in memory anyway don't need it.
+    fn batch(&self, operation: &mut dyn FnMut() -> Result<()>) -> Result<()> {
+        operation()
+    }
+}
+
+impl<T: StateDatabase> Database for T {
+    fn read_db(&self) -> Result<DBState> {
+        self.load_state()
+    }
+
+    fn write_db(&self, db_state: &DBState) -> Result<()> {
+        self.save_state(db_state)
+    }
+
+    fn next_item_id(&self) -> Result<u32> {
+        let mut db_state = self.load_state()?;
+
+        db_state.last_item_id += 1;
+
+        self.save_state(&db_state)?;
+        Ok(db_state.last_item_id)
+    }
+
+    fn get_epic(&self, epic_id: u32) -> Result<Option<Epic>> {
+        Ok(self.load_state()?.epics.remove(&epic_id))
+    }
+
+    fn get_story(&self, story_id: u32) -> Result<Option<Story>> {
+        Ok(self.load_state()?.stories.remove(&story_id))
+    }
+
+    fn list_epics(&self) -> Result<Vec<(u32, Epic)>> {
+        Ok(self
+            .load_state()?
+            .epics
+            .into_iter()
+            .sorted_by_key(|(id, _)| *id)
+            .collect())
+    }
+
+    fn list_stories_of_epic(&self, epic_id: u32) -> Result<Vec<(u32, Story)>> {
+        let mut db_state = self.load_state()?;
+        let epic = db_state
+            .epics
+            .get(&epic_id)
+            .ok_or_else(|| anyhow!("Epic of id {} not found", epic_id))?;
+
+        Ok(epic
+            .stories
+            .iter()
+            .sorted()
+            .filter_map(|story_id| {
+                db_state
+                    .stories
+                    .remove(story_id)
+                    .map(|story| (*story_id, story))
+            })
+            .collect())
+    }
+
+    fn insert_epic(&self, epic_id: u32, epic: &Epic) -> Result<()> {
+        let mut db_state = self.load_state()?;
+
+        db_state.epics.insert(epic_id, epic.clone());
+
+        self.save_state(&db_state)
+    }
+
+    fn update_epic(&self, epic_id: u32, epic: &Epic) -> Result<()> {
+        let mut db_state = self.load_state()?;
+
+        let current = db_state
+            .epics
+            .get_mut(&epic_id)
+            .ok_or_else(|| anyhow!("Epic of id {} not found", epic_id))?;
+        let stories = std::mem::take(&mut current.stories);
+
+        *current = Epic {
+            stories,
+            ..epic.clone()
+        };
+
+        self.save_state(&db_state)
+    }
+
+    fn delete_epic(&self, epic_id: u32) -> Result<()> {
+        let mut db_state = self.load_state()?;
+
+        let epic = db_state
+            .epics
+            .remove(&epic_id)
+            .ok_or_else(|| anyhow!("Epic of id {} not found", epic_id))?;
+
+        for story_id in &epic.stories {
+            db_state.stories.remove(story_id);
+        }
+
+        db_state
+            .links
+            .retain(|link| !epic.stories.contains(&link.from) && !epic.stories.contains(&link.to));
+
+        self.save_state(&db_state)
+    }
+
+    fn insert_story(&self, epic_id: u32, story_id: u32, story: &Story) -> Result<()> {
+        let mut db_state = self.load_state()?;
+
+        db_state
+            .epics
+            .get_mut(&epic_id)
+            .ok_or_else(|| anyhow!("Epic of id {} not found", epic_id))?
+            .stories
+            .push(story_id);
+        db_state.stories.insert(story_id, story.clone());
+
+        self.save_state(&db_state)
+    }
+
+    fn update_story(&self, story_id: u32, story: &Story) -> Result<()> {
+        let mut db_state = self.load_state()?;
+
+        *db_state
+            .stories
+            .get_mut(&story_id)
+            .ok_or_else(|| anyhow!("Story of id {} not found", story_id))? = story.clone();
+
+        self.save_state(&db_state)
+    }
+
+    fn delete_story(&self, story_id: u32) -> Result<()> {
+        let mut db_state = self.load_state()?;
+
+        db_state
+            .stories
+            .remove(&story_id)
+            .ok_or_else(|| anyhow!("Story of id {} not found", story_id))?;
+
+        for epic in db_state.epics.values_mut() {
+            epic.stories.retain(|&x| x != story_id);
+        }
+
+        db_state
+            .links
+            .retain(|link| link.from != story_id && link.to != story_id);
+
+        self.save_state(&db_state)
+    }
+
+    fn move_story(&self, story_id: u32, to_epic_id: u32) -> Result<()> {
+        let mut db_state = self.load_state()?;
+
+        if !db_state.stories.contains_key(&story_id) {
+            return Err(anyhow!("Story of id {} not found", story_id));
+        }
+
+        if !db_state.epics.contains_key(&to_epic_id) {
+            return Err(anyhow!("Epic of id {} not found", to_epic_id));
+        }
+
+        for epic in db_state.epics.values_mut() {
+            epic.stories.retain(|&x| x != story_id);
+        }
+
+        db_state
+            .epics
+            .get_mut(&to_epic_id)
+            .ok_or_else(|| anyhow!("Epic of id {} not found", to_epic_id))?
+            .stories
+            .push(story_id);
+
+        self.save_state(&db_state)
+    }
+
+    fn append_history(&self, entry: &HistoryEntry) -> Result<()> {
+        let mut db_state = self.load_state()?;
+
+        db_state.history.push(entry.clone());
+
+        self.save_state(&db_state)
+    }
+
+    fn history_of(&self, item_id: u32) -> Result<Vec<HistoryEntry>> {
+        Ok(self
+            .load_state()?
+            .history
+            .into_iter()
+            .filter(|entry| entry.item_id == item_id)
+            .collect())
+    }
+
+    fn append_comment(&self, comment: &Comment) -> Result<()> {
+        let mut db_state = self.load_state()?;
+
+        db_state.comments.push(comment.clone());
+
+        self.save_state(&db_state)
+    }
+
+    fn comments_of(&self, story_id: u32) -> Result<Vec<Comment>> {
+        Ok(self
+            .load_state()?
+            .comments
+            .into_iter()
+            .filter(|comment| comment.story_id == story_id)
+            .collect())
+    }
+
+    fn delete_comment(&self, comment: &Comment) -> Result<()> {
+        let mut db_state = self.load_state()?;
+
+        let Some(index) = db_state.comments.iter().rposition(|x| x == comment) else {
+            return Err(comment_not_found(comment));
+        };
+        db_state.comments.remove(index);
+
+        self.save_state(&db_state)
+    }
+
+    fn list_links(&self) -> Result<Vec<StoryLink>> {
+        Ok(self.load_state()?.links)
+    }
+
+    fn insert_link(&self, link: &StoryLink) -> Result<()> {
+        let mut db_state = self.load_state()?;
+
+        db_state.links.push(link.clone());
+
+        self.save_state(&db_state)
+    }
+
+    fn delete_link(&self, link: &StoryLink) -> Result<()> {
+        let mut db_state = self.load_state()?;
+
+        let count = db_state.links.len();
+        db_state.links.retain(|x| x != link);
+
+        if db_state.links.len() == count {
+            return Err(link_not_found(link));
+        }
+
+        self.save_state(&db_state)
+    }
+
+    fn transaction(&self, operation: &mut dyn FnMut() -> Result<()>) -> Result<()> {
+        self.batch(operation)
+    }
+
+    fn lock_db(&self) -> Result<DBLock> {
+        self.lock_state()
+    }
+}
+
+/// Guard returned by `Database::lock_db`; releases the lock when dropped.
+#[derive(Default)]
+pub struct DBLock {
+    _file: Option<File>,
+}
+
+impl DBLock {
+    /// Takes an advisory lock on `lock_path`, retrying until `timeout` elapses.
+    pub fn acquire(lock_path: &str, timeout: Duration) -> Result<Self> {
+        let lock_file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(lock_path)?;
+        let started_at = Instant::now();
+
+        loop {
+            match lock_file.try_lock() {
+                Err(TryLockError::WouldBlock) if started_at.elapsed() < timeout => {
+                    thread::sleep(LOCK_RETRY_INTERVAL);
+                }
+                Err(TryLockError::WouldBlock) => {
+                    return Err(anyhow!(
+                        "database lock {} is held by another process, try again later",
+                        lock_path
+                    ))
+                }
+                Err(TryLockError::Error(error)) => return Err(error.into()),
+                _ => {
+                    return Ok(DBLock {
+                        _file: Some(lock_file),
+                    })
+                }
+            }
+        }
+    }
+}
+
+/// How long file-backed databases wait for another process to release the lock.
+pub const LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Number of rolling `.bak` snapshots kept next to the database file.
+pub const BACKUP_COUNT: usize = 3;
+
+pub struct JSONFileDatabase {
+    pub file_path: String,
+    // State of the running `batch`, loads and saves go here until it ends.
+    batched: RefCell<Option<DBState>>,
+}
+
+impl JSONFileDatabase {
+    pub fn new(file_path: String) -> Self {
+        JSONFileDatabase {
+            file_path,
+            batched: RefCell::new(None),
+        }
+    }
+
+    pub fn backup_path(&self, index: usize) -> String {
+        format!("{}.bak.{}", self.file_path, index)
+    }
+
+    /// Writes an empty board if neither the file nor any backup of it exists.
+    pub fn create_if_missing(&self) -> Result<()> {
+        if Path::new(&self.file_path).exists() || Path::new(&self.backup_path(1)).exists() {
+            return Ok(());
+        }
+
+        self.write_atomically(&DBState::default())
+    }
+
+    /// Where `upgrade` keeps the file as it was before upgrading it from `version`.
+    pub fn upgrade_backup_path(&self, version: u32) -> String {
+        format!("{}.v{}.bak", self.file_path, version)
+    }
+
+    /// Rewrites a file of an older version in the current layout, after copying it to
+    /// `upgrade_backup_path`. Reads upgrade older files on their own, this just does it
+    /// once for good. Unreadable files are left for `load_state` to recover.
+    pub fn upgrade(&self) -> Result<()> {
+        let _lock = self.lock_state()?;
+
+        let Some(value) = Self::read_value(&self.file_path).ok() else {
+            return Ok(());
+        };
+        let version = migrations::version_of(&value)?;
+
+        if version >= SCHEMA_VERSION {
+            return Ok(());
+        }
+
+        let db_state = Self::read_file(&self.file_path)?;
+
+        fs::copy(&self.file_path, self.upgrade_backup_path(version))?;
+        self.write_atomically(&db_state)
+    }
+
+    // The data file itself is replaced on every write, so the lock lives in a sibling file.
+    pub fn lock_path(&self) -> String {
+        format!("{}.lock", self.file_path)
+    }
+
+    pub fn lock_with_timeout(&self, timeout: Duration) -> Result<DBLock> {
+        DBLock::acquire(&self.lock_path(), timeout)
+    }
+
+    /// Where `load_state` moves a file it couldn't parse before restoring a backup.
+    pub fn corrupt_path(&self) -> String {
+        format!("{}.corrupt", self.file_path)
+    }
+
+    fn read_value(file_path: &str) -> Result<serde_json::Value> {
+        let db_content = fs::read_to_string(file_path)?;
+        Ok(serde_json::from_str(&db_content)?)
+    }
+
+    /// Reads and upgrades a board file without ever writing to it, not even to recover
+    /// it from a backup.
+    pub fn read_file(file_path: &str) -> Result<DBState> {
+        migrations::migrate(Self::read_value(file_path)?)
+    }
+
+    // Shifts `.bak.1` -> `.bak.2` -> ... and copies the current file into `.bak.1`,
+    // dropping the oldest snapshot.
+    fn rotate_backups(&self) -> Result<()> {
+        if !Path::new(&self.file_path).exists() {
+            return Ok(());
+        }
+
+        let content = fs::read(&self.file_path)?;
+
+        for index in (1..BACKUP_COUNT).rev() {
+            let backup_path = self.backup_path(index);
+
+            if Path::new(&backup_path).exists() {
+                fs::rename(&backup_path, self.backup_path(index + 1))?;
+            }
+        }
+
+        // also syncs the directory, and with it the renames
+        replace_file(&self.backup_path(1), &content)
+    }
+
+    fn write_atomically(&self, db_state: &DBState) -> Result<()> {
+        replace_file(&self.file_path, migrations::to_json(db_state)?.as_bytes())
+    }
+
+    // Moves the unreadable file aside and puts the newest readable backup in its place.
+    fn recover_from_backup(&self) -> Result<DBState> {
+        let (backup_path, db_state) = (1..=BACKUP_COUNT)
+            .map(|index| self.backup_path(index))
+            .find_map(|backup_path| {
+                let db_state = Self::read_file(&backup_path).ok()?;
+                Some((backup_path, db_state))
+            })
+            .ok_or_else(|| anyhow!("no valid backup found for {}", self.file_path))?;
+
+        fs::rename(&self.file_path, self.corrupt_path())?;
+        self.write_atomically(&db_state)?;
+
+        eprintln!(
+            "Warning: {} could not be read and was restored from {}, the unreadable file was kept as {}.",
+            self.file_path,
+            backup_path,
+            self.corrupt_path()
+        );
+
+        Ok(db_state)
+    }
+}
+
+// Writes to a temp file in the same directory, fsyncs it and renames it over the
+// original, so readers only ever see the old or the new content.
+fn replace_file(file_path: &str, content: &[u8]) -> Result<()> {
+    let path = Path::new(file_path);
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    let mut tmpfile = tempfile::NamedTempFile::new_in(dir)?;
+    tmpfile.write_all(content)?;
+    tmpfile.as_file().sync_all()?;
+    tmpfile.persist(path)?;
+
+    #[cfg(unix)]
+    fs::File::open(dir)?.sync_all()?;
+
+    Ok(())
+}
+
+// Whether reading failed on the content of the file, e.g. because a crash left it
+// empty or truncated, rather than on getting to it at all.
+fn is_unparsable(error: &anyhow::Error) -> bool {
+    error
+        .downcast_ref::<serde_json::Error>()
+        .is_some_and(|error| !error.is_io())
+}
+
+impl StateDatabase for JSONFileDatabase {
+    fn load_state(&self) -> Result<DBState> {
+        if let Some(db_state) = self.batched.borrow().as_ref() {
+            return Ok(db_state.clone());
+        }
+
+        Self::read_file(&self.file_path).or_else(|error| {
+            // I/O errors may pass, and a file from a newer version isn't broken, so
+            // neither may be overwritten
+            if !is_unparsable(&error) {
+                return Err(error);
+            }
+
+            self.recover_from_backup()
+                .map_err(|_| error.context(format!("failed to read {}", self.file_path)))
+        })
+    }
+
+    fn save_state(&self, db_state: &DBState) -> Result<()> {
+        if let Some(batched) = self.batched.borrow_mut().as_mut() {
+            *batched = db_state.clone();
+            return Ok(());
+        }
+
+        self.rotate_backups()?;
+        self.write_atomically(db_state)
+    }
+
+    fn lock_state(&self) -> Result<DBLock> {
+        self.lock_with_timeout(LOCK_TIMEOUT)
+    }
+
+    // Writes the file, and rotates the backups, once per batch and only if something
+    // changed.
+    fn batch(&self, operation: &mut dyn FnMut() -> Result<()>) -> Result<()> {
+        if self.batched.borrow().is_some() {
+            return operation();
+        }
+
+        let loaded = self.load_state()?;
+        *self.batched.borrow_mut() = Some(loaded.clone());
+
+        let result = operation();
+        let batched = self.batched.borrow_mut().take();
+        result?;
+
+        match batched {
+            Some(db_state) if db_state != loaded => self.save_state(&db_state),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,830 @@
+use anyhow::{anyhow, Ok, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection};
+use serde::{de::DeserializeOwned, Serialize};
+use std::collections::HashMap;
+
+use super::{comment_not_found, link_not_found, DBLock, Database, LOCK_TIMEOUT};
+use crate::models::{Comment, DBState, Epic, HistoryEntry, Story, StoryLink};
+
+// Applied in order; `PRAGMA user_version` records how many already ran.
+const MIGRATIONS: &[&str] = &[
+    "
+    CREATE TABLE IF NOT EXISTS meta (
+        key TEXT PRIMARY KEY,
+        value INTEGER NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS epics (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL,
+        description TEXT NOT NULL,
+        status TEXT NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS stories (
+        id INTEGER PRIMARY KEY,
+        epic_id INTEGER REFERENCES epics(id) ON DELETE CASCADE,
+        position INTEGER NOT NULL DEFAULT 0,
+        name TEXT NOT NULL,
+        description TEXT NOT NULL,
+        status TEXT NOT NULL
+    );
+
+    CREATE INDEX IF NOT EXISTS stories_epic_id ON stories(epic_id);
+
+    INSERT OR IGNORE INTO meta (key, value) VALUES ('last_item_id', 0);
+    ",
+    "
+    ALTER TABLE epics ADD COLUMN labels TEXT NOT NULL DEFAULT '[]';
+
+    ALTER TABLE stories ADD COLUMN priority TEXT;
+    ALTER TABLE stories ADD COLUMN assignee TEXT;
+    ALTER TABLE stories ADD COLUMN labels TEXT NOT NULL DEFAULT '[]';
+    ALTER TABLE stories ADD COLUMN estimate INTEGER;
+    ",
+    "
+    ALTER TABLE epics ADD COLUMN created_at TEXT;
+    ALTER TABLE epics ADD COLUMN updated_at TEXT;
+
+    ALTER TABLE stories ADD COLUMN created_at TEXT;
+    ALTER TABLE stories ADD COLUMN updated_at TEXT;
+
+    -- no foreign key: entries outlive the items they describe
+    CREATE TABLE IF NOT EXISTS history (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        item_id INTEGER NOT NULL,
+        timestamp TEXT NOT NULL,
+        author TEXT NOT NULL,
+        change TEXT NOT NULL
+    );
+
+    CREATE INDEX IF NOT EXISTS history_item_id ON history(item_id);
+    ",
+    "
+    ALTER TABLE epics ADD COLUMN deleted_at TEXT;
+    ALTER TABLE stories ADD COLUMN deleted_at TEXT;
+    ",
+    "
+    ALTER TABLE stories ADD COLUMN checklist TEXT NOT NULL DEFAULT '[]';
+    ",
+    "
+    -- no foreign key either, like the history
+    CREATE TABLE IF NOT EXISTS comments (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        story_id INTEGER NOT NULL,
+        timestamp TEXT NOT NULL,
+        author TEXT NOT NULL,
+        text TEXT NOT NULL
+    );
+
+    CREATE INDEX IF NOT EXISTS comments_story_id ON comments(story_id);
+    ",
+    "
+    -- unlike the history and comments, links go away with their stories
+    CREATE TABLE IF NOT EXISTS links (
+        from_id INTEGER NOT NULL REFERENCES stories(id) ON DELETE CASCADE,
+        to_id INTEGER NOT NULL REFERENCES stories(id) ON DELETE CASCADE,
+        kind TEXT NOT NULL,
+        PRIMARY KEY (from_id, to_id, kind)
+    );
+
+    CREATE INDEX IF NOT EXISTS links_to_id ON links(to_id);
+    ",
+];
+
+// Columns holding the fields of `Epic`/`Story`, in the order used by
+// `epic_values`/`epic_from_row` and `story_values`/`story_from_row`.
+const EPIC_COLUMNS: &[&str] = &[
+    "name",
+    "description",
+    "status",
+    "labels",
+    "created_at",
+    "updated_at",
+    "deleted_at",
+];
+const STORY_COLUMNS: &[&str] = &[
+    "name",
+    "description",
+    "status",
+    "priority",
+    "assignee",
+    "labels",
+    "estimate",
+    "created_at",
+    "updated_at",
+    "deleted_at",
+    "checklist",
+];
+const HISTORY_COLUMNS: &[&str] = &["item_id", "timestamp", "author", "change"];
+const COMMENT_COLUMNS: &[&str] = &["story_id", "timestamp", "author", "text"];
+const LINK_COLUMNS: &[&str] = &["from_id", "to_id", "kind"];
+
+pub struct SqliteDatabase {
+    connection: Connection,
+    lock_path: Option<String>,
+}
+
+impl SqliteDatabase {
+    pub fn open(file_path: &str) -> Result<Self> {
+        Self::init(
+            Connection::open(file_path)?,
+            Some(format!("{}.lock", file_path)),
+        )
+    }
+
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?, None)
+    }
+
+    fn init(connection: Connection, lock_path: Option<String>) -> Result<Self> {
+        connection.execute_batch("PRAGMA foreign_keys = ON;")?;
+        connection.busy_timeout(LOCK_TIMEOUT)?;
+
+        Self::migrate(&connection)?;
+
+        Ok(SqliteDatabase {
+            connection,
+            lock_path,
+        })
+    }
+
+    fn migrate(connection: &Connection) -> Result<()> {
+        let version: usize =
+            connection.pragma_query_value(None, "user_version", |row| row.get(0))?;
+
+        for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+            let transaction = connection.unchecked_transaction()?;
+
+            transaction.execute_batch(migration)?;
+            transaction.pragma_update(None, "user_version", index + 1)?;
+
+            transaction.commit()?;
+        }
+
+        Ok(())
+    }
+
+    /// Replaces the contents of this database with the whole state of another backend.
+    pub fn import_from(&self, source: &dyn Database) -> Result<()> {
+        self.import_state(&source.read_db()?)
+    }
+
+    /// Replaces the contents of this database with `db_state`, e.g. the content of an
+    /// existing `db.json`.
+    pub fn import_state(&self, db_state: &DBState) -> Result<()> {
+        let _lock = self.lock_db()?;
+
+        self.write_db(db_state)
+    }
+
+    // Swaps all tables for `db_state` in one transaction
+    fn replace_all(&self, db_state: &DBState) -> Result<()> {
+        let memberships = Self::story_memberships(db_state);
+
+        self.transaction(&mut || {
+            self.connection.execute_batch(
+                "DELETE FROM links; DELETE FROM stories; DELETE FROM epics; DELETE FROM history;
+                 DELETE FROM comments;",
+            )?;
+
+            for (epic_id, epic) in &db_state.epics {
+                let mut values = vec![Value::from(*epic_id)];
+                values.extend(epic_values(epic)?);
+
+                self.connection.execute(
+                    &format!(
+                        "INSERT INTO epics (id, {}) VALUES (?1, {})",
+                        EPIC_COLUMNS.join(", "),
+                        placeholders(EPIC_COLUMNS, 2)
+                    ),
+                    params_from_iter(values),
+                )?;
+            }
+
+            for (story_id, story) in &db_state.stories {
+                let membership = memberships.get(story_id);
+
+                let mut values = vec![
+                    Value::from(*story_id),
+                    membership.map_or(Value::Null, |(epic_id, _)| Value::from(*epic_id)),
+                    Value::from(membership.map_or(0, |(_, position)| *position as i64)),
+                ];
+                values.extend(story_values(story)?);
+
+                self.connection.execute(
+                    &format!(
+                        "INSERT INTO stories (id, epic_id, position, {}) VALUES (?1, ?2, ?3, {})",
+                        STORY_COLUMNS.join(", "),
+                        placeholders(STORY_COLUMNS, 4)
+                    ),
+                    params_from_iter(values),
+                )?;
+            }
+
+            for entry in &db_state.history {
+                Self::insert_history(&self.connection, entry)?;
+            }
+
+            for comment in &db_state.comments {
+                Self::insert_comment(&self.connection, comment)?;
+            }
+
+            for link in &db_state.links {
+                Self::store_link(&self.connection, link)?;
+            }
+
+            self.connection.execute(
+                "UPDATE meta SET value = ?1 WHERE key = 'last_item_id'",
+                params![db_state.last_item_id],
+            )?;
+
+            Ok(())
+        })
+    }
+
+    fn last_item_id(&self) -> Result<u32> {
+        let last_item_id = self.connection.query_row(
+            "SELECT value FROM meta WHERE key = 'last_item_id'",
+            [],
+            |row| row.get(0),
+        )?;
+
+        Ok(last_item_id)
+    }
+
+    fn story_ids_of_epic(&self, epic_id: u32) -> Result<Vec<u32>> {
+        let mut statement = self
+            .connection
+            .prepare("SELECT id FROM stories WHERE epic_id = ?1 ORDER BY position")?;
+        let story_ids = statement
+            .query_map(params![epic_id], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<u32>>>()?;
+
+        Ok(story_ids)
+    }
+
+    fn epic_from_row(row: &rusqlite::Row, offset: usize) -> Result<Epic> {
+        Ok(Epic {
+            name: row.get(offset)?,
+            description: row.get(offset + 1)?,
+            status: enum_from_sql(&row.get::<_, String>(offset + 2)?)?,
+            stories: vec![],
+            labels: serde_json::from_str(&row.get::<_, String>(offset + 3)?)?,
+            created_at: timestamp_from_sql(row.get(offset + 4)?)?,
+            updated_at: timestamp_from_sql(row.get(offset + 5)?)?,
+            deleted_at: timestamp_from_sql(row.get(offset + 6)?)?,
+        })
+    }
+
+    fn story_from_row(row: &rusqlite::Row, offset: usize) -> Result<Story> {
+        let priority: Option<String> = row.get(offset + 3)?;
+
+        Ok(Story {
+            name: row.get(offset)?,
+            description: row.get(offset + 1)?,
+            status: enum_from_sql(&row.get::<_, String>(offset + 2)?)?,
+            priority: priority.as_deref().map(enum_from_sql).transpose()?,
+            assignee: row.get(offset + 4)?,
+            labels: serde_json::from_str(&row.get::<_, String>(offset + 5)?)?,
+            estimate: row.get(offset + 6)?,
+            created_at: timestamp_from_sql(row.get(offset + 7)?)?,
+            updated_at: timestamp_from_sql(row.get(offset + 8)?)?,
+            deleted_at: timestamp_from_sql(row.get(offset + 9)?)?,
+            checklist: serde_json::from_str(&row.get::<_, String>(offset + 10)?)?,
+        })
+    }
+
+    fn history_from_row(row: &rusqlite::Row) -> Result<HistoryEntry> {
+        let timestamp: Option<String> = row.get(1)?;
+
+        Ok(HistoryEntry {
+            item_id: row.get(0)?,
+            timestamp: timestamp_from_sql(timestamp)?
+                .ok_or_else(|| anyhow!("history entry without timestamp"))?,
+            author: row.get(2)?,
+            change: serde_json::from_str(&row.get::<_, String>(3)?)?,
+        })
+    }
+
+    fn insert_history(connection: &Connection, entry: &HistoryEntry) -> Result<()> {
+        connection.execute(
+            &format!(
+                "INSERT INTO history ({}) VALUES ({})",
+                HISTORY_COLUMNS.join(", "),
+                placeholders(HISTORY_COLUMNS, 1)
+            ),
+            params_from_iter([
+                Value::from(entry.item_id),
+                timestamp_to_sql(Some(entry.timestamp)),
+                Value::from(entry.author.clone()),
+                Value::from(serde_json::to_string(&entry.change)?),
+            ]),
+        )?;
+
+        Ok(())
+    }
+
+    fn query_history(&self, filter: &str, values: Vec<Value>) -> Result<Vec<HistoryEntry>> {
+        let mut statement = self.connection.prepare(&format!(
+            "SELECT {} FROM history {} ORDER BY id",
+            HISTORY_COLUMNS.join(", "),
+            filter
+        ))?;
+        let mut rows = statement.query(params_from_iter(values))?;
+        let mut history = vec![];
+
+        while let Some(row) = rows.next()? {
+            history.push(Self::history_from_row(row)?);
+        }
+
+        Ok(history)
+    }
+
+    fn comment_from_row(row: &rusqlite::Row) -> Result<Comment> {
+        let timestamp: Option<String> = row.get(1)?;
+
+        Ok(Comment {
+            story_id: row.get(0)?,
+            timestamp: timestamp_from_sql(timestamp)?
+                .ok_or_else(|| anyhow!("comment without timestamp"))?,
+            author: row.get(2)?,
+            text: row.get(3)?,
+        })
+    }
+
+    fn insert_comment(connection: &Connection, comment: &Comment) -> Result<()> {
+        connection.execute(
+            &format!(
+                "INSERT INTO comments ({}) VALUES ({})",
+                COMMENT_COLUMNS.join(", "),
+                placeholders(COMMENT_COLUMNS, 1)
+            ),
+            params_from_iter([
+                Value::from(comment.story_id),
+                timestamp_to_sql(Some(comment.timestamp)),
+                Value::from(comment.author.clone()),
+                Value::from(comment.text.clone()),
+            ]),
+        )?;
+
+        Ok(())
+    }
+
+    fn query_comments(&self, filter: &str, values: Vec<Value>) -> Result<Vec<Comment>> {
+        let mut statement = self.connection.prepare(&format!(
+            "SELECT {} FROM comments {} ORDER BY id",
+            COMMENT_COLUMNS.join(", "),
+            filter
+        ))?;
+        let mut rows = statement.query(params_from_iter(values))?;
+        let mut comments = vec![];
+
+        while let Some(row) = rows.next()? {
+            comments.push(Self::comment_from_row(row)?);
+        }
+
+        Ok(comments)
+    }
+
+    fn store_link(connection: &Connection, link: &StoryLink) -> Result<()> {
+        connection.execute(
+            &format!(
+                "INSERT INTO links ({}) VALUES ({})",
+                LINK_COLUMNS.join(", "),
+                placeholders(LINK_COLUMNS, 1)
+            ),
+            params![link.from, link.to, enum_to_sql(&link.kind)?],
+        )?;
+
+        Ok(())
+    }
+
+    // story id -> (epic id, position in `Epic::stories`)
+    fn story_memberships(db_state: &DBState) -> HashMap<u32, (u32, usize)> {
+        let mut memberships = HashMap::new();
+
+        for (epic_id, epic) in &db_state.epics {
+            for (position, story_id) in epic.stories.iter().enumerate() {
+                memberships.insert(*story_id, (*epic_id, position));
+            }
+        }
+
+        memberships
+    }
+}
+
+fn epic_values(epic: &Epic) -> Result<Vec<Value>> {
+    Ok(vec![
+        Value::from(epic.name.clone()),
+        Value::from(epic.description.clone()),
+        Value::from(enum_to_sql(&epic.status)?),
+        Value::from(serde_json::to_string(&epic.labels)?),
+        timestamp_to_sql(epic.created_at),
+        timestamp_to_sql(epic.updated_at),
+        timestamp_to_sql(epic.deleted_at),
+    ])
+}
+
+fn story_values(story: &Story) -> Result<Vec<Value>> {
+    Ok(vec![
+        Value::from(story.name.clone()),
+        Value::from(story.description.clone()),
+        Value::from(enum_to_sql(&story.status)?),
+        Value::from(story.priority.as_ref().map(enum_to_sql).transpose()?),
+        Value::from(story.assignee.clone()),
+        Value::from(serde_json::to_string(&story.labels)?),
+        Value::from(story.estimate),
+        timestamp_to_sql(story.created_at),
+        timestamp_to_sql(story.updated_at),
+        timestamp_to_sql(story.deleted_at),
+        Value::from(serde_json::to_string(&story.checklist)?),
+    ])
+}
+
+// Timestamps are stored as RFC 3339 text, which sorts chronologically.
+fn timestamp_to_sql(timestamp: Option<DateTime<Utc>>) -> Value {
+    Value::from(timestamp.map(|timestamp| timestamp.to_rfc3339_opts(SecondsFormat::AutoSi, true)))
+}
+
+fn timestamp_from_sql(text: Option<String>) -> Result<Option<DateTime<Utc>>> {
+    text.map(|text| {
+        DateTime::parse_from_rfc3339(&text)
+            .map(|timestamp| timestamp.with_timezone(&Utc))
+            .map_err(|_| anyhow!("invalid timestamp {} in database", text))
+    })
+    .transpose()
+}
+
+// Enums are stored by their serde name, e.g. `InProgress`.
+fn enum_to_sql<T: Serialize>(value: &T) -> Result<String> {
+    match serde_json::to_value(value)? {
+        serde_json::Value::String(name) => Ok(name),
+        other => Err(anyhow!("cannot store {} as text", other)),
+    }
+}
+
+fn enum_from_sql<T: DeserializeOwned>(name: &str) -> Result<T> {
+    serde_json::from_value(serde_json::Value::String(name.to_owned()))
+        .map_err(|_| anyhow!("unknown value {} in database", name))
+}
+
+// "?first, ?first+1, ..." for each column
+fn placeholders(columns: &[&str], first: usize) -> String {
+    (first..first + columns.len())
+        .map(|index| format!("?{}", index))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// "column = ?first, ..." for each column
+fn assignments(columns: &[&str], first: usize) -> String {
+    columns
+        .iter()
+        .enumerate()
+        .map(|(index, column)| format!("{} = ?{}", column, first + index))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl Database for SqliteDatabase {
+    fn read_db(&self) -> Result<DBState> {
+        let mut epics = HashMap::new();
+        let mut stories = HashMap::new();
+
+        let mut statement = self.connection.prepare(&format!(
+            "SELECT id, {} FROM epics",
+            EPIC_COLUMNS.join(", ")
+        ))?;
+        let mut rows = statement.query([])?;
+
+        while let Some(row) = rows.next()? {
+            epics.insert(row.get(0)?, Self::epic_from_row(row, 1)?);
+        }
+
+        let mut statement = self.connection.prepare(&format!(
+            "SELECT id, epic_id, {} FROM stories ORDER BY epic_id, position",
+            STORY_COLUMNS.join(", ")
+        ))?;
+        let mut rows = statement.query([])?;
+
+        while let Some(row) = rows.next()? {
+            let story_id: u32 = row.get(0)?;
+            let epic_id: Option<u32> = row.get(1)?;
+
+            stories.insert(story_id, Self::story_from_row(row, 2)?);
+
+            if let Some(epic) = epic_id.and_then(|epic_id| epics.get_mut(&epic_id)) {
+                epic.stories.push(story_id);
+            }
+        }
+
+        Ok(DBState {
+            last_item_id: self.last_item_id()?,
+            epics,
+            stories,
+            history: self.query_history("", vec![])?,
+            comments: self.query_comments("", vec![])?,
+            links: self.list_links()?,
+        })
+    }
+
+    fn write_db(&self, db_state: &DBState) -> Result<()> {
+        self.replace_all(db_state)
+    }
+
+    fn next_item_id(&self) -> Result<u32> {
+        let next_item_id = self.connection.query_row(
+            "UPDATE meta SET value = value + 1 WHERE key = 'last_item_id' RETURNING value",
+            [],
+            |row| row.get(0),
+        )?;
+
+        Ok(next_item_id)
+    }
+
+    fn get_epic(&self, epic_id: u32) -> Result<Option<Epic>> {
+        let mut statement = self.connection.prepare(&format!(
+            "SELECT {} FROM epics WHERE id = ?1",
+            EPIC_COLUMNS.join(", ")
+        ))?;
+        let mut rows = statement.query(params![epic_id])?;
+
+        let Some(row) = rows.next()? else {
+            return Ok(None);
+        };
+
+        Ok(Some(Epic {
+            stories: self.story_ids_of_epic(epic_id)?,
+            ..Self::epic_from_row(row, 0)?
+        }))
+    }
+
+    fn get_story(&self, story_id: u32) -> Result<Option<Story>> {
+        let mut statement = self.connection.prepare(&format!(
+            "SELECT {} FROM stories WHERE id = ?1",
+            STORY_COLUMNS.join(", ")
+        ))?;
+        let mut rows = statement.query(params![story_id])?;
+
+        let Some(row) = rows.next()? else {
+            return Ok(None);
+        };
+
+        Ok(Some(Self::story_from_row(row, 0)?))
+    }
+
+    fn list_epics(&self) -> Result<Vec<(u32, Epic)>> {
+        let mut statement = self.connection.prepare(&format!(
+            "SELECT id, {} FROM epics ORDER BY id",
+            EPIC_COLUMNS.join(", ")
+        ))?;
+        let mut rows = statement.query([])?;
+        let mut epics = vec![];
+
+        while let Some(row) = rows.next()? {
+            let epic_id = row.get(0)?;
+
+            epics.push((
+                epic_id,
+                Epic {
+                    stories: self.story_ids_of_epic(epic_id)?,
+                    ..Self::epic_from_row(row, 1)?
+                },
+            ));
+        }
+
+        Ok(epics)
+    }
+
+    fn list_stories_of_epic(&self, epic_id: u32) -> Result<Vec<(u32, Story)>> {
+        if self.get_epic(epic_id)?.is_none() {
+            return Err(anyhow!("Epic of id {} not found", epic_id));
+        }
+
+        let mut statement = self.connection.prepare(&format!(
+            "SELECT id, {} FROM stories WHERE epic_id = ?1 ORDER BY id",
+            STORY_COLUMNS.join(", ")
+        ))?;
+        let mut rows = statement.query(params![epic_id])?;
+        let mut stories = vec![];
+
+        while let Some(row) = rows.next()? {
+            stories.push((row.get(0)?, Self::story_from_row(row, 1)?));
+        }
+
+        Ok(stories)
+    }
+
+    fn insert_epic(&self, epic_id: u32, epic: &Epic) -> Result<()> {
+        let mut values = vec![Value::from(epic_id)];
+        values.extend(epic_values(epic)?);
+
+        self.connection.execute(
+            &format!(
+                "INSERT INTO epics (id, {}) VALUES (?1, {})",
+                EPIC_COLUMNS.join(", "),
+                placeholders(EPIC_COLUMNS, 2)
+            ),
+            params_from_iter(values),
+        )?;
+
+        Ok(())
+    }
+
+    fn update_epic(&self, epic_id: u32, epic: &Epic) -> Result<()> {
+        let mut values = vec![Value::from(epic_id)];
+        values.extend(epic_values(epic)?);
+
+        let updated = self.connection.execute(
+            &format!(
+                "UPDATE epics SET {} WHERE id = ?1",
+                assignments(EPIC_COLUMNS, 2)
+            ),
+            params_from_iter(values),
+        )?;
+
+        if updated == 0 {
+            return Err(anyhow!("Epic of id {} not found", epic_id));
+        }
+
+        Ok(())
+    }
+
+    fn delete_epic(&self, epic_id: u32) -> Result<()> {
+        let deleted = self
+            .connection
+            .execute("DELETE FROM epics WHERE id = ?1", params![epic_id])?;
+
+        if deleted == 0 {
+            return Err(anyhow!("Epic of id {} not found", epic_id));
+        }
+
+        Ok(())
+    }
+
+    fn insert_story(&self, epic_id: u32, story_id: u32, story: &Story) -> Result<()> {
+        let mut values = vec![Value::from(story_id), Value::from(epic_id)];
+        values.extend(story_values(story)?);
+
+        self.connection.execute(
+            &format!(
+                "INSERT INTO stories (id, epic_id, position, {})
+                 VALUES (
+                    ?1, ?2,
+                    (SELECT COALESCE(MAX(position) + 1, 0) FROM stories WHERE epic_id = ?2),
+                    {}
+                 )",
+                STORY_COLUMNS.join(", "),
+                placeholders(STORY_COLUMNS, 3)
+            ),
+            params_from_iter(values),
+        )?;
+
+        Ok(())
+    }
+
+    fn update_story(&self, story_id: u32, story: &Story) -> Result<()> {
+        let mut values = vec![Value::from(story_id)];
+        values.extend(story_values(story)?);
+
+        let updated = self.connection.execute(
+            &format!(
+                "UPDATE stories SET {} WHERE id = ?1",
+                assignments(STORY_COLUMNS, 2)
+            ),
+            params_from_iter(values),
+        )?;
+
+        if updated == 0 {
+            return Err(anyhow!("Story of id {} not found", story_id));
+        }
+
+        Ok(())
+    }
+
+    fn delete_story(&self, story_id: u32) -> Result<()> {
+        let deleted = self
+            .connection
+            .execute("DELETE FROM stories WHERE id = ?1", params![story_id])?;
+
+        if deleted == 0 {
+            return Err(anyhow!("Story of id {} not found", story_id));
+        }
+
+        Ok(())
+    }
+
+    fn move_story(&self, story_id: u32, to_epic_id: u32) -> Result<()> {
+        let updated = self.connection.execute(
+            "UPDATE stories SET
+                epic_id = ?2,
+                position = (SELECT COALESCE(MAX(position) + 1, 0) FROM stories WHERE epic_id = ?2)
+             WHERE id = ?1",
+            params![story_id, to_epic_id],
+        )?;
+
+        if updated == 0 {
+            return Err(anyhow!("Story of id {} not found", story_id));
+        }
+
+        Ok(())
+    }
+
+    fn append_history(&self, entry: &HistoryEntry) -> Result<()> {
+        Self::insert_history(&self.connection, entry)
+    }
+
+    fn history_of(&self, item_id: u32) -> Result<Vec<HistoryEntry>> {
+        self.query_history("WHERE item_id = ?1", vec![Value::from(item_id)])
+    }
+
+    fn append_comment(&self, comment: &Comment) -> Result<()> {
+        Self::insert_comment(&self.connection, comment)
+    }
+
+    fn comments_of(&self, story_id: u32) -> Result<Vec<Comment>> {
+        self.query_comments("WHERE story_id = ?1", vec![Value::from(story_id)])
+    }
+
+    fn delete_comment(&self, comment: &Comment) -> Result<()> {
+        // comments have no id of their own, identical ones go latest first
+        let deleted = self.connection.execute(
+            "DELETE FROM comments WHERE id = (
+                SELECT MAX(id) FROM comments
+                WHERE story_id = ?1 AND timestamp = ?2 AND author = ?3 AND text = ?4
+            )",
+            params_from_iter([
+                Value::from(comment.story_id),
+                timestamp_to_sql(Some(comment.timestamp)),
+                Value::from(comment.author.clone()),
+                Value::from(comment.text.clone()),
+            ]),
+        )?;
+
+        if deleted == 0 {
+            return Err(comment_not_found(comment));
+        }
+
+        Ok(())
+    }
+
+    fn list_links(&self) -> Result<Vec<StoryLink>> {
+        let mut statement = self.connection.prepare(&format!(
+            "SELECT {} FROM links ORDER BY rowid",
+            LINK_COLUMNS.join(", ")
+        ))?;
+        let mut rows = statement.query([])?;
+        let mut links = vec![];
+
+        while let Some(row) = rows.next()? {
+            links.push(StoryLink {
+                from: row.get(0)?,
+                to: row.get(1)?,
+                kind: enum_from_sql(&row.get::<_, String>(2)?)?,
+            });
+        }
+
+        Ok(links)
+    }
+
+    fn insert_link(&self, link: &StoryLink) -> Result<()> {
+        Self::store_link(&self.connection, link)
+    }
+
+    fn delete_link(&self, link: &StoryLink) -> Result<()> {
+        let deleted = self.connection.execute(
+            "DELETE FROM links WHERE from_id = ?1 AND to_id = ?2 AND kind = ?3",
+            params![link.from, link.to, enum_to_sql(&link.kind)?],
+        )?;
+
+        if deleted == 0 {
+            return Err(link_not_found(link));
+        }
+
+        Ok(())
+    }
+
+    fn transaction(&self, operation: &mut dyn FnMut() -> Result<()>) -> Result<()> {
+        if !self.connection.is_autocommit() {
+            return operation();
+        }
+
+        // rolls back when dropped without committing
+        let transaction = self.connection.unchecked_transaction()?;
+
+        operation()?;
+
+        transaction.commit()?;
+        Ok(())
+    }
+
+    fn lock_db(&self) -> Result<DBLock> {
+        match &self.lock_path {
+            Some(lock_path) => DBLock::acquire(lock_path, LOCK_TIMEOUT),
+            None => Ok(DBLock::default()),
+        }
+    }
+}
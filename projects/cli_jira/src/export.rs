@@ -0,0 +1,263 @@
+//! Exports of a board, as returned by `JiraDatabase::export_state`, for spreadsheets,
+//! READMEs and other tools.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::csv;
+use crate::models::{DBState, Epic, Story};
+use crate::ui::pages::page_helpers::{
+    format_checkbox, format_labels, format_optional, format_progress,
+};
+use crate::workflow::Workflow;
+
+/// Version of the JSON export, raised whenever its layout changes.
+pub const JSON_EXPORT_VERSION: u32 = 1;
+
+/// Columns of the CSV export, also understood by `import::import_csv`.
+pub const CSV_COLUMNS: [&str; 12] = [
+    "type",
+    "id",
+    "epic_id",
+    "name",
+    "description",
+    "status",
+    "priority",
+    "assignee",
+    "labels",
+    "estimate",
+    "created_at",
+    "updated_at",
+];
+
+#[derive(ValueEnum, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ExportFormat {
+    Csv,
+    Markdown,
+    Json,
+}
+
+/// The JSON export: the board with some information about the export.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+pub struct JsonExport {
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub board: DBState,
+}
+
+pub fn export(db_state: &DBState, workflow: &Workflow, format: ExportFormat) -> Result<String> {
+    match format {
+        ExportFormat::Csv => Ok(to_csv(db_state)),
+        ExportFormat::Markdown => Ok(to_markdown(db_state, workflow)),
+        ExportFormat::Json => to_json(db_state),
+    }
+}
+
+/// One row per epic, each followed by its stories, all sorted by id.
+pub fn to_csv(db_state: &DBState) -> String {
+    let mut lines = vec![csv::write_record(&CSV_COLUMNS)];
+
+    for (epic_id, epic, stories) in epics_with_stories(db_state) {
+        lines.push(csv::write_record(&[
+            "epic".to_owned(),
+            epic_id.to_string(),
+            String::new(),
+            epic.name.clone(),
+            epic.description.clone(),
+            String::from(epic.status.clone()),
+            String::new(),
+            String::new(),
+            epic.labels.join(", "),
+            String::new(),
+            format_rfc3339(epic.created_at),
+            format_rfc3339(epic.updated_at),
+        ]));
+
+        for (story_id, story) in stories {
+            lines.push(csv::write_record(&[
+                "story".to_owned(),
+                story_id.to_string(),
+                epic_id.to_string(),
+                story.name.clone(),
+                story.description.clone(),
+                String::from(story.status.clone()),
+                story
+                    .priority
+                    .map(|priority| priority.to_string().to_lowercase())
+                    .unwrap_or_default(),
+                story.assignee.clone().unwrap_or_default(),
+                story.labels.join(", "),
+                story
+                    .estimate
+                    .map(|estimate| estimate.to_string())
+                    .unwrap_or_default(),
+                format_rfc3339(story.created_at),
+                format_rfc3339(story.updated_at),
+            ]));
+        }
+    }
+
+    lines.push(String::new());
+    lines.join("\n")
+}
+
+/// A report with a section per epic and a checklist of its stories, ticked once they
+/// are done in `workflow`, each followed by its own checklist.
+pub fn to_markdown(db_state: &DBState, workflow: &Workflow) -> String {
+    let epics = epics_with_stories(db_state);
+    let story_count: usize = epics.iter().map(|(_, _, stories)| stories.len()).sum();
+    let done_count = epics
+        .iter()
+        .flat_map(|(_, _, stories)| stories)
+        .filter(|(_, story)| workflow.is_done(&story.status))
+        .count();
+
+    let mut lines = vec![
+        "# Board".to_owned(),
+        String::new(),
+        format!(
+            "{} epics, {} stories, {} done",
+            epics.len(),
+            story_count,
+            done_count
+        ),
+    ];
+
+    for (epic_id, epic, stories) in epics {
+        let done = stories
+            .iter()
+            .filter(|(_, story)| workflow.is_done(&story.status))
+            .count();
+
+        lines.push(String::new());
+        lines.push(format!(
+            "## {} {}. {}",
+            format_checkbox(workflow.is_done(&epic.status)),
+            epic_id,
+            escape_markdown(&epic.name)
+        ));
+        lines.push(String::new());
+        lines.push(format!(
+            "status: {} | progress: {} | labels: {}",
+            epic.status,
+            format_progress(done, stories.len()),
+            escape_markdown(&format_labels(&epic.labels))
+        ));
+
+        if !epic.description.trim().is_empty() {
+            lines.push(String::new());
+            lines.push(escape_markdown(epic.description.trim()));
+        }
+
+        lines.push(String::new());
+
+        if stories.is_empty() {
+            lines.push("_No stories._".to_owned());
+        }
+
+        for (story_id, story) in stories {
+            let mut details = vec![story.status.to_string()];
+
+            if let Some(priority) = story.priority {
+                details.push(format!("priority: {}", priority));
+            }
+            if let Some(assignee) = &story.assignee {
+                details.push(format!("assignee: {}", escape_markdown(assignee)));
+            }
+            if story.estimate.is_some() {
+                details.push(format!("points: {}", format_optional(story.estimate)));
+            }
+            if !story.labels.is_empty() {
+                details.push(format!(
+                    "labels: {}",
+                    escape_markdown(&format_labels(&story.labels))
+                ));
+            }
+
+            lines.push(format!(
+                "- {} {}. {} ({})",
+                format_checkbox(workflow.is_done(&story.status)),
+                story_id,
+                escape_markdown(&story.name),
+                details.join(" | ")
+            ));
+
+            for item in &story.checklist {
+                lines.push(format!(
+                    "  - {} {}",
+                    format_checkbox(item.done),
+                    escape_markdown(&item.text)
+                ));
+            }
+        }
+    }
+
+    lines.push(String::new());
+    lines.join("\n")
+}
+
+/// The board as JSON, see `JsonExport`.
+pub fn to_json(db_state: &DBState) -> Result<String> {
+    let export = JsonExport {
+        version: JSON_EXPORT_VERSION,
+        exported_at: Utc::now(),
+        board: db_state.clone(),
+    };
+
+    Ok(serde_json::to_string_pretty(&export)? + "\n")
+}
+
+type EpicWithStories<'a> = (u32, &'a Epic, Vec<(u32, &'a Story)>);
+
+// Epics with their stories, all sorted by id
+fn epics_with_stories(db_state: &DBState) -> Vec<EpicWithStories<'_>> {
+    let mut epics: Vec<_> = db_state
+        .epics
+        .iter()
+        .map(|(epic_id, epic)| {
+            let mut stories: Vec<_> = epic
+                .stories
+                .iter()
+                .filter_map(|story_id| {
+                    db_state
+                        .stories
+                        .get(story_id)
+                        .map(|story| (*story_id, story))
+                })
+                .collect();
+            stories.sort_by_key(|(story_id, _)| *story_id);
+
+            (*epic_id, epic, stories)
+        })
+        .collect();
+
+    epics.sort_by_key(|(epic_id, _, _)| *epic_id);
+    epics
+}
+
+fn format_rfc3339(timestamp: Option<DateTime<Utc>>) -> String {
+    timestamp
+        .map(|timestamp| timestamp.to_rfc3339())
+        .unwrap_or_default()
+}
+
+// Keeps names like `*draft*` or `[WIP]` from being formatted, and line breaks
+// from ending the list item
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::new();
+
+    for c in text.chars() {
+        match c {
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '|' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' | '\r' => escaped.push(' '),
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
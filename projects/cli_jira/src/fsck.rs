@@ -0,0 +1,250 @@
+//! Consistency checks of a board: story membership lives in `Epic::stories` while the
+//! stories themselves are in a separate map, and ids are handed out from `last_item_id`.
+//! Nothing keeps these in sync when a file is edited by hand or a write was cut short.
+
+use core::fmt;
+use itertools::Itertools;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::models::{DBState, Epic, LinkKind};
+
+/// Name of the epic that `repair` puts orphaned stories into.
+pub const RECOVERED_EPIC_NAME: &str = "Recovered stories";
+
+#[derive(Serialize, PartialEq, Eq, Debug, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Problem {
+    /// The epic lists a story that doesn't exist.
+    DanglingStory { epic_id: u32, story_id: u32 },
+    /// The story isn't listed by any epic, so it can't be reached.
+    OrphanedStory { story_id: u32 },
+    /// The story is listed more than once, by these epics.
+    DuplicateStory { story_id: u32, epic_ids: Vec<u32> },
+    /// The id is used by both an epic and a story.
+    DuplicateId { item_id: u32 },
+    /// The id hasn't been handed out yet, so a new item would get it too.
+    IdAboveLastItemId { item_id: u32, last_item_id: u32 },
+    /// The link starts or ends at a story that doesn't exist.
+    DanglingLink { from: u32, kind: LinkKind, to: u32 },
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Problem::DanglingStory { epic_id, story_id } => write!(
+                f,
+                "epic {} lists story {}, which doesn't exist",
+                epic_id, story_id
+            ),
+            Problem::OrphanedStory { story_id } => {
+                write!(f, "story {} doesn't belong to any epic", story_id)
+            }
+            Problem::DuplicateStory { story_id, epic_ids } => write!(
+                f,
+                "story {} is listed more than once, by epics {}",
+                story_id,
+                epic_ids
+                    .iter()
+                    .map(u32::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Problem::DuplicateId { item_id } => {
+                write!(f, "id {} is used by both an epic and a story", item_id)
+            }
+            Problem::IdAboveLastItemId {
+                item_id,
+                last_item_id,
+            } => write!(
+                f,
+                "id {} is above the last handed out id {}",
+                item_id, last_item_id
+            ),
+            Problem::DanglingLink { from, kind, to } => write!(
+                f,
+                "link \"{} {} {}\" refers to a story that doesn't exist",
+                from, kind, to
+            ),
+        }
+    }
+}
+
+/// All problems of the board, sorted by kind and id.
+pub fn check(db_state: &DBState) -> Vec<Problem> {
+    let mut problems = vec![];
+
+    // epics listing each story, once per listing
+    let mut memberships: BTreeMap<u32, Vec<u32>> = BTreeMap::new();
+
+    for (epic_id, epic) in epics_by_id(db_state) {
+        for story_id in &epic.stories {
+            if db_state.stories.contains_key(story_id) {
+                memberships.entry(*story_id).or_default().push(epic_id);
+            } else {
+                problems.push(Problem::DanglingStory {
+                    epic_id,
+                    story_id: *story_id,
+                });
+            }
+        }
+    }
+
+    let story_ids: BTreeSet<u32> = db_state.stories.keys().copied().collect();
+
+    for story_id in &story_ids {
+        if !memberships.contains_key(story_id) {
+            problems.push(Problem::OrphanedStory {
+                story_id: *story_id,
+            });
+        }
+    }
+
+    for (story_id, epic_ids) in memberships {
+        if epic_ids.len() > 1 {
+            problems.push(Problem::DuplicateStory { story_id, epic_ids });
+        }
+    }
+
+    for story_id in &story_ids {
+        if db_state.epics.contains_key(story_id) {
+            problems.push(Problem::DuplicateId { item_id: *story_id });
+        }
+    }
+
+    let item_ids: BTreeSet<u32> = db_state.epics.keys().chain(&story_ids).copied().collect();
+
+    for item_id in item_ids {
+        if item_id > db_state.last_item_id {
+            problems.push(Problem::IdAboveLastItemId {
+                item_id,
+                last_item_id: db_state.last_item_id,
+            });
+        }
+    }
+
+    for link in &db_state.links {
+        if !story_ids.contains(&link.from) || !story_ids.contains(&link.to) {
+            problems.push(Problem::DanglingLink {
+                from: link.from,
+                kind: link.kind,
+                to: link.to,
+            });
+        }
+    }
+
+    problems
+}
+
+/// A copy of the board without the problems found by `check`:
+///
+/// * stories sharing an id with an epic get a new id, which their links and comments
+///   follow,
+/// * `last_item_id` is raised to the highest id,
+/// * dangling ids are dropped from the epics' story lists,
+/// * a story listed more than once only stays where it's listed first (lowest epic id),
+/// * orphaned stories are put into a new "Recovered stories" epic,
+/// * links to stories that don't exist are dropped.
+///
+/// History entries are kept as they are.
+pub fn repair(db_state: &DBState) -> DBState {
+    let mut db_state = db_state.clone();
+
+    let highest_id = db_state
+        .epics
+        .keys()
+        .chain(db_state.stories.keys())
+        .copied()
+        .max()
+        .unwrap_or_default();
+    db_state.last_item_id = db_state.last_item_id.max(highest_id);
+
+    let clashing_ids: Vec<u32> = db_state
+        .stories
+        .keys()
+        .filter(|story_id| db_state.epics.contains_key(story_id))
+        .copied()
+        .sorted()
+        .collect();
+
+    for old_id in clashing_ids {
+        db_state.last_item_id += 1;
+        let new_id = db_state.last_item_id;
+
+        if let Some(story) = db_state.stories.remove(&old_id) {
+            db_state.stories.insert(new_id, story);
+        }
+
+        // epics can't list or be linked to epics, so every use of the id meant the story
+        for epic in db_state.epics.values_mut() {
+            for story_id in &mut epic.stories {
+                if *story_id == old_id {
+                    *story_id = new_id;
+                }
+            }
+        }
+
+        for link in &mut db_state.links {
+            for story_id in [&mut link.from, &mut link.to] {
+                if *story_id == old_id {
+                    *story_id = new_id;
+                }
+            }
+        }
+
+        for comment in &mut db_state.comments {
+            if comment.story_id == old_id {
+                comment.story_id = new_id;
+            }
+        }
+    }
+
+    let mut listed = BTreeSet::new();
+    let epic_ids: Vec<u32> = db_state.epics.keys().copied().sorted().collect();
+
+    for epic_id in epic_ids {
+        if let Some(epic) = db_state.epics.get_mut(&epic_id) {
+            epic.stories.retain(|story_id| {
+                db_state.stories.contains_key(story_id) && listed.insert(*story_id)
+            });
+        }
+    }
+
+    let orphans: Vec<u32> = db_state
+        .stories
+        .keys()
+        .filter(|story_id| !listed.contains(story_id))
+        .copied()
+        .sorted()
+        .collect();
+
+    db_state.links.retain(|link| {
+        db_state.stories.contains_key(&link.from) && db_state.stories.contains_key(&link.to)
+    });
+
+    if !orphans.is_empty() {
+        db_state.last_item_id += 1;
+        db_state.epics.insert(
+            db_state.last_item_id,
+            Epic {
+                stories: orphans,
+                ..Epic::new(
+                    RECOVERED_EPIC_NAME.to_owned(),
+                    "Stories that didn't belong to any epic".to_owned(),
+                )
+            },
+        );
+    }
+
+    db_state
+}
+
+fn epics_by_id(db_state: &DBState) -> Vec<(u32, &Epic)> {
+    let mut epics: Vec<_> = db_state
+        .epics
+        .iter()
+        .map(|(epic_id, epic)| (*epic_id, epic))
+        .collect();
+    epics.sort_by_key(|(epic_id, _)| *epic_id);
+    epics
+}
@@ -0,0 +1,347 @@
+//! Creates epics and stories from a CSV file laid out like `export::to_csv`, e.g. one
+//! exported and then edited in a spreadsheet.
+//!
+//! Only `type` and `name` are required. Ids in the file are just references: a story's
+//! `epic_id` is the `id` of an epic row, or else of an epic already on the board. The
+//! new items get fresh ids, and `created_at`/`updated_at` are ignored.
+//!
+//! Statuses follow the same rules as on the board: an epic can't be imported as done
+//! while some of its stories in the file aren't, and with `auto_resolve_epics` an epic
+//! whose stories are all done is resolved.
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::csv::{self, Record};
+use crate::db::{EpicRef, JiraDatabase};
+use crate::export::CSV_COLUMNS;
+use crate::models::{Epic, Priority, RecordStatus, Story};
+use crate::workflow::Workflow;
+
+/// An epic or story that was, or on a dry run would be, created.
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct ImportedItem {
+    /// "epic" or "story"
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    /// Line of the CSV file the item is on
+    pub line: usize,
+    pub name: String,
+    /// Id of the new item, `None` on a dry run
+    pub id: Option<u32>,
+    /// Name of the epic a story goes to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub epic: Option<String>,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct ImportSummary {
+    pub dry_run: bool,
+    pub items: Vec<ImportedItem>,
+}
+
+impl ImportSummary {
+    pub fn epic_count(&self) -> usize {
+        self.items.iter().filter(|item| item.kind == "epic").count()
+    }
+
+    pub fn story_count(&self) -> usize {
+        self.items
+            .iter()
+            .filter(|item| item.kind == "story")
+            .count()
+    }
+}
+
+enum Row {
+    Epic {
+        line: usize,
+        file_id: Option<u32>,
+        epic: Epic,
+    },
+    Story {
+        line: usize,
+        epic_file_id: u32,
+        story: Story,
+    },
+}
+
+/// Checks the whole file first and, unless `dry_run` is set, then creates its epics
+/// followed by their stories in one change. Nothing is created if any row is invalid.
+pub fn import_csv(db: &JiraDatabase, input: &str, dry_run: bool) -> Result<ImportSummary> {
+    let mut records = csv::parse(input)?.into_iter();
+    let header = records
+        .next()
+        .ok_or_else(|| anyhow!("the CSV file is empty"))?;
+    let columns = parse_header(&header)?;
+
+    let mut rows = vec![];
+    let mut errors = vec![];
+
+    for record in records {
+        match parse_row(&columns, &record, &db.workflow) {
+            Ok(row) => rows.push(row),
+            Err(error) => errors.push(format!("line {}: {}", record.line, error)),
+        }
+    }
+
+    // epics of the file by their id in it
+    let mut file_epics = HashMap::new();
+
+    for row in &rows {
+        if let Row::Epic {
+            line,
+            file_id: Some(file_id),
+            epic,
+        } = row
+        {
+            if file_epics.insert(*file_id, epic.name.clone()).is_some() {
+                errors.push(format!("line {}: epic id {} is used twice", line, file_id));
+            }
+        }
+    }
+
+    // like `JiraDatabase::update_epic_status`, done epics need all stories done
+    if !db.workflow.allow_unfinished_epics {
+        for row in &rows {
+            let Row::Epic {
+                line,
+                file_id: Some(file_id),
+                epic,
+            } = row
+            else {
+                continue;
+            };
+
+            let unfinished = rows
+                .iter()
+                .filter(|row| {
+                    matches!(row, Row::Story { epic_file_id, story, .. }
+                        if epic_file_id == file_id && !db.workflow.is_done(&story.status))
+                })
+                .count();
+
+            if db.workflow.is_done(&epic.status) && unfinished > 0 {
+                errors.push(format!(
+                    "line {}: epic {} is {} but has {} unfinished stories",
+                    line, file_id, epic.status, unfinished
+                ));
+            }
+        }
+    }
+
+    let mut items = vec![];
+
+    for row in &rows {
+        match row {
+            Row::Epic { line, epic, .. } => items.push(ImportedItem {
+                kind: "epic",
+                line: *line,
+                name: epic.name.clone(),
+                id: None,
+                epic: None,
+            }),
+            Row::Story {
+                line,
+                epic_file_id,
+                story,
+            } => {
+                let epic_name = match file_epics.get(epic_file_id) {
+                    Some(name) => Some(name.clone()),
+                    None => db.get_epic(*epic_file_id)?.map(|epic| epic.name),
+                };
+
+                let Some(epic_name) = epic_name else {
+                    errors.push(format!("line {}: unknown epic {}", line, epic_file_id));
+                    continue;
+                };
+
+                items.push(ImportedItem {
+                    kind: "story",
+                    line: *line,
+                    name: story.name.clone(),
+                    id: None,
+                    epic: Some(epic_name),
+                });
+            }
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(anyhow!("invalid CSV file:\n{}", errors.join("\n")));
+    }
+
+    // epics first, so that stories can go to an epic further down the file
+    items.sort_by_key(|item| (item.kind == "story", item.line));
+
+    if dry_run {
+        return Ok(ImportSummary {
+            dry_run: true,
+            items,
+        });
+    }
+
+    // lines of the new items, in the order `create_items` returns their ids
+    let mut lines = vec![];
+    let mut epics = vec![];
+    // index in `epics` of the epics of the file by their id in it
+    let mut epic_indexes = HashMap::new();
+
+    for row in &rows {
+        if let Row::Epic {
+            line,
+            file_id,
+            epic,
+        } = row
+        {
+            if let Some(file_id) = file_id {
+                epic_indexes.insert(*file_id, epics.len());
+            }
+
+            lines.push(*line);
+            epics.push(epic.clone());
+        }
+    }
+
+    let mut stories = vec![];
+
+    for row in &rows {
+        if let Row::Story {
+            line,
+            epic_file_id,
+            story,
+        } = row
+        {
+            let epic = match epic_indexes.get(epic_file_id) {
+                Some(index) => EpicRef::New(*index),
+                None => EpicRef::Existing(*epic_file_id),
+            };
+
+            lines.push(*line);
+            stories.push((epic, story.clone()));
+        }
+    }
+
+    let (epic_ids, story_ids) = db.create_items(epics, stories)?;
+    let ids: HashMap<_, _> = lines
+        .into_iter()
+        .zip(epic_ids.into_iter().chain(story_ids))
+        .collect();
+
+    for item in &mut items {
+        item.id = ids.get(&item.line).copied();
+    }
+
+    Ok(ImportSummary {
+        dry_run: false,
+        items,
+    })
+}
+
+// Index of every known column in the file
+fn parse_header(header: &Record) -> Result<HashMap<&'static str, usize>> {
+    let mut columns = HashMap::new();
+
+    for (index, title) in header.fields.iter().enumerate() {
+        let title = title.trim().to_lowercase();
+        let column = CSV_COLUMNS
+            .iter()
+            .find(|column| **column == title)
+            .ok_or_else(|| {
+                anyhow!(
+                    "unknown column '{}', expected some of: {}",
+                    title,
+                    CSV_COLUMNS.join(", ")
+                )
+            })?;
+
+        columns.insert(*column, index);
+    }
+
+    for required in ["type", "name"] {
+        if !columns.contains_key(required) {
+            return Err(anyhow!("the CSV file has no '{}' column", required));
+        }
+    }
+
+    Ok(columns)
+}
+
+fn parse_row(
+    columns: &HashMap<&'static str, usize>,
+    record: &Record,
+    workflow: &Workflow,
+) -> Result<Row> {
+    // trimmed value of a column, empty if the column or the field is missing
+    let field = |column: &str| {
+        columns
+            .get(column)
+            .and_then(|index| record.fields.get(*index))
+            .map_or("", |value| value.trim())
+    };
+
+    let name = field("name");
+
+    if name.is_empty() {
+        return Err(anyhow!("name is required"));
+    }
+
+    let status = match field("status") {
+        "" => workflow.initial_status(),
+        status => parse_status(status, workflow)?,
+    };
+    let labels: Vec<String> = field("labels")
+        .split(',')
+        .map(str::trim)
+        .filter(|label| !label.is_empty())
+        .map(str::to_owned)
+        .collect();
+
+    match field("type").to_lowercase().as_str() {
+        "epic" => Ok(Row::Epic {
+            line: record.line,
+            file_id: parse_optional(field("id"), "id")?,
+            epic: Epic {
+                status,
+                labels,
+                ..Epic::new(name.to_owned(), field("description").to_owned())
+            },
+        }),
+        "story" => Ok(Row::Story {
+            line: record.line,
+            epic_file_id: parse_optional(field("epic_id"), "epic_id")?
+                .ok_or_else(|| anyhow!("a story needs an epic_id"))?,
+            story: Story {
+                status,
+                priority: parse_optional::<Priority>(field("priority"), "priority")?,
+                assignee: Some(field("assignee").to_owned()).filter(|a| !a.is_empty()),
+                labels,
+                estimate: parse_optional(field("estimate"), "estimate")?,
+                ..Story::new(name.to_owned(), field("description").to_owned())
+            },
+        }),
+        kind => Err(anyhow!("unknown type '{}', expected epic or story", kind)),
+    }
+}
+
+fn parse_status(status: &str, workflow: &Workflow) -> Result<RecordStatus> {
+    let status = workflow.resolve(status.parse().map_err(|error: String| anyhow!(error))?);
+
+    if !workflow.statuses.contains(&status) {
+        return Err(anyhow!("status '{}' is not part of the workflow", status));
+    }
+
+    Ok(status)
+}
+
+fn parse_optional<T: std::str::FromStr>(value: &str, column: &str) -> Result<Option<T>> {
+    if value.is_empty() {
+        return Ok(None);
+    }
+
+    value
+        .parse()
+        .map(Some)
+        .map_err(|_| anyhow!("invalid {} '{}'", column, value))
+}
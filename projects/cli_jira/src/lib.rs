@@ -1,5 +1,15 @@
+pub mod cli;
+pub mod config;
+pub mod csv;
 pub mod db;
+pub mod export;
+pub mod fsck;
+pub mod import;
 pub mod io_utils;
 pub mod models;
 pub mod navigator;
+pub mod search;
+pub mod tui;
 pub mod ui;
+pub mod undo;
+pub mod workflow;
@@ -1,27 +1,124 @@
+use chrono::{DateTime, Utc};
 use core::fmt;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::str::FromStr;
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum Action {
-    NavigateToEpicDetail { epic_id: u32 },
-    NavigateToStoryDetail { epic_id: u32, story_id: u32 },
+    NavigateToEpicDetail {
+        epic_id: u32,
+    },
+    NavigateToStoryDetail {
+        epic_id: u32,
+        story_id: u32,
+    },
     NavigateToPreviousPage,
     CreateEpic,
-    UpdateEpicStatus { epic_id: u32 },
-    DeleteEpic { epic_id: u32 },
-    CreateStory { epic_id: u32 },
-    UpdateStoryStatus { story_id: u32 },
-    DeleteStory { epic_id: u32, story_id: u32 },
+    UpdateEpicStatus {
+        epic_id: u32,
+    },
+    EditEpic {
+        epic_id: u32,
+    },
+    EditEpicLabels {
+        epic_id: u32,
+    },
+    DeleteEpic {
+        epic_id: u32,
+    },
+    CreateStory {
+        epic_id: u32,
+    },
+    UpdateStoryStatus {
+        story_id: u32,
+    },
+    EditStory {
+        story_id: u32,
+    },
+    EditStoryDetails {
+        story_id: u32,
+    },
+    DeleteStory {
+        epic_id: u32,
+        story_id: u32,
+    },
+    MoveStory {
+        epic_id: u32,
+        story_id: u32,
+    },
+    AddComment {
+        story_id: u32,
+    },
+    /// Shows a page of the current story's comments, 0 being the newest ones.
+    ShowComments {
+        page: usize,
+    },
+    AddChecklistItem {
+        story_id: u32,
+    },
+    /// `index` counts from 0, while the pages number the items from 1.
+    ToggleChecklistItem {
+        story_id: u32,
+        index: usize,
+    },
+    RemoveChecklistItem {
+        story_id: u32,
+        index: usize,
+    },
+    LinkStory {
+        story_id: u32,
+    },
+    UnlinkStory {
+        story_id: u32,
+    },
+    /// Opens the board of an epic's stories, or of all stories if `epic_id` is `None`.
+    NavigateToBoard {
+        epic_id: Option<u32>,
+    },
+    /// Sets the status without asking, e.g. when a story is moved to another column.
+    SetStoryStatus {
+        story_id: u32,
+        status: RecordStatus,
+    },
+    Search,
+    NavigateToTrash,
+    RestoreFromTrash,
+    PurgeFromTrash,
+    EmptyTrash,
+    Undo,
+    Redo,
+    FilterList {
+        list: ListKind,
+    },
+    SortList {
+        list: ListKind,
+    },
+    /// Tells the user why an input was refused.
+    ShowMessage {
+        text: String,
+    },
     Exit,
 }
 
-#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+/// Which of the two item lists a filter or sort action applies to.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ListKind {
+    Epics,
+    Stories,
+}
+
+/// Status of an epic or story. Besides the built-in ones a board's workflow can
+/// define its own, which are stored by name.
+#[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Default)]
+#[serde(try_from = "String", into = "String")]
 pub enum RecordStatus {
+    #[default]
     Open,
     InProgress,
     Resolved,
     Closed,
+    Custom(String),
 }
 
 impl fmt::Display for RecordStatus {
@@ -31,16 +128,98 @@ impl fmt::Display for RecordStatus {
             RecordStatus::InProgress => write!(f, "In progress"),
             RecordStatus::Resolved => write!(f, "Resolved"),
             RecordStatus::Closed => write!(f, "Closed"),
+            RecordStatus::Custom(name) => write!(f, "{}", name),
         }
     }
 }
 
-#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+impl FromStr for RecordStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().replace(['_', ' '], "-").as_str() {
+            "open" => Ok(RecordStatus::Open),
+            "in-progress" | "inprogress" => Ok(RecordStatus::InProgress),
+            "resolved" => Ok(RecordStatus::Resolved),
+            "closed" => Ok(RecordStatus::Closed),
+            "" => Err("status can't be empty".to_owned()),
+            _ => Ok(RecordStatus::Custom(s.trim().to_owned())),
+        }
+    }
+}
+
+impl TryFrom<String> for RecordStatus {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+// Serialized by name, e.g. `InProgress` or `Review`
+impl From<RecordStatus> for String {
+    fn from(status: RecordStatus) -> Self {
+        match status {
+            RecordStatus::Open => "Open".to_owned(),
+            RecordStatus::InProgress => "InProgress".to_owned(),
+            RecordStatus::Resolved => "Resolved".to_owned(),
+            RecordStatus::Closed => "Closed".to_owned(),
+            RecordStatus::Custom(name) => name,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy)]
+pub enum Priority {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Priority::Low => write!(f, "Low"),
+            Priority::Medium => write!(f, "Medium"),
+            Priority::High => write!(f, "High"),
+            Priority::Critical => write!(f, "Critical"),
+        }
+    }
+}
+
+impl FromStr for Priority {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "low" => Ok(Priority::Low),
+            "medium" => Ok(Priority::Medium),
+            "high" => Ok(Priority::High),
+            "critical" => Ok(Priority::Critical),
+            _ => Err(format!(
+                "invalid priority '{s}', expected one of: low, medium, high, critical"
+            )),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Default)]
 pub struct Epic {
     pub name: String,
     pub description: String,
     pub status: RecordStatus,
     pub stories: Vec<u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<String>,
+    /// Set by the database when the epic is created; missing for older boards.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<DateTime<Utc>>,
+    /// When the epic was moved to the trash. Its stories are hidden along with it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<DateTime<Utc>>,
 }
 
 #[allow(dead_code)]
@@ -50,16 +229,49 @@ impl Epic {
             name,
             description,
             status: RecordStatus::Open,
-            stories: vec![],
+            ..Default::default()
         }
     }
 }
 
-#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Default)]
 pub struct Story {
     pub name: String,
     pub description: String,
     pub status: RecordStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<Priority>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assignee: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<String>,
+    /// Story points
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimate: Option<u32>,
+    /// Small steps of the story, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub checklist: Vec<ChecklistItem>,
+    /// Set by the database when the story is created; missing for older boards.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<DateTime<Utc>>,
+    /// When the story was moved to the trash; it stays in its epic until purged.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+/// A step of a story that is ticked off on its own, without a status or history.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+pub struct ChecklistItem {
+    pub text: String,
+    pub done: bool,
+}
+
+impl ChecklistItem {
+    pub fn new(text: String) -> Self {
+        ChecklistItem { text, done: false }
+    }
 }
 
 #[allow(dead_code)]
@@ -69,13 +281,260 @@ impl Story {
             name,
             description,
             status: RecordStatus::Open,
+            ..Default::default()
+        }
+    }
+}
+
+/// A single change to an epic or story, as shown in its activity feed.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+pub enum Change {
+    Created,
+    StatusChanged {
+        from: RecordStatus,
+        to: RecordStatus,
+    },
+    /// Any other field, with its old and new value rendered as text ("" when unset).
+    FieldChanged {
+        field: String,
+        from: String,
+        to: String,
+    },
+    Moved {
+        from_epic_id: u32,
+        to_epic_id: u32,
+    },
+    /// The item was put back the way it was before the last change to it.
+    Undone,
+    Redone,
+    Trashed,
+    Restored,
+    ChecklistItemAdded {
+        text: String,
+    },
+    ChecklistItemToggled {
+        text: String,
+        done: bool,
+    },
+    ChecklistItemRemoved {
+        text: String,
+    },
+    /// A link was added, recorded on both stories with `kind` read from that story.
+    Linked {
+        kind: LinkKind,
+        story_id: u32,
+    },
+    Unlinked {
+        kind: LinkKind,
+        story_id: u32,
+    },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Change::Created => write!(f, "created"),
+            Change::StatusChanged { from, to } => write!(f, "status: {} -> {}", from, to),
+            Change::FieldChanged { field, from, to } => {
+                let or_dash = |value: &str| {
+                    if value.is_empty() {
+                        "-".to_owned()
+                    } else {
+                        format!("\"{}\"", value)
+                    }
+                };
+                write!(f, "{}: {} -> {}", field, or_dash(from), or_dash(to))
+            }
+            Change::Moved {
+                from_epic_id,
+                to_epic_id,
+            } => write!(f, "moved from epic {} to epic {}", from_epic_id, to_epic_id),
+            Change::Undone => write!(f, "change undone"),
+            Change::Redone => write!(f, "change redone"),
+            Change::Trashed => write!(f, "moved to the trash"),
+            Change::Restored => write!(f, "restored from the trash"),
+            Change::ChecklistItemAdded { text } => write!(f, "checklist: added \"{}\"", text),
+            Change::ChecklistItemToggled { text, done } => write!(
+                f,
+                "checklist: {} \"{}\"",
+                if *done { "ticked" } else { "unticked" },
+                text
+            ),
+            Change::ChecklistItemRemoved { text } => {
+                write!(f, "checklist: removed \"{}\"", text)
+            }
+            Change::Linked { kind, story_id } => write!(f, "linked: {} {}", kind, story_id),
+            Change::Unlinked { kind, story_id } => write!(f, "unlinked: {} {}", kind, story_id),
+        }
+    }
+}
+
+/// An epic or story in the trash, with the epic it will be restored to.
+#[derive(Serialize, PartialEq, Eq, Debug, Clone)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum TrashedItem {
+    Epic {
+        epic_id: u32,
+        epic: Epic,
+    },
+    Story {
+        epic_id: u32,
+        story_id: u32,
+        story: Story,
+    },
+}
+
+impl TrashedItem {
+    pub fn id(&self) -> u32 {
+        match self {
+            TrashedItem::Epic { epic_id, .. } => *epic_id,
+            TrashedItem::Story { story_id, .. } => *story_id,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            TrashedItem::Epic { epic, .. } => &epic.name,
+            TrashedItem::Story { story, .. } => &story.name,
+        }
+    }
+
+    pub fn deleted_at(&self) -> Option<DateTime<Utc>> {
+        match self {
+            TrashedItem::Epic { epic, .. } => epic.deleted_at,
+            TrashedItem::Story { story, .. } => story.deleted_at,
         }
     }
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+pub struct HistoryEntry {
+    pub item_id: u32,
+    pub timestamp: DateTime<Utc>,
+    pub author: String,
+    pub change: Change,
+}
+
+/// A note on a story, shown on its page. Comments can't be edited or replied to.
+#[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Debug, Clone)]
+pub struct Comment {
+    pub story_id: u32,
+    pub timestamp: DateTime<Utc>,
+    pub author: String,
+    pub text: String,
+}
+
+/// How two stories are linked, read from the story the link starts at: "12 blocks 15".
+#[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy)]
+pub enum LinkKind {
+    Blocks,
+    BlockedBy,
+    RelatesTo,
+    Duplicates,
+    DuplicatedBy,
+}
+
+impl LinkKind {
+    /// The same link read from the other story.
+    pub fn inverse(self) -> Self {
+        match self {
+            LinkKind::Blocks => LinkKind::BlockedBy,
+            LinkKind::BlockedBy => LinkKind::Blocks,
+            LinkKind::RelatesTo => LinkKind::RelatesTo,
+            LinkKind::Duplicates => LinkKind::DuplicatedBy,
+            LinkKind::DuplicatedBy => LinkKind::Duplicates,
+        }
+    }
+}
+
+impl fmt::Display for LinkKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LinkKind::Blocks => write!(f, "blocks"),
+            LinkKind::BlockedBy => write!(f, "blocked by"),
+            LinkKind::RelatesTo => write!(f, "relates to"),
+            LinkKind::Duplicates => write!(f, "duplicates"),
+            LinkKind::DuplicatedBy => write!(f, "duplicated by"),
+        }
+    }
+}
+
+impl FromStr for LinkKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().replace(['_', ' '], "-").as_str() {
+            "blocks" => Ok(LinkKind::Blocks),
+            "blocked-by" => Ok(LinkKind::BlockedBy),
+            "relates-to" => Ok(LinkKind::RelatesTo),
+            "duplicates" => Ok(LinkKind::Duplicates),
+            "duplicated-by" => Ok(LinkKind::DuplicatedBy),
+            _ => Err(format!(
+                "invalid link '{s}', expected one of: blocks, blocked-by, relates-to, duplicates, duplicated-by"
+            )),
+        }
+    }
+}
+
+/// A link between two stories, possibly of different epics. Stored the way round
+/// that `kind` is `Blocks`, `RelatesTo` or `Duplicates`, see `StoryLink::new`.
+#[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Debug, Clone)]
+pub struct StoryLink {
+    pub from: u32,
+    pub to: u32,
+    pub kind: LinkKind,
+}
+
+impl StoryLink {
+    /// "`from` `kind` `to`", turned around for the inverse kinds. `RelatesTo` goes
+    /// both ways, so it always starts at the lower id.
+    pub fn new(from: u32, kind: LinkKind, to: u32) -> Self {
+        match kind {
+            LinkKind::BlockedBy | LinkKind::DuplicatedBy => StoryLink {
+                from: to,
+                to: from,
+                kind: kind.inverse(),
+            },
+            LinkKind::RelatesTo => StoryLink {
+                from: from.min(to),
+                to: from.max(to),
+                kind,
+            },
+            LinkKind::Blocks | LinkKind::Duplicates => StoryLink { from, to, kind },
+        }
+    }
+
+    /// The link as read from `story_id` with the other story's id, `None` if it
+    /// doesn't involve the story.
+    pub fn seen_from(&self, story_id: u32) -> Option<(LinkKind, u32)> {
+        if self.from == story_id {
+            Some((self.kind, self.to))
+        } else if self.to == story_id {
+            Some((self.kind.inverse(), self.from))
+        } else {
+            None
+        }
+    }
+}
+
+/// A story linked to the one being looked at, with the link read from that one.
+#[derive(Serialize, PartialEq, Eq, Debug, Clone)]
+pub struct LinkedStory {
+    pub kind: LinkKind,
+    pub story_id: u32,
+    pub story: Story,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Default)]
 pub struct DBState {
     pub last_item_id: u32,
     pub epics: HashMap<u32, Epic>,
     pub stories: HashMap<u32, Story>,
+    /// Append-only log of changes to all items, oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub history: Vec<HistoryEntry>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub comments: Vec<Comment>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub links: Vec<StoryLink>,
 }
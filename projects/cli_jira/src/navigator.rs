@@ -1,39 +1,102 @@
 use anyhow::{anyhow, Context, Ok, Result};
-use std::rc::Rc;
+use chrono::Utc;
+use std::{cell::RefCell, rc::Rc};
 
 use crate::{
     db::JiraDatabase,
-    models::Action,
-    ui::{EpicDetail, HomePage, Page, Prompts, StoryDetail},
+    models::{Action, ListKind, RecordStatus},
+    ui::{
+        BoardPage, EpicDetail, HomePage, ListView, Page, Prompts, SearchPage, StoryDetail,
+        TrashPage,
+    },
+    undo::UndoStack,
 };
 
 pub struct Navigator {
     pages: Vec<Box<dyn Page>>,
     prompts: Prompts,
     db: Rc<JiraDatabase>,
+    // filter and sort order of the lists, kept until the program exits
+    epic_view: Rc<RefCell<ListView>>,
+    story_view: Rc<RefCell<ListView>>,
+    undo_stack: UndoStack,
 }
 
 impl Navigator {
     pub fn new(db: Rc<JiraDatabase>) -> Self {
-        let home_page = Box::new(HomePage { db: db.clone() });
+        Self::with_prompts(db, Prompts::new())
+    }
+
+    /// Uses `prompts` instead of the ones reading from stdin, e.g. for another frontend.
+    pub fn with_prompts(db: Rc<JiraDatabase>, prompts: Prompts) -> Self {
+        let epic_view = Rc::new(RefCell::new(ListView::default()));
+        let home_page = Box::new(HomePage {
+            db: db.clone(),
+            view: epic_view.clone(),
+        });
 
         Navigator {
             pages: vec![home_page],
-            prompts: Prompts::new(),
+            prompts,
             db,
+            epic_view,
+            story_view: Rc::new(RefCell::new(ListView::default())),
+            undo_stack: UndoStack::default(),
         }
     }
 
-    pub fn get_current_page(&self) -> Option<&Box<dyn Page>> {
-        self.pages.last()
+    pub fn get_list_view(&self, list: ListKind) -> ListView {
+        self.list_view(list).borrow().clone()
+    }
+
+    fn list_view(&self, list: ListKind) -> &Rc<RefCell<ListView>> {
+        match list {
+            ListKind::Epics => &self.epic_view,
+            ListKind::Stories => &self.story_view,
+        }
+    }
+
+    /// Number of pages on the stack, the current one included; 0 once the user quit.
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    pub fn get_current_page(&self) -> Option<&dyn Page> {
+        self.pages.last().map(|page| page.as_ref())
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.undo_stack.can_undo()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.undo_stack.can_redo()
     }
 
     pub fn handle_action(&mut self, action: Action) -> Result<()> {
+        if !changes_board(&action) {
+            return self.perform(action);
+        }
+
+        self.db.start_journal();
+        let result = self.perform(action);
+        let revision = self.db.take_revision();
+        result?;
+
+        if let Some(revision) = revision? {
+            self.undo_stack.push(revision);
+        }
+
+        Ok(())
+    }
+
+    fn perform(&mut self, action: Action) -> Result<()> {
         match action {
             Action::NavigateToEpicDetail { epic_id } => {
                 let epic_detail_page = Box::new(EpicDetail {
                     db: self.db.clone(),
                     epic_id,
+                    view: self.story_view.clone(),
                 });
 
                 self.pages.push(epic_detail_page);
@@ -45,6 +108,7 @@ impl Navigator {
                     db: self.db.clone(),
                     story_id,
                     epic_id,
+                    comment_page: Default::default(),
                 });
 
                 self.pages.push(story_detail_page);
@@ -57,21 +121,48 @@ impl Navigator {
                 Ok(())
             }
             Action::CreateEpic => {
-                let epic = (self.prompts.create_epic)();
+                if let Some(epic) = (self.prompts.create_epic)() {
+                    self.db
+                        .create_epic(epic)
+                        .with_context(|| anyhow!("failed to create epic"))?;
+                }
+
+                Ok(())
+            }
+            Action::UpdateEpicStatus { epic_id } => {
+                let statuses = self.db.next_epic_statuses(epic_id)?;
+
+                if let Some(status) = (self.prompts.update_status)(&statuses) {
+                    self.db
+                        .update_epic_status(epic_id, status)
+                        .with_context(|| anyhow!("failed to update epic ({epic_id}) status"))?;
+                }
+
+                Ok(())
+            }
+            Action::EditEpic { epic_id } => {
+                let epic = self
+                    .db
+                    .get_epic(epic_id)?
+                    .ok_or_else(|| anyhow!("could not find epic ({epic_id})"))?;
+                let epic = (self.prompts.edit_epic)(&epic);
 
                 self.db
-                    .create_epic(epic)
-                    .with_context(|| anyhow!("failed to create epic"))?;
+                    .update_epic(epic_id, epic.name, epic.description)
+                    .with_context(|| anyhow!("failed to edit epic ({epic_id})"))?;
 
                 Ok(())
             }
-            Action::UpdateEpicStatus { epic_id } => {
-                let status = (self.prompts.update_status)()
-                    .ok_or_else(|| anyhow!("Error to get valid status"))?;
+            Action::EditEpicLabels { epic_id } => {
+                let epic = self
+                    .db
+                    .get_epic(epic_id)?
+                    .ok_or_else(|| anyhow!("could not find epic ({epic_id})"))?;
+                let labels = (self.prompts.edit_labels)(&epic.labels);
 
                 self.db
-                    .update_epic_status(epic_id, status)
-                    .with_context(|| anyhow!("failed to update epic ({epic_id}) status"))?;
+                    .update_epic_labels(epic_id, labels)
+                    .with_context(|| anyhow!("failed to edit epic ({epic_id}) labels"))?;
 
                 Ok(())
             }
@@ -89,21 +180,62 @@ impl Navigator {
                 Ok(())
             }
             Action::CreateStory { epic_id } => {
-                let story = (self.prompts.create_story)();
+                if let Some(story) = (self.prompts.create_story)() {
+                    self.db
+                        .create_story(story, epic_id)
+                        .with_context(|| anyhow!("failed to create Story"))?;
+                }
+
+                Ok(())
+            }
+            Action::UpdateStoryStatus { story_id } => {
+                let story = self
+                    .db
+                    .get_story(story_id)?
+                    .ok_or_else(|| anyhow!("could not find Story ({story_id})"))?;
+                let statuses = self.db.workflow.next_statuses(&story.status);
+
+                let Some(status) = (self.prompts.update_status)(&statuses) else {
+                    return Ok(());
+                };
+
+                if self.may_start(story_id, &status)? {
+                    self.db
+                        .update_story_status(story_id, status)
+                        .with_context(|| anyhow!("failed to update Story ({story_id}) status"))?;
+                }
+
+                Ok(())
+            }
+            Action::EditStory { story_id } => {
+                let story = self
+                    .db
+                    .get_story(story_id)?
+                    .ok_or_else(|| anyhow!("could not find Story ({story_id})"))?;
+                let story = (self.prompts.edit_story)(&story);
 
                 self.db
-                    .create_story(story, epic_id)
-                    .with_context(|| anyhow!("failed to create Story"))?;
+                    .update_story(story_id, story.name, story.description)
+                    .with_context(|| anyhow!("failed to edit Story ({story_id})"))?;
 
                 Ok(())
             }
-            Action::UpdateStoryStatus { story_id } => {
-                let status = (self.prompts.update_status)()
-                    .ok_or_else(|| anyhow!("Error to get valid status"))?;
+            Action::EditStoryDetails { story_id } => {
+                let story = self
+                    .db
+                    .get_story(story_id)?
+                    .ok_or_else(|| anyhow!("could not find Story ({story_id})"))?;
+                let story = (self.prompts.edit_story_details)(&story);
 
                 self.db
-                    .update_story_status(story_id, status)
-                    .with_context(|| anyhow!("failed to update Story ({story_id}) status"))?;
+                    .update_story_details(
+                        story_id,
+                        story.priority,
+                        story.assignee,
+                        story.labels,
+                        story.estimate,
+                    )
+                    .with_context(|| anyhow!("failed to edit Story ({story_id}) details"))?;
 
                 Ok(())
             }
@@ -120,6 +252,214 @@ impl Navigator {
 
                 Ok(())
             }
+            Action::MoveStory { epic_id, story_id } => {
+                let epics: Vec<_> = self
+                    .db
+                    .list_epics()?
+                    .into_iter()
+                    .filter(|(id, _)| *id != epic_id)
+                    .collect();
+
+                if let Some(to_epic_id) = (self.prompts.move_story)(&epics) {
+                    self.db
+                        .move_story(story_id, epic_id, to_epic_id)
+                        .with_context(|| anyhow!("failed to move Story ({story_id})"))?;
+
+                    self.pages.pop();
+                }
+
+                Ok(())
+            }
+            Action::AddComment { story_id } => {
+                let text = (self.prompts.add_comment)();
+
+                if !text.trim().is_empty() {
+                    self.db
+                        .add_comment(story_id, text)
+                        .with_context(|| anyhow!("failed to comment on Story ({story_id})"))?;
+
+                    // back to the newest comments, so that the new one is shown
+                    self.perform(Action::ShowComments { page: 0 })?;
+                }
+
+                Ok(())
+            }
+            Action::ShowComments { page } => {
+                if let Some(story_detail) = self
+                    .get_current_page()
+                    .and_then(|current| current.as_any().downcast_ref::<StoryDetail>())
+                {
+                    story_detail.comment_page.set(page);
+                }
+
+                Ok(())
+            }
+            Action::AddChecklistItem { story_id } => {
+                let text = (self.prompts.add_checklist_item)();
+
+                if !text.trim().is_empty() {
+                    self.db
+                        .add_checklist_item(story_id, text)
+                        .with_context(|| {
+                            anyhow!("failed to add to Story ({story_id}) checklist")
+                        })?;
+                }
+
+                Ok(())
+            }
+            Action::ToggleChecklistItem { story_id, index } => {
+                self.db
+                    .toggle_checklist_item(story_id, index)
+                    .with_context(|| anyhow!("failed to update Story ({story_id}) checklist"))?;
+
+                Ok(())
+            }
+            Action::RemoveChecklistItem { story_id, index } => {
+                self.db
+                    .remove_checklist_item(story_id, index)
+                    .with_context(|| anyhow!("failed to update Story ({story_id}) checklist"))?;
+
+                Ok(())
+            }
+            Action::LinkStory { story_id } => {
+                if let Some((kind, other_id)) = (self.prompts.link_story)() {
+                    // a wrong id or a cycle is shown on the page instead of failing the action
+                    if let Err(error) = self.db.check_link(story_id, kind, other_id) {
+                        (self.prompts.message)(&error.to_string());
+                        return Ok(());
+                    }
+
+                    self.db
+                        .link_stories(story_id, kind, other_id)
+                        .with_context(|| anyhow!("failed to link Story ({story_id})"))?;
+                }
+
+                Ok(())
+            }
+            Action::UnlinkStory { story_id } => {
+                let links = self.db.links_of(story_id)?;
+
+                if let Some(linked) = (self.prompts.unlink_story)(&links).and_then(|i| links.get(i))
+                {
+                    self.db
+                        .unlink_stories(story_id, linked.kind, linked.story_id)
+                        .with_context(|| anyhow!("failed to unlink Story ({story_id})"))?;
+                }
+
+                Ok(())
+            }
+            Action::NavigateToBoard { epic_id } => {
+                self.pages.push(Box::new(BoardPage {
+                    db: self.db.clone(),
+                    epic_id,
+                }));
+
+                Ok(())
+            }
+            Action::SetStoryStatus { story_id, status } => {
+                if self.may_start(story_id, &status)? {
+                    self.db
+                        .update_story_status(story_id, status)
+                        .with_context(|| anyhow!("failed to update Story ({story_id}) status"))?;
+                }
+
+                Ok(())
+            }
+            Action::Search => {
+                let query = (self.prompts.search)();
+
+                if query.trim().is_empty() {
+                    return Ok(());
+                }
+
+                // a new search replaces the results of the previous one
+                if self
+                    .get_current_page()
+                    .is_some_and(|page| page.as_any().is::<SearchPage>())
+                {
+                    self.pages.pop();
+                }
+
+                self.pages.push(Box::new(SearchPage {
+                    query: query.trim().to_owned(),
+                    db: self.db.clone(),
+                }));
+
+                Ok(())
+            }
+            Action::NavigateToTrash => {
+                self.pages.push(Box::new(TrashPage {
+                    db: self.db.clone(),
+                }));
+
+                Ok(())
+            }
+            Action::RestoreFromTrash => {
+                let items = self.db.list_trash()?;
+
+                if let Some(item_id) = (self.prompts.restore_from_trash)(&items) {
+                    self.db
+                        .restore_from_trash(item_id)
+                        .with_context(|| anyhow!("failed to restore item ({item_id})"))?;
+                }
+
+                Ok(())
+            }
+            Action::PurgeFromTrash => {
+                let items = self.db.list_trash()?;
+
+                if let Some(item_id) = (self.prompts.purge_from_trash)(&items) {
+                    self.db
+                        .purge_from_trash(item_id)
+                        .with_context(|| anyhow!("failed to purge item ({item_id})"))?;
+                }
+
+                Ok(())
+            }
+            Action::EmptyTrash => {
+                if (self.prompts.empty_trash)() {
+                    self.db
+                        .purge_trash(Utc::now())
+                        .with_context(|| anyhow!("failed to empty the trash"))?;
+                }
+
+                Ok(())
+            }
+            Action::Undo => {
+                self.undo_stack
+                    .undo(&self.db)
+                    .with_context(|| anyhow!("failed to undo"))?;
+                self.drop_stale_pages()
+            }
+            Action::Redo => {
+                self.undo_stack
+                    .redo(&self.db)
+                    .with_context(|| anyhow!("failed to redo"))?;
+                self.drop_stale_pages()
+            }
+            Action::FilterList { list } => {
+                let view = (self.prompts.filter_list)(
+                    &self.get_list_view(list),
+                    list,
+                    &self.db.workflow.statuses,
+                );
+
+                *self.list_view(list).borrow_mut() = view;
+
+                Ok(())
+            }
+            Action::SortList { list } => {
+                let sort = (self.prompts.sort_list)(self.get_list_view(list).sort);
+
+                self.list_view(list).borrow_mut().sort = sort;
+
+                Ok(())
+            }
+            Action::ShowMessage { text } => {
+                (self.prompts.message)(&text);
+
+                Ok(())
+            }
             Action::Exit => {
                 self.pages.clear();
 
@@ -128,13 +468,71 @@ impl Navigator {
         }
     }
 
-    // Private functions used for testing
+    // Starting a story that unfinished stories block has to be confirmed
+    fn may_start(&self, story_id: u32, status: &RecordStatus) -> Result<bool> {
+        let blockers = self.db.blockers_on_start(story_id, status.clone())?;
 
-    pub fn _get_page_count(&self) -> usize {
-        self.pages.len()
+        Ok(blockers.is_empty() || (self.prompts.start_blocked_story)(&blockers))
+    }
+
+    // Leaves the pages of items that an undo or redo deleted
+    fn drop_stale_pages(&mut self) -> Result<()> {
+        while let Some(page) = self.pages.last() {
+            let exists = if let Some(page) = page.as_any().downcast_ref::<EpicDetail>() {
+                self.db.get_epic(page.epic_id)?.is_some()
+            } else if let Some(page) = page.as_any().downcast_ref::<BoardPage>() {
+                match page.epic_id {
+                    Some(epic_id) => self.db.get_epic(epic_id)?.is_some(),
+                    None => true,
+                }
+            } else if let Some(page) = page.as_any().downcast_ref::<StoryDetail>() {
+                self.db.get_story(page.story_id)?.is_some()
+                    && self.db.epic_id_of_story(page.story_id).ok() == Some(page.epic_id)
+            } else {
+                true
+            };
+
+            if exists {
+                break;
+            }
+
+            self.pages.pop();
+        }
+
+        Ok(())
     }
 
+    // Private functions used for testing
+
     pub fn _set_prompts(&mut self, prompts: Prompts) {
         self.prompts = prompts;
     }
 }
+
+// Actions that can edit epics, stories, comments or links, and so can be undone
+fn changes_board(action: &Action) -> bool {
+    matches!(
+        action,
+        Action::CreateEpic
+            | Action::UpdateEpicStatus { .. }
+            | Action::EditEpic { .. }
+            | Action::EditEpicLabels { .. }
+            | Action::DeleteEpic { .. }
+            | Action::CreateStory { .. }
+            | Action::UpdateStoryStatus { .. }
+            | Action::EditStory { .. }
+            | Action::EditStoryDetails { .. }
+            | Action::DeleteStory { .. }
+            | Action::MoveStory { .. }
+            | Action::AddChecklistItem { .. }
+            | Action::ToggleChecklistItem { .. }
+            | Action::RemoveChecklistItem { .. }
+            | Action::SetStoryStatus { .. }
+            | Action::AddComment { .. }
+            | Action::LinkStory { .. }
+            | Action::UnlinkStory { .. }
+            | Action::RestoreFromTrash
+            | Action::PurgeFromTrash
+            | Action::EmptyTrash
+    )
+}
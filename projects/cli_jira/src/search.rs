@@ -0,0 +1,82 @@
+use serde::Serialize;
+
+use crate::models::{DBState, Epic, Story};
+
+/// An epic or story whose name or description matches a search.
+#[derive(Serialize, PartialEq, Eq, Debug, Clone)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum SearchHit {
+    Epic {
+        epic_id: u32,
+        epic: Epic,
+    },
+    Story {
+        epic_id: u32,
+        story_id: u32,
+        story: Story,
+    },
+}
+
+impl SearchHit {
+    pub fn id(&self) -> u32 {
+        match self {
+            SearchHit::Epic { epic_id, .. } => *epic_id,
+            SearchHit::Story { story_id, .. } => *story_id,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            SearchHit::Epic { epic, .. } => &epic.name,
+            SearchHit::Story { story, .. } => &story.name,
+        }
+    }
+}
+
+/// Finds the epics and stories whose name or description contains every
+/// whitespace-separated term of `query`, ignoring case. Epics come first, each
+/// group sorted by id.
+pub fn search(db_state: &DBState, query: &str) -> Vec<SearchHit> {
+    let terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+
+    if terms.is_empty() {
+        return vec![];
+    }
+
+    let matches = |name: &str, description: &str| {
+        let text = format!("{}\n{}", name, description).to_lowercase();
+        terms.iter().all(|term| text.contains(term))
+    };
+
+    let mut epic_hits = vec![];
+    let mut story_hits = vec![];
+
+    for (epic_id, epic) in &db_state.epics {
+        if matches(&epic.name, &epic.description) {
+            epic_hits.push(SearchHit::Epic {
+                epic_id: *epic_id,
+                epic: epic.clone(),
+            });
+        }
+
+        for story_id in &epic.stories {
+            let Some(story) = db_state.stories.get(story_id) else {
+                continue;
+            };
+
+            if matches(&story.name, &story.description) {
+                story_hits.push(SearchHit::Story {
+                    epic_id: *epic_id,
+                    story_id: *story_id,
+                    story: story.clone(),
+                });
+            }
+        }
+    }
+
+    epic_hits.sort_by_key(SearchHit::id);
+    story_hits.sort_by_key(SearchHit::id);
+
+    epic_hits.extend(story_hits);
+    epic_hits
+}
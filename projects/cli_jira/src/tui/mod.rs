@@ -0,0 +1,285 @@
+//! Full-screen frontend: the same pages and actions as the plain one, drawn with
+//! ratatui and driven by single key presses instead of lines of input.
+
+use anyhow::{anyhow, Result};
+use ratatui::backend::Backend;
+use ratatui::buffer::Buffer;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Paragraph, Row, Table, TableState, Wrap};
+use ratatui::{Frame, Terminal};
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use crate::db::JiraDatabase;
+use crate::models::Action;
+use crate::navigator::Navigator;
+
+mod prompts;
+mod view;
+
+pub use view::{page_view, PageView, RowView};
+
+/// Where key presses come from: the terminal, or a script in tests.
+pub trait EventSource {
+    /// The next event, `None` once a script has run out.
+    fn next_event(&mut self) -> Result<Option<Event>>;
+}
+
+pub struct TerminalEvents;
+
+impl EventSource for TerminalEvents {
+    fn next_event(&mut self) -> Result<Option<Event>> {
+        Ok(Some(event::read()?))
+    }
+}
+
+/// Scripted events; keep a clone to queue more while the app runs.
+impl EventSource for Rc<RefCell<VecDeque<Event>>> {
+    fn next_event(&mut self) -> Result<Option<Event>> {
+        Ok(self.borrow_mut().pop_front())
+    }
+}
+
+/// Terminal and input shared by the app and its modal prompts.
+pub(crate) struct Screen<B: Backend> {
+    terminal: Terminal<B>,
+    events: Box<dyn EventSource>,
+    /// Last frame of the page, drawn under the modals
+    background: Buffer,
+}
+
+impl<B: Backend> Screen<B> {
+    /// Next key press, `None` if the input has run out.
+    fn next_key(&mut self) -> Result<Option<KeyEvent>> {
+        loop {
+            match self.events.next_event()? {
+                Some(Event::Key(key)) if key.kind != KeyEventKind::Release => return Ok(Some(key)),
+                Some(_) => continue,
+                None => return Ok(None),
+            }
+        }
+    }
+}
+
+pub struct App<B: Backend> {
+    navigator: Navigator,
+    screen: Rc<RefCell<Screen<B>>>,
+    /// Selected row of every open page, bottom of the stack first
+    selections: Vec<TableState>,
+    /// Error of the last action, shown until the next key press
+    status: Option<String>,
+}
+
+impl<B: Backend + 'static> App<B> {
+    pub fn new(db: Rc<JiraDatabase>, terminal: Terminal<B>, events: Box<dyn EventSource>) -> Self {
+        let screen = Rc::new(RefCell::new(Screen {
+            terminal,
+            events,
+            background: Buffer::default(),
+        }));
+
+        App {
+            navigator: Navigator::with_prompts(db, prompts::prompts(screen.clone())),
+            screen,
+            selections: vec![TableState::default()],
+            status: None,
+        }
+    }
+
+    /// Draws and handles key presses until the last page is closed or the input
+    /// runs out.
+    pub fn run(&mut self) -> Result<()> {
+        loop {
+            if self.navigator.get_current_page().is_none() {
+                return Ok(());
+            }
+
+            self.draw()?;
+
+            let Some(key) = self.screen.borrow_mut().next_key()? else {
+                return Ok(());
+            };
+
+            self.handle_key(key)?;
+        }
+    }
+
+    /// The frame drawn last.
+    pub fn last_frame(&self) -> Buffer {
+        self.screen.borrow().background.clone()
+    }
+
+    pub fn draw(&mut self) -> Result<()> {
+        let page = self
+            .navigator
+            .get_current_page()
+            .ok_or_else(|| anyhow!("no page to draw"))?;
+        let view = page_view(page)?;
+
+        let selection = self
+            .selections
+            .last_mut()
+            .ok_or_else(|| anyhow!("no page to draw"))?;
+        clamp_selection(selection, view.rows.len());
+
+        let status = self.status.clone();
+        let mut screen = self.screen.borrow_mut();
+        let frame = screen
+            .terminal
+            .draw(|frame| draw_page(frame, &view, selection, status.as_deref()))?;
+        let buffer = frame.buffer.clone();
+
+        screen.background = buffer;
+        Ok(())
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Result<()> {
+        self.status = None;
+
+        let Some(page) = self.navigator.get_current_page() else {
+            return Ok(());
+        };
+        let view = page_view(page)?;
+        let selection = self
+            .selections
+            .last_mut()
+            .ok_or_else(|| anyhow!("no page selected"))?;
+        let last_row = view.rows.len().saturating_sub(1);
+        let selected = selection.selected().unwrap_or_default();
+
+        let action = match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                Some(Action::Exit)
+            }
+            KeyCode::Up => {
+                selection.select(Some(selected.saturating_sub(1)));
+                None
+            }
+            KeyCode::Down => {
+                selection.select(Some((selected + 1).min(last_row)));
+                None
+            }
+            KeyCode::PageUp => {
+                selection.select(Some(selected.saturating_sub(PAGE_STEP)));
+                None
+            }
+            KeyCode::PageDown => {
+                selection.select(Some((selected + PAGE_STEP).min(last_row)));
+                None
+            }
+            KeyCode::Home => {
+                selection.select(Some(0));
+                None
+            }
+            KeyCode::End => {
+                selection.select(Some(last_row));
+                None
+            }
+            KeyCode::Esc => Some(Action::NavigateToPreviousPage),
+            KeyCode::Enter => match view.rows.get(selected).and_then(|row| row.id) {
+                Some(id) => page.handle_input(&id.to_string())?,
+                None => None,
+            },
+            KeyCode::Char(c) if view.row_keys.contains(&c) => {
+                match view.rows.get(selected).and_then(|row| row.id) {
+                    Some(id) => page.handle_input(&format!("{id}{c}"))?,
+                    None => None,
+                }
+            }
+            KeyCode::Char(c) => page.handle_input(&c.to_string())?,
+            _ => None,
+        };
+
+        if let Some(action) = action {
+            if let Err(error) = self.navigator.handle_action(action) {
+                self.status = Some(format!("{:#}", error));
+            }
+
+            self.selections
+                .resize_with(self.navigator.page_count(), TableState::default);
+        }
+
+        Ok(())
+    }
+}
+
+const PAGE_STEP: usize = 10;
+
+fn clamp_selection(selection: &mut TableState, rows: usize) {
+    if rows == 0 {
+        selection.select(None);
+    } else {
+        let selected = selection.selected().unwrap_or_default();
+        selection.select(Some(selected.min(rows - 1)));
+    }
+}
+
+fn draw_page(frame: &mut Frame, view: &PageView, selection: &mut TableState, status: Option<&str>) {
+    let [details_area, table_area, status_area, hints_area] = Layout::vertical([
+        Constraint::Length(view.details.len() as u16 + 2),
+        Constraint::Min(3),
+        Constraint::Length(1),
+        Constraint::Length(2),
+    ])
+    .areas(frame.area());
+
+    let details = Paragraph::new(
+        view.details
+            .iter()
+            .map(|line| Line::from(line.as_str()))
+            .collect::<Vec<_>>(),
+    )
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(view.title.as_str()),
+    );
+    frame.render_widget(details, details_area);
+
+    let rows = view
+        .rows
+        .iter()
+        .map(|row| Row::new(row.cells.iter().map(String::as_str)));
+    let table = Table::new(rows, view.widths.iter().copied())
+        .header(
+            Row::new(view.header.iter().map(String::as_str))
+                .style(Style::default().add_modifier(Modifier::BOLD)),
+        )
+        .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(view.list_title),
+        );
+    frame.render_stateful_widget(table, table_area, selection);
+
+    if let Some(status) = status {
+        frame.render_widget(
+            Paragraph::new(status).style(Style::default().fg(Color::Red)),
+            status_area,
+        );
+    }
+
+    frame.render_widget(
+        Paragraph::new(format!(
+            "[↑↓] select | [enter] open | [esc] back | {}",
+            view.hints
+        ))
+        .wrap(Wrap { trim: true }),
+        hints_area,
+    );
+}
+
+/// Runs the full-screen frontend on the real terminal until the user quits.
+pub fn run(db: Rc<JiraDatabase>) -> Result<()> {
+    let terminal = ratatui::init();
+
+    let result = App::new(db, terminal, Box::new(TerminalEvents)).run();
+
+    ratatui::restore();
+    result
+}
@@ -0,0 +1,511 @@
+use anyhow::Result;
+use ratatui::backend::Backend;
+use ratatui::crossterm::event::KeyCode;
+use ratatui::layout::{Constraint, Flex, Layout, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Clear, List, ListState, Paragraph};
+use ratatui::Frame;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::Screen;
+use crate::models::{Epic, LinkKind, ListKind, Priority, RecordStatus, Story, TrashedItem};
+use crate::ui::{ListView, Prompts, SortKey};
+
+type SharedScreen<B> = Rc<RefCell<Screen<B>>>;
+
+/// `Prompts` shown as modals over the current page. Terminal errors count as
+/// cancelling the prompt.
+pub(crate) fn prompts<B: Backend + 'static>(screen: SharedScreen<B>) -> Prompts {
+    Prompts {
+        create_epic: {
+            let screen = screen.clone();
+            Box::new(move || {
+                let values = form(&screen, "Create epic", &[("Name", ""), ("Description", "")]);
+
+                values.map(|values| Epic::new(values[0].clone(), values[1].clone()))
+            })
+        },
+        create_story: {
+            let screen = screen.clone();
+            Box::new(move || {
+                let values = form(
+                    &screen,
+                    "Create story",
+                    &[("Name", ""), ("Description", "")],
+                );
+
+                values.map(|values| Story::new(values[0].clone(), values[1].clone()))
+            })
+        },
+        edit_epic: {
+            let screen = screen.clone();
+            Box::new(move |epic| {
+                let fields = [
+                    ("Name", epic.name.as_str()),
+                    ("Description", &epic.description),
+                ];
+
+                match form(&screen, "Edit epic", &fields) {
+                    Some(values) => Epic {
+                        name: values[0].clone(),
+                        description: values[1].clone(),
+                        ..epic.clone()
+                    },
+                    None => epic.clone(),
+                }
+            })
+        },
+        edit_story: {
+            let screen = screen.clone();
+            Box::new(move |story| {
+                let fields = [
+                    ("Name", story.name.as_str()),
+                    ("Description", &story.description),
+                ];
+
+                match form(&screen, "Edit story", &fields) {
+                    Some(values) => Story {
+                        name: values[0].clone(),
+                        description: values[1].clone(),
+                        ..story.clone()
+                    },
+                    None => story.clone(),
+                }
+            })
+        },
+        edit_labels: {
+            let screen = screen.clone();
+            Box::new(move |labels| {
+                let current = labels.join(", ");
+
+                match form(&screen, "Labels", &[("Comma separated", &current)]) {
+                    Some(values) => split_list(&values[0]),
+                    None => labels.to_vec(),
+                }
+            })
+        },
+        edit_story_details: {
+            let screen = screen.clone();
+            Box::new(move |story| {
+                let priority = story.priority.map(|p| p.to_string()).unwrap_or_default();
+                let assignee = story.assignee.clone().unwrap_or_default();
+                let labels = story.labels.join(", ");
+                let estimate = story.estimate.map(|e| e.to_string()).unwrap_or_default();
+                let fields = [
+                    ("Priority (low, medium, high, critical)", priority.as_str()),
+                    ("Assignee", &assignee),
+                    ("Labels, comma separated", &labels),
+                    ("Estimate in story points", &estimate),
+                ];
+
+                let Some(values) = form(&screen, "Edit details", &fields) else {
+                    return story.clone();
+                };
+
+                Story {
+                    // invalid values keep the current one
+                    priority: optional(&values[0])
+                        .map_or(Some(None), |p| p.parse::<Priority>().ok().map(Some))
+                        .unwrap_or(story.priority),
+                    assignee: optional(&values[1]),
+                    labels: split_list(&values[2]),
+                    estimate: optional(&values[3])
+                        .map_or(Some(None), |e| e.parse::<u32>().ok().map(Some))
+                        .unwrap_or(story.estimate),
+                    ..story.clone()
+                }
+            })
+        },
+        delete_epic: {
+            let screen = screen.clone();
+            Box::new(move || confirm(&screen, "Move this epic and all its stories to the trash?"))
+        },
+        delete_story: {
+            let screen = screen.clone();
+            Box::new(move || confirm(&screen, "Move this story to the trash?"))
+        },
+        update_status: {
+            let screen = screen.clone();
+            Box::new(move |statuses| {
+                if statuses.is_empty() {
+                    message(&screen, "This status is final and can't be changed.");
+                    return None;
+                }
+
+                let options: Vec<_> = statuses.iter().map(|s| s.to_string()).collect();
+
+                choose(&screen, "New status", &options).map(|index| statuses[index].clone())
+            })
+        },
+        move_story: {
+            let screen = screen.clone();
+            Box::new(move |epics| {
+                let options: Vec<_> = epics
+                    .iter()
+                    .map(|(epic_id, epic)| format!("{} - {}", epic_id, epic.name))
+                    .collect();
+
+                choose(&screen, "Move story to epic", &options).map(|index| epics[index].0)
+            })
+        },
+        link_story: {
+            let screen = screen.clone();
+            Box::new(move || {
+                let kinds = [
+                    LinkKind::Blocks,
+                    LinkKind::BlockedBy,
+                    LinkKind::RelatesTo,
+                    LinkKind::Duplicates,
+                    LinkKind::DuplicatedBy,
+                ];
+                let options: Vec<_> = kinds.iter().map(|kind| kind.to_string()).collect();
+                let kind = kinds[choose(&screen, "This story", &options)?];
+
+                let values = form(&screen, "Link story", &[("Id of the other story", "")])?;
+
+                Some((kind, values[0].trim().parse::<u32>().ok()?))
+            })
+        },
+        unlink_story: {
+            let screen = screen.clone();
+            Box::new(move |links| {
+                if links.is_empty() {
+                    message(&screen, "This story has no links.");
+                    return None;
+                }
+
+                let options: Vec<_> = links
+                    .iter()
+                    .map(|linked| {
+                        format!(
+                            "{} {} - {}",
+                            linked.kind, linked.story_id, linked.story.name
+                        )
+                    })
+                    .collect();
+
+                choose(&screen, "Remove link", &options)
+            })
+        },
+        start_blocked_story: {
+            let screen = screen.clone();
+            Box::new(move |blockers| {
+                let story_ids: Vec<_> = blockers
+                    .iter()
+                    .map(|(story_id, _)| story_id.to_string())
+                    .collect();
+
+                confirm(
+                    &screen,
+                    &format!(
+                        "Blocked by unfinished stories {}. Start it anyway?",
+                        story_ids.join(", ")
+                    ),
+                )
+            })
+        },
+        add_checklist_item: {
+            let screen = screen.clone();
+            Box::new(move || {
+                form(&screen, "Add checklist item", &[("Item", "")])
+                    .map(|values| values[0].clone())
+                    .unwrap_or_default()
+            })
+        },
+        add_comment: {
+            let screen = screen.clone();
+            Box::new(move || {
+                form(&screen, "Add comment", &[("Comment", "")])
+                    .map(|values| values[0].clone())
+                    .unwrap_or_default()
+            })
+        },
+        search: {
+            let screen = screen.clone();
+            Box::new(move || {
+                form(&screen, "Search", &[("Epics and stories containing", "")])
+                    .map(|values| values[0].clone())
+                    .unwrap_or_default()
+            })
+        },
+        filter_list: {
+            let screen = screen.clone();
+            Box::new(move |view, list, statuses| filter(&screen, view, list, statuses))
+        },
+        sort_list: {
+            let screen = screen.clone();
+            Box::new(move |current| {
+                let keys = [
+                    SortKey::Id,
+                    SortKey::Name,
+                    SortKey::Status,
+                    SortKey::Created,
+                ];
+                let options: Vec<_> = keys.iter().map(|key| key.to_string()).collect();
+
+                choose(&screen, "Sort by", &options).map_or(current, |index| keys[index])
+            })
+        },
+        restore_from_trash: {
+            let screen = screen.clone();
+            Box::new(move |items| pick_trashed_item(&screen, "Restore", items))
+        },
+        purge_from_trash: {
+            let screen = screen.clone();
+            Box::new(move |items| pick_trashed_item(&screen, "Delete for good", items))
+        },
+        empty_trash: {
+            let screen = screen.clone();
+            Box::new(move || confirm(&screen, "Delete everything in the trash for good?"))
+        },
+        message: Box::new(move |text| message(&screen, text)),
+    }
+}
+
+fn optional(value: &str) -> Option<String> {
+    let value = value.trim();
+
+    (!value.is_empty()).then(|| value.to_owned())
+}
+
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|item| item.trim().to_owned())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+fn filter<B: Backend>(
+    screen: &SharedScreen<B>,
+    view: &ListView,
+    list: ListKind,
+    statuses: &[RecordStatus],
+) -> ListView {
+    let hidden = view
+        .hidden_statuses
+        .iter()
+        .map(|status| status.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let label = view.label.clone().unwrap_or_default();
+    let assignee = view.assignee.clone().unwrap_or_default();
+    let all_statuses = statuses
+        .iter()
+        .map(|status| status.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let hidden_label = format!("Hide statuses ({})", all_statuses);
+
+    let mut fields = vec![
+        (hidden_label.as_str(), hidden.as_str()),
+        ("Only with label", &label),
+    ];
+
+    if list == ListKind::Stories {
+        fields.push(("Only assigned to", &assignee));
+    }
+
+    let Some(values) = form(screen, "Filter", &fields) else {
+        return view.clone();
+    };
+
+    ListView {
+        // names the workflow doesn't know are dropped
+        hidden_statuses: split_list(&values[0])
+            .iter()
+            .filter_map(|name| {
+                statuses
+                    .iter()
+                    .find(|status| status.to_string().eq_ignore_ascii_case(name))
+                    .or_else(|| {
+                        let parsed = name.parse::<RecordStatus>().ok()?;
+                        statuses.iter().find(|status| **status == parsed)
+                    })
+                    .cloned()
+            })
+            .collect(),
+        label: optional(&values[1]),
+        assignee: values.get(2).and_then(|value| optional(value)),
+        ..view.clone()
+    }
+}
+
+fn pick_trashed_item<B: Backend>(
+    screen: &SharedScreen<B>,
+    title: &str,
+    items: &[TrashedItem],
+) -> Option<u32> {
+    if items.is_empty() {
+        message(screen, "The trash is empty.");
+        return None;
+    }
+
+    let options: Vec<_> = items
+        .iter()
+        .map(|item| format!("{} - {}", item.id(), item.name()))
+        .collect();
+
+    choose(screen, title, &options).map(|index| items[index].id())
+}
+
+/// Area of `width` x `height` in the middle of `area`.
+fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let [area] = Layout::horizontal([Constraint::Length(width)])
+        .flex(Flex::Center)
+        .areas(area);
+    let [area] = Layout::vertical([Constraint::Length(height)])
+        .flex(Flex::Center)
+        .areas(area);
+
+    area
+}
+
+// Draws the page below and a modal on top of it
+fn draw_modal<B: Backend>(
+    screen: &mut Screen<B>,
+    height: u16,
+    render: impl FnOnce(&mut Frame, Rect),
+) -> Result<()> {
+    let background = screen.background.clone();
+
+    screen.terminal.draw(|frame| {
+        frame.buffer_mut().merge(&background);
+
+        let area = centered(frame.area(), 60, height);
+        frame.render_widget(Clear, area);
+        render(frame, area);
+    })?;
+
+    Ok(())
+}
+
+/// Text fields with initial values; tab/arrows move between them, enter submits all
+/// and esc cancels.
+fn form<B: Backend>(
+    screen: &SharedScreen<B>,
+    title: &str,
+    fields: &[(&str, &str)],
+) -> Option<Vec<String>> {
+    let mut screen = screen.borrow_mut();
+    let mut values: Vec<String> = fields.iter().map(|(_, value)| value.to_string()).collect();
+    let mut focus = 0;
+
+    loop {
+        let lines: Vec<Line> = fields
+            .iter()
+            .zip(&values)
+            .enumerate()
+            .flat_map(|(index, ((label, _), value))| {
+                let (marker, cursor) = if index == focus {
+                    ("> ", "_")
+                } else {
+                    ("  ", "")
+                };
+
+                [
+                    Line::from(format!("{}{}:", marker, label)),
+                    Line::from(format!("  {}{}", value, cursor)),
+                ]
+            })
+            .collect();
+
+        let height = lines.len() as u16 + 3;
+        draw_modal(&mut screen, height, |frame, area| {
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .title_bottom("[tab] next field | [enter] ok | [esc] cancel");
+            frame.render_widget(Paragraph::new(lines).block(block), area);
+        })
+        .ok()?;
+
+        let key = screen.next_key().ok()??;
+
+        match key.code {
+            KeyCode::Esc => return None,
+            KeyCode::Enter => return Some(values),
+            KeyCode::Tab | KeyCode::Down => focus = (focus + 1) % fields.len(),
+            KeyCode::BackTab | KeyCode::Up => focus = (focus + fields.len() - 1) % fields.len(),
+            KeyCode::Backspace => {
+                values[focus].pop();
+            }
+            KeyCode::Char(c) => values[focus].push(c),
+            _ => {}
+        }
+    }
+}
+
+/// Picks one of `options`, `None` on esc.
+fn choose<B: Backend>(screen: &SharedScreen<B>, title: &str, options: &[String]) -> Option<usize> {
+    let mut screen = screen.borrow_mut();
+    let mut state = ListState::default().with_selected(Some(0));
+
+    loop {
+        let height = (options.len() as u16 + 2).min(20);
+        draw_modal(&mut screen, height, |frame, area| {
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .title_bottom("[enter] ok | [esc] cancel");
+            let list = List::new(options.iter().map(String::as_str))
+                .block(block)
+                .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+            frame.render_stateful_widget(list, area, &mut state);
+        })
+        .ok()?;
+
+        let selected = state.selected().unwrap_or_default();
+
+        match screen.next_key().ok()??.code {
+            KeyCode::Esc => return None,
+            KeyCode::Enter => return Some(selected),
+            KeyCode::Up => state.select(Some(selected.saturating_sub(1))),
+            KeyCode::Down => state.select(Some((selected + 1).min(options.len() - 1))),
+            _ => {}
+        }
+    }
+}
+
+/// Yes/no question. Only `y` confirms, so a stray enter can't delete anything.
+fn confirm<B: Backend>(screen: &SharedScreen<B>, question: &str) -> bool {
+    let mut screen = screen.borrow_mut();
+
+    loop {
+        let drawn = draw_modal(&mut screen, 3, |frame, area| {
+            let block = Block::default().borders(Borders::ALL).title("[y/n]");
+            frame.render_widget(Paragraph::new(question).block(block), area);
+        });
+
+        if drawn.is_err() {
+            return false;
+        }
+
+        match screen.next_key() {
+            Ok(Some(key)) => match key.code {
+                KeyCode::Char('y') | KeyCode::Char('Y') => return true,
+                KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => return false,
+                _ => {}
+            },
+            _ => return false,
+        }
+    }
+}
+
+/// Shows `text` until a key is pressed.
+fn message<B: Backend>(screen: &SharedScreen<B>, text: &str) {
+    let mut screen = screen.borrow_mut();
+
+    let drawn = draw_modal(&mut screen, 3, |frame, area| {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title_bottom("press any key");
+        frame.render_widget(Paragraph::new(text).block(block), area);
+    });
+
+    if drawn.is_ok() {
+        let _ = screen.next_key();
+    }
+}
@@ -0,0 +1,370 @@
+use anyhow::{anyhow, Result};
+use ratatui::layout::Constraint;
+
+use crate::models::TrashedItem;
+use crate::search::SearchHit;
+use crate::ui::pages::page_helpers::{
+    format_checkbox, format_checklist, format_history_entry, format_labels, format_links,
+    format_optional, format_progress, format_timestamp,
+};
+use crate::ui::{BoardPage, EpicDetail, HomePage, Page, SearchPage, StoryDetail, TrashPage};
+
+/// What the full-screen frontend shows for a page: some lines about the page's item
+/// and a table to pick from.
+pub struct PageView {
+    pub title: String,
+    pub details: Vec<String>,
+    pub list_title: &'static str,
+    pub header: Vec<String>,
+    pub widths: Vec<Constraint>,
+    pub rows: Vec<RowView>,
+    /// Key commands of the page, passed on to `Page::handle_input`
+    pub hints: &'static str,
+    /// Keys passed on together with the id of the selected row, e.g. "12>"
+    pub row_keys: &'static [char],
+}
+
+pub struct RowView {
+    /// Id passed to `Page::handle_input` on enter; `None` for rows that don't open anything
+    pub id: Option<u32>,
+    pub cells: Vec<String>,
+}
+
+impl RowView {
+    fn new(id: Option<u32>, cells: Vec<String>) -> Self {
+        RowView { id, cells }
+    }
+}
+
+fn header(titles: &[&str]) -> Vec<String> {
+    titles.iter().map(|title| title.to_string()).collect()
+}
+
+/// Builds the view of one of the pages of `crate::ui`.
+pub fn page_view(page: &dyn Page) -> Result<PageView> {
+    let page = page.as_any();
+
+    if let Some(page) = page.downcast_ref::<HomePage>() {
+        home_view(page)
+    } else if let Some(page) = page.downcast_ref::<EpicDetail>() {
+        epic_view(page)
+    } else if let Some(page) = page.downcast_ref::<StoryDetail>() {
+        story_view(page)
+    } else if let Some(page) = page.downcast_ref::<SearchPage>() {
+        search_view(page)
+    } else if let Some(page) = page.downcast_ref::<TrashPage>() {
+        trash_view(page)
+    } else if let Some(page) = page.downcast_ref::<BoardPage>() {
+        board_view(page)
+    } else {
+        Err(anyhow!("page can't be shown in the full-screen frontend"))
+    }
+}
+
+fn home_view(page: &HomePage) -> Result<PageView> {
+    let view = page.view.borrow();
+    let mut rows = vec![];
+
+    for (epic_id, epic) in view.apply(page.db.list_epics()?, &page.db.workflow) {
+        let (done, total) = page.db.epic_progress(epic_id)?;
+
+        rows.push(RowView::new(
+            Some(epic_id),
+            vec![
+                epic_id.to_string(),
+                epic.name,
+                epic.status.to_string(),
+                format_progress(done, total),
+            ],
+        ));
+    }
+
+    Ok(PageView {
+        title: "EPICS".to_owned(),
+        details: vec![format!("view: {}", view.describe())],
+        list_title: "epics",
+        header: header(&["id", "name", "status", "progress"]),
+        widths: vec![
+            Constraint::Length(6),
+            Constraint::Fill(1),
+            Constraint::Length(14),
+            Constraint::Length(9),
+        ],
+        rows,
+        row_keys: &[],
+        hints: "[c] create epic | [f] filter | [s] sort | [b] board | [/] search | [t] trash | [z] undo | [y] redo | [q] quit",
+    })
+}
+
+fn epic_view(page: &EpicDetail) -> Result<PageView> {
+    let epic = page
+        .db
+        .get_epic(page.epic_id)?
+        .ok_or_else(|| anyhow!("could not find epic!"))?;
+    let (done, total) = page.db.epic_progress(page.epic_id)?;
+    let view = page.view.borrow();
+
+    let rows = view
+        .apply(
+            page.db.list_stories_of_epic(page.epic_id)?,
+            &page.db.workflow,
+        )
+        .into_iter()
+        .map(|(story_id, story)| {
+            RowView::new(
+                Some(story_id),
+                vec![
+                    story_id.to_string(),
+                    story.name,
+                    format_optional(story.priority),
+                    format_optional(story.estimate),
+                    format_checklist(&story.checklist),
+                    story.status.to_string(),
+                ],
+            )
+        })
+        .collect();
+
+    Ok(PageView {
+        title: format!("EPIC {}: {}", page.epic_id, epic.name),
+        details: vec![
+            epic.description,
+            format!(
+                "status: {} | progress: {}",
+                epic.status,
+                format_progress(done, total)
+            ),
+            format!("labels: {}", format_labels(&epic.labels)),
+            format!(
+                "created: {} | updated: {}",
+                format_timestamp(epic.created_at),
+                format_timestamp(epic.updated_at)
+            ),
+            format!("view: {}", view.describe()),
+        ],
+        list_title: "stories",
+        header: header(&["id", "name", "priority", "points", "checklist", "status"]),
+        widths: vec![
+            Constraint::Length(6),
+            Constraint::Fill(1),
+            Constraint::Length(9),
+            Constraint::Length(7),
+            Constraint::Length(10),
+            Constraint::Length(14),
+        ],
+        rows,
+        row_keys: &[],
+        hints: "[u] update status | [e] edit | [l] labels | [d] delete | [c] create story | [f] filter | [s] sort | [b] board | [/] search | [z] undo | [y] redo",
+    })
+}
+
+/// How many of the latest history entries the story page shows above its checklist
+const ACTIVITY_LINES: usize = 5;
+
+fn story_view(page: &StoryDetail) -> Result<PageView> {
+    let story = page
+        .db
+        .get_story(page.story_id)?
+        .ok_or_else(|| anyhow!("could not find story!"))?;
+
+    let history = page.db.history_of(page.story_id)?;
+    let rows = story
+        .checklist
+        .iter()
+        .enumerate()
+        .map(|(index, item)| {
+            let number = index as u32 + 1;
+
+            RowView::new(
+                Some(number),
+                vec![
+                    number.to_string(),
+                    format_checkbox(item.done).to_owned(),
+                    item.text.clone(),
+                ],
+            )
+        })
+        .collect();
+
+    let mut details = vec![
+        story.description,
+        format!("status: {}", story.status),
+        format!(
+            "priority: {} | assignee: {} | points: {}",
+            format_optional(story.priority),
+            format_optional(story.assignee.as_ref()),
+            format_optional(story.estimate)
+        ),
+        format!("labels: {}", format_labels(&story.labels)),
+        format!("links: {}", format_links(&page.db.links_of(page.story_id)?)),
+        format!(
+            "created: {} | updated: {}",
+            format_timestamp(story.created_at),
+            format_timestamp(story.updated_at)
+        ),
+        "latest activity:".to_owned(),
+    ];
+    details.extend(
+        history[history.len().saturating_sub(ACTIVITY_LINES)..]
+            .iter()
+            .map(format_history_entry),
+    );
+
+    let (comments, comment_page, comment_pages) = page.shown_comments()?;
+
+    if !comments.is_empty() {
+        details.push(format!(
+            "comments, page {}/{}, newest first:",
+            comment_page + 1,
+            comment_pages
+        ));
+        details.extend(comments.into_iter().map(|comment| {
+            format!(
+                "{} {}: {}",
+                format_timestamp(Some(comment.timestamp)),
+                comment.author,
+                comment.text.replace('\n', " ")
+            )
+        }));
+    }
+
+    Ok(PageView {
+        title: format!("STORY {}: {}", page.story_id, story.name),
+        details,
+        list_title: "checklist",
+        header: header(&["#", "done", "item"]),
+        widths: vec![
+            Constraint::Length(4),
+            Constraint::Length(5),
+            Constraint::Fill(1),
+        ],
+        rows,
+        row_keys: &['x'],
+        hints: "[enter] tick item | [x] remove item | [a] add item | [c] comment | [<] [>] older/newer comments | [l] link story | [r] remove link | [u] update status | [e] edit | [t] details | [m] move | [d] delete | [/] search | [z] undo | [y] redo",
+    })
+}
+
+fn search_view(page: &SearchPage) -> Result<PageView> {
+    let hits = page.db.search(&page.query)?;
+    let mut rows = vec![];
+
+    for hit in &hits {
+        let (kind, epic_name) = match hit {
+            SearchHit::Epic { .. } => ("epic", "-".to_owned()),
+            SearchHit::Story { epic_id, .. } => (
+                "story",
+                page.db
+                    .get_epic(*epic_id)?
+                    .map_or("-".to_owned(), |epic| epic.name),
+            ),
+        };
+
+        rows.push(RowView::new(
+            Some(hit.id()),
+            vec![
+                hit.id().to_string(),
+                kind.to_owned(),
+                hit.name().to_owned(),
+                epic_name,
+            ],
+        ));
+    }
+
+    Ok(PageView {
+        title: "SEARCH".to_owned(),
+        details: vec![format!("results for \"{}\": {}", page.query, hits.len())],
+        list_title: "results",
+        header: header(&["id", "type", "name", "epic"]),
+        widths: vec![
+            Constraint::Length(6),
+            Constraint::Length(6),
+            Constraint::Fill(2),
+            Constraint::Fill(1),
+        ],
+        rows,
+        row_keys: &[],
+        hints: "[/] new search | [z] undo | [y] redo",
+    })
+}
+
+fn trash_view(page: &TrashPage) -> Result<PageView> {
+    let items = page.db.list_trash()?;
+
+    let rows = items
+        .iter()
+        .map(|item| {
+            let kind = match item {
+                TrashedItem::Epic { .. } => "epic",
+                TrashedItem::Story { .. } => "story",
+            };
+
+            // nothing to open, restoring and purging pick the item in a modal
+            RowView::new(
+                None,
+                vec![
+                    item.id().to_string(),
+                    kind.to_owned(),
+                    item.name().to_owned(),
+                    format_timestamp(item.deleted_at()),
+                ],
+            )
+        })
+        .collect();
+
+    Ok(PageView {
+        title: "TRASH".to_owned(),
+        details: vec![format!("items: {}", items.len())],
+        list_title: "deleted epics and stories",
+        header: header(&["id", "type", "name", "deleted"]),
+        widths: vec![
+            Constraint::Length(6),
+            Constraint::Length(6),
+            Constraint::Fill(1),
+            Constraint::Length(17),
+        ],
+        rows,
+        row_keys: &[],
+        hints:
+            "[r] restore | [x] delete for good | [e] empty trash | [/] search | [z] undo | [y] redo",
+    })
+}
+
+// One row per story, shown in the column of its status, so that a story can be selected
+// and moved between columns.
+fn board_view(page: &BoardPage) -> Result<PageView> {
+    let title = match page.epic_id {
+        Some(epic_id) => {
+            let epic = page
+                .db
+                .get_epic(epic_id)?
+                .ok_or_else(|| anyhow!("could not find epic!"))?;
+            format!("BOARD OF EPIC {}: {}", epic_id, epic.name)
+        }
+        None => "BOARD".to_owned(),
+    };
+    let columns = page.columns()?;
+    let mut rows = vec![];
+
+    for (index, column) in columns.iter().enumerate() {
+        for card in &column.cards {
+            let mut cells = vec![String::new(); columns.len()];
+            cells[index] = format!("{} {}", card.story_id, card.story.name);
+
+            rows.push(RowView::new(Some(card.story_id), cells));
+        }
+    }
+
+    Ok(PageView {
+        title,
+        details: vec![format!("stories: {}", rows.len())],
+        list_title: "stories by status",
+        header: columns
+            .iter()
+            .map(|column| format!("{} ({})", column.status, column.cards.len()))
+            .collect(),
+        widths: vec![Constraint::Fill(1); columns.len()],
+        rows,
+        row_keys: &['<', '>'],
+        hints: "[<] move left | [>] move right | [/] search | [z] undo | [y] redo",
+    })
+}
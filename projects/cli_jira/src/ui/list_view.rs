@@ -0,0 +1,172 @@
+use chrono::{DateTime, Utc};
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::models::{Epic, RecordStatus, Story};
+use crate::workflow::Workflow;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum SortKey {
+    #[default]
+    Id,
+    Name,
+    /// In workflow order
+    Status,
+    Created,
+}
+
+impl fmt::Display for SortKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SortKey::Id => write!(f, "id"),
+            SortKey::Name => write!(f, "name"),
+            SortKey::Status => write!(f, "status"),
+            SortKey::Created => write!(f, "creation date"),
+        }
+    }
+}
+
+/// Filter and sort order of the epic or story list, kept by the `Navigator` for the
+/// rest of the session.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct ListView {
+    pub hidden_statuses: Vec<RecordStatus>,
+    pub label: Option<String>,
+    /// Only used for stories
+    pub assignee: Option<String>,
+    pub sort: SortKey,
+}
+
+/// What `ListView` needs to know about the items it lists.
+pub trait Listable {
+    fn name(&self) -> &str;
+    fn status(&self) -> &RecordStatus;
+    fn labels(&self) -> &[String];
+    fn assignee(&self) -> Option<&str>;
+    fn created_at(&self) -> Option<DateTime<Utc>>;
+}
+
+impl Listable for Epic {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn status(&self) -> &RecordStatus {
+        &self.status
+    }
+
+    fn labels(&self) -> &[String] {
+        &self.labels
+    }
+
+    fn assignee(&self) -> Option<&str> {
+        None
+    }
+
+    fn created_at(&self) -> Option<DateTime<Utc>> {
+        self.created_at
+    }
+}
+
+impl Listable for Story {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn status(&self) -> &RecordStatus {
+        &self.status
+    }
+
+    fn labels(&self) -> &[String] {
+        &self.labels
+    }
+
+    fn assignee(&self) -> Option<&str> {
+        self.assignee.as_deref()
+    }
+
+    fn created_at(&self) -> Option<DateTime<Utc>> {
+        self.created_at
+    }
+}
+
+impl ListView {
+    pub fn is_filtered(&self) -> bool {
+        !self.hidden_statuses.is_empty() || self.label.is_some() || self.assignee.is_some()
+    }
+
+    fn matches<T: Listable>(&self, item: &T) -> bool {
+        let label_matches = self.label.as_ref().is_none_or(|label| {
+            item.labels()
+                .iter()
+                .any(|item_label| item_label.eq_ignore_ascii_case(label))
+        });
+        let assignee_matches = self.assignee.as_ref().is_none_or(|assignee| {
+            item.assignee()
+                .is_some_and(|item_assignee| item_assignee.eq_ignore_ascii_case(assignee))
+        });
+
+        !self.hidden_statuses.contains(item.status()) && label_matches && assignee_matches
+    }
+
+    fn compare<T: Listable>(
+        &self,
+        workflow: &Workflow,
+        (a_id, a): &(u32, T),
+        (b_id, b): &(u32, T),
+    ) -> Ordering {
+        // statuses the workflow doesn't know go last
+        let status_index = |status: &RecordStatus| {
+            workflow
+                .statuses
+                .iter()
+                .position(|candidate| candidate == status)
+                .unwrap_or(usize::MAX)
+        };
+
+        let ordering = match self.sort {
+            SortKey::Id => Ordering::Equal,
+            SortKey::Name => a.name().to_lowercase().cmp(&b.name().to_lowercase()),
+            SortKey::Status => status_index(a.status()).cmp(&status_index(b.status())),
+            SortKey::Created => a.created_at().cmp(&b.created_at()),
+        };
+
+        ordering.then(a_id.cmp(b_id))
+    }
+
+    /// Drops the items hidden by the filter and sorts the rest, ties broken by id.
+    pub fn apply<T: Listable>(&self, items: Vec<(u32, T)>, workflow: &Workflow) -> Vec<(u32, T)> {
+        let mut items: Vec<_> = items
+            .into_iter()
+            .filter(|(_, item)| self.matches(item))
+            .collect();
+
+        items.sort_by(|a, b| self.compare(workflow, a, b));
+        items
+    }
+
+    /// One line summary for the page header, e.g. "hiding Closed | label: api | sorted by name".
+    pub fn describe(&self) -> String {
+        let mut parts = vec![];
+
+        if !self.hidden_statuses.is_empty() {
+            let statuses: Vec<_> = self
+                .hidden_statuses
+                .iter()
+                .map(|status| status.to_string())
+                .collect();
+            parts.push(format!("hiding {}", statuses.join(", ")));
+        }
+
+        if let Some(label) = &self.label {
+            parts.push(format!("label: {}", label));
+        }
+
+        if let Some(assignee) = &self.assignee {
+            parts.push(format!("assignee: {}", assignee));
+        }
+
+        parts.push(format!("sorted by {}", self.sort));
+        parts.join(" | ")
+    }
+}
@@ -1,5 +1,9 @@
+pub mod list_view;
 pub mod pages;
 pub mod prompts;
+pub mod table;
 
+pub use list_view::*;
 pub use pages::*;
 pub use prompts::*;
+pub use table::*;
@@ -1,11 +1,13 @@
 use std::any::Any;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 
 use anyhow::{anyhow, Result};
-use itertools::Itertools;
 
 use crate::db::JiraDatabase;
-use crate::models::Action;
+use crate::models::{Action, Comment, ListKind, RecordStatus, Story, TrashedItem};
+use crate::search::SearchHit;
+use crate::ui::{Column, ListView, Table};
 
 pub mod page_helpers;
 use page_helpers::*;
@@ -18,28 +20,52 @@ pub trait Page {
 
 pub struct HomePage {
     pub db: Rc<JiraDatabase>,
+    /// Shared with the `Navigator`, which keeps it for the session
+    pub view: Rc<RefCell<ListView>>,
 }
+
+// Table of a single epic or story, with its description wrapped
+fn item_table() -> Table {
+    Table::new(vec![
+        Column::fixed("id", 6),
+        Column::fill("name", 12),
+        Column::fill("description", 20).wrapped(),
+        Column::fill("status", 8),
+    ])
+}
+
 impl Page for HomePage {
     fn draw_page(&self) -> Result<()> {
-        let db_state = self.db.read_db()?;
-        let epics = db_state.epics;
+        let view = self.view.borrow();
+        let epics = view.apply(self.db.list_epics()?, &self.db.workflow);
 
         println!("----------------------------- EPICS -----------------------------");
-        println!("     id     |               name               |      status      ");
-
-        for epic_id in epics.keys().sorted() {
-            let epic = &epics[epic_id];
-
-            let id_col = get_column_string(&epic_id.to_string(), 11);
-            let name_col = get_column_string(&epic.name, 32);
-            let status_col = get_column_string(&epic.status.to_string(), 17);
-            println!("{} | {} | {}", id_col, name_col, status_col);
+        println!("view: {}", view.describe());
+
+        let mut table = Table::new(vec![
+            Column::fixed("id", 6),
+            Column::fill("name", 12),
+            Column::fill("status", 8),
+            Column::fixed("progress", 8),
+        ]);
+
+        for (epic_id, epic) in epics {
+            let (done, total) = self.db.epic_progress(epic_id)?;
+
+            table.add_row(vec![
+                epic_id.to_string(),
+                epic.name,
+                epic.status.to_string(),
+                format_progress(done, total),
+            ]);
         }
 
+        table.print();
+
         println!();
         println!();
 
-        println!("[q] quit | [c] create epic | [:id:] navigate to epic");
+        println!("[q] quit | [c] create epic | [f] filter | [s] sort | [b] board | [/] search | [t] trash | [z] undo | [y] redo | [:id:] navigate to epic");
 
         Ok(())
     }
@@ -48,6 +74,17 @@ impl Page for HomePage {
         let action = match input {
             "q" => Some(Action::Exit),
             "c" => Some(Action::CreateEpic),
+            "t" => Some(Action::NavigateToTrash),
+            "b" => Some(Action::NavigateToBoard { epic_id: None }),
+            "f" => Some(Action::FilterList {
+                list: ListKind::Epics,
+            }),
+            "s" => Some(Action::SortList {
+                list: ListKind::Epics,
+            }),
+            "/" => Some(Action::Search),
+            "z" => Some(Action::Undo),
+            "y" => Some(Action::Redo),
             _ => None,
         };
 
@@ -60,9 +97,7 @@ impl Page for HomePage {
             _ => return Ok(None),
         };
 
-        let db_state = self.db.read_db()?;
-
-        if !db_state.epics.contains_key(&epic_id) {
+        if self.db.get_epic(epic_id)?.is_none() {
             return Ok(None);
         }
 
@@ -77,45 +112,72 @@ impl Page for HomePage {
 pub struct EpicDetail {
     pub epic_id: u32,
     pub db: Rc<JiraDatabase>,
+    /// Shared with the `Navigator` and every other epic's page
+    pub view: Rc<RefCell<ListView>>,
 }
 
 impl Page for EpicDetail {
     fn draw_page(&self) -> Result<()> {
-        let db_state = self.db.read_db()?;
-        let epic = db_state
-            .epics
-            .get(&self.epic_id)
+        let epic = self
+            .db
+            .get_epic(self.epic_id)?
             .ok_or_else(|| anyhow!("could not find epic!"))?;
 
         println!("------------------------------ EPIC ------------------------------");
-        println!("  id  |     name     |         description         |    status    ");
 
-        let id_col = get_column_string(&self.epic_id.to_string(), 5);
-        let name_col = get_column_string(&epic.name, 12);
-        let desc_col = get_column_string(&epic.description, 27);
-        let status_col = get_column_string(&epic.status.to_string(), 13);
-        println!("{} | {} | {} | {}", id_col, name_col, desc_col, status_col);
+        let mut table = item_table();
+        table.add_row(vec![
+            self.epic_id.to_string(),
+            epic.name,
+            epic.description,
+            epic.status.to_string(),
+        ]);
+        table.print();
+
+        println!("labels: {}", format_labels(&epic.labels));
+        println!(
+            "created: {} | updated: {}",
+            format_timestamp(epic.created_at),
+            format_timestamp(epic.updated_at)
+        );
 
         println!();
 
-        println!("---------------------------- STORIES ----------------------------");
-        println!("     id     |               name               |      status      ");
-
-        let stories = &db_state.stories;
+        let view = self.view.borrow();
+        let stories = view.apply(
+            self.db.list_stories_of_epic(self.epic_id)?,
+            &self.db.workflow,
+        );
 
-        for story_id in epic.stories.iter().sorted() {
-            let story = &stories[story_id];
-
-            let id_col = get_column_string(&story_id.to_string(), 11);
-            let name_col = get_column_string(&story.name, 32);
-            let status_col = get_column_string(&story.status.to_string(), 17);
-            println!("{} | {} | {}", id_col, name_col, status_col);
+        println!("---------------------------- STORIES ----------------------------");
+        println!("view: {}", view.describe());
+
+        let mut table = Table::new(vec![
+            Column::fixed("id", 6),
+            Column::fill("name", 12),
+            Column::fixed("priority", 8),
+            Column::fixed("points", 6),
+            Column::fixed("checklist", 9),
+            Column::fill("status", 8),
+        ]);
+
+        for (story_id, story) in stories {
+            table.add_row(vec![
+                story_id.to_string(),
+                story.name,
+                format_optional(story.priority),
+                format_optional(story.estimate),
+                format_checklist(&story.checklist),
+                story.status.to_string(),
+            ]);
         }
 
+        table.print();
+
         println!();
         println!();
 
-        println!("[p] previous | [u] update epic | [d] delete epic | [c] create story | [:id:] navigate to story");
+        println!("[p] previous | [u] update epic | [e] edit epic | [l] edit labels | [d] delete epic | [c] create story | [f] filter | [s] sort | [b] board | [/] search | [z] undo | [y] redo | [:id:] navigate to story");
 
         Ok(())
     }
@@ -126,8 +188,22 @@ impl Page for EpicDetail {
         let action = match input {
             "p" => Some(Action::NavigateToPreviousPage),
             "u" => Some(Action::UpdateEpicStatus { epic_id }),
+            "e" => Some(Action::EditEpic { epic_id }),
+            "l" => Some(Action::EditEpicLabels { epic_id }),
             "d" => Some(Action::DeleteEpic { epic_id }),
             "c" => Some(Action::CreateStory { epic_id }),
+            "b" => Some(Action::NavigateToBoard {
+                epic_id: Some(epic_id),
+            }),
+            "f" => Some(Action::FilterList {
+                list: ListKind::Stories,
+            }),
+            "s" => Some(Action::SortList {
+                list: ListKind::Stories,
+            }),
+            "/" => Some(Action::Search),
+            "z" => Some(Action::Undo),
+            "y" => Some(Action::Redo),
             _ => None,
         };
 
@@ -140,9 +216,12 @@ impl Page for EpicDetail {
             _ => return Ok(None),
         };
 
-        let db_state = self.db.read_db()?;
+        let epic = self
+            .db
+            .get_epic(epic_id)?
+            .ok_or_else(|| anyhow!("could not find epic!"))?;
 
-        if !db_state.stories.contains_key(&story_id) {
+        if !epic.stories.contains(&story_id) {
             return Ok(None);
         }
 
@@ -154,32 +233,129 @@ impl Page for EpicDetail {
     }
 }
 
+/// Number of comments shown at once on a story's page.
+pub const COMMENTS_PER_PAGE: usize = 5;
+
 pub struct StoryDetail {
     pub epic_id: u32,
     pub story_id: u32,
     pub db: Rc<JiraDatabase>,
+    /// Page of the comments shown, 0 being the newest ones
+    pub comment_page: Cell<usize>,
 }
 
 impl Page for StoryDetail {
     fn draw_page(&self) -> Result<()> {
-        let db_state = self.db.read_db()?;
-        let story = db_state
-            .stories
-            .get(&self.story_id)
+        let story = self
+            .db
+            .get_story(self.story_id)?
             .ok_or_else(|| anyhow!("could not find story!"))?;
 
         println!("------------------------------ STORY ------------------------------");
-        println!("  id  |     name     |         description         |    status    ");
 
-        print!("{}|", get_column_string(&self.story_id.to_string(), 6));
-        print!("{}|", get_column_string(&story.name, 14));
-        print!("{}|", get_column_string(&story.description, 29));
-        print!("{}", get_column_string(&story.status.to_string(), 14));
+        let mut table = item_table();
+        table.add_row(vec![
+            self.story_id.to_string(),
+            story.name.clone(),
+            story.description.clone(),
+            story.status.to_string(),
+        ]);
+        table.print();
+
+        println!();
+
+        println!(
+            "priority: {} | assignee: {} | points: {}",
+            format_optional(story.priority),
+            format_optional(story.assignee.as_ref()),
+            format_optional(story.estimate)
+        );
+        println!("labels: {}", format_labels(&story.labels));
+        println!(
+            "created: {} | updated: {}",
+            format_timestamp(story.created_at),
+            format_timestamp(story.updated_at)
+        );
+
+        println!();
+
+        println!("------------------------------ LINKS ------------------------------");
+
+        let mut table = Table::new(vec![
+            Column::fixed("link", 13),
+            Column::fixed("id", 6),
+            Column::fill("name", 20),
+            Column::fill("status", 8),
+        ]);
+
+        for linked in self.db.links_of(self.story_id)? {
+            table.add_row(vec![
+                linked.kind.to_string(),
+                linked.story_id.to_string(),
+                linked.story.name,
+                linked.story.status.to_string(),
+            ]);
+        }
+
+        table.print();
+
+        println!();
+
+        println!("---------------------------- CHECKLIST ----------------------------");
+
+        let mut table = Table::new(vec![
+            Column::fixed("#", 4),
+            Column::fixed("done", 4),
+            Column::fill("item", 20).wrapped(),
+        ]);
+
+        for (index, item) in story.checklist.iter().enumerate() {
+            table.add_row(vec![
+                (index + 1).to_string(),
+                format_checkbox(item.done).to_owned(),
+                item.text.clone(),
+            ]);
+        }
+
+        table.print();
+
+        println!();
+
+        println!("---------------------------- COMMENTS ----------------------------");
+
+        let (comments, page, pages) = self.shown_comments()?;
+
+        if !comments.is_empty() {
+            println!("page {}/{}, newest first", page + 1, pages);
+        }
+
+        let mut table = Table::new(vec![
+            Column::fixed("when", 16),
+            Column::fixed("author", 12),
+            Column::fill("comment", 20).wrapped(),
+        ]);
+
+        for comment in comments {
+            table.add_row(vec![
+                format_timestamp(Some(comment.timestamp)),
+                comment.author,
+                comment.text,
+            ]);
+        }
+
+        table.print();
 
         println!();
+
+        println!("---------------------------- ACTIVITY ----------------------------");
+
+        for entry in self.db.history_of(self.story_id)? {
+            println!("{}", format_history_entry(&entry));
+        }
+
         println!();
 
-        println!("[p] previous | [u] update story | [d] delete story");
+        println!("[p] previous | [u] update story | [e] edit story | [t] edit details | [m] move story | [d] delete story | [l] link story | [r] remove link | [a] add checklist item | [c] comment | [<] older comments | [>] newer comments | [/] search | [z] undo | [y] redo | [:n:] tick item | [:n:x] remove item");
 
         Ok(())
     }
@@ -191,9 +367,429 @@ impl Page for StoryDetail {
         match input {
             "p" => Ok(Some(Action::NavigateToPreviousPage)),
             "u" => Ok(Some(Action::UpdateStoryStatus { story_id })),
+            "e" => Ok(Some(Action::EditStory { story_id })),
+            "t" => Ok(Some(Action::EditStoryDetails { story_id })),
             "d" => Ok(Some(Action::DeleteStory { epic_id, story_id })),
-            _ => Ok(None),
+            "m" => Ok(Some(Action::MoveStory { epic_id, story_id })),
+            "l" => Ok(Some(Action::LinkStory { story_id })),
+            "r" => Ok(Some(Action::UnlinkStory { story_id })),
+            "a" => Ok(Some(Action::AddChecklistItem { story_id })),
+            "c" => Ok(Some(Action::AddComment { story_id })),
+            "<" | ">" => {
+                let (_, page, pages) = self.shown_comments()?;
+
+                let page = if input == "<" {
+                    Some(page + 1).filter(|page| *page < pages)
+                } else {
+                    page.checked_sub(1)
+                };
+
+                Ok(page.map(|page| Action::ShowComments { page }))
+            }
+            "/" => Ok(Some(Action::Search)),
+            "z" => Ok(Some(Action::Undo)),
+            "y" => Ok(Some(Action::Redo)),
+            _ => self.handle_checklist_input(input),
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl StoryDetail {
+    /// The comments of the page shown, newest first, with the number of that page
+    /// and of all pages.
+    pub fn shown_comments(&self) -> Result<(Vec<Comment>, usize, usize)> {
+        let comments = self.db.comments_of(self.story_id)?;
+        let pages = comments.len().div_ceil(COMMENTS_PER_PAGE).max(1);
+        // the page may be gone if the story was edited elsewhere
+        let page = self.comment_page.get().min(pages - 1);
+
+        let shown = comments
+            .into_iter()
+            .rev()
+            .skip(page * COMMENTS_PER_PAGE)
+            .take(COMMENTS_PER_PAGE)
+            .collect();
+
+        Ok((shown, page, pages))
+    }
+
+    // "3" ticks off the third checklist item, or unticks it, and "3x" removes it
+    fn handle_checklist_input(&self, input: &str) -> Result<Option<Action>> {
+        let story_id = self.story_id;
+        let (number, remove) = match input.strip_suffix('x') {
+            Some(number) => (number, true),
+            None => (input, false),
+        };
+
+        let Ok(number) = number.trim().parse::<usize>() else {
+            return Ok(None);
+        };
+
+        let story = self
+            .db
+            .get_story(story_id)?
+            .ok_or_else(|| anyhow!("could not find story!"))?;
+
+        if number == 0 || number > story.checklist.len() {
+            return Ok(None);
+        }
+
+        let index = number - 1;
+
+        if remove {
+            Ok(Some(Action::RemoveChecklistItem { story_id, index }))
+        } else {
+            Ok(Some(Action::ToggleChecklistItem { story_id, index }))
+        }
+    }
+}
+
+pub struct SearchPage {
+    pub query: String,
+    pub db: Rc<JiraDatabase>,
+}
+
+impl Page for SearchPage {
+    fn draw_page(&self) -> Result<()> {
+        let hits = self.db.search(&self.query)?;
+
+        println!("----------------------------- SEARCH -----------------------------");
+        println!("results for \"{}\": {}", self.query, hits.len());
+        println!();
+
+        let mut table = Table::new(vec![
+            Column::fixed("id", 6),
+            Column::fixed("type", 5),
+            Column::fill("name", 12),
+            Column::fill("epic", 12),
+        ]);
+
+        for hit in &hits {
+            let (kind, epic_name) = match hit {
+                SearchHit::Epic { .. } => ("epic", "-".to_owned()),
+                SearchHit::Story { epic_id, .. } => (
+                    "story",
+                    self.db
+                        .get_epic(*epic_id)?
+                        .map_or("-".to_owned(), |epic| epic.name),
+                ),
+            };
+
+            table.add_row(vec![
+                hit.id().to_string(),
+                kind.to_owned(),
+                hit.name().to_owned(),
+                epic_name,
+            ]);
+        }
+
+        table.print();
+
+        println!();
+        println!();
+
+        println!("[p] previous | [/] new search | [z] undo | [y] redo | [:id:] navigate to epic or story");
+
+        Ok(())
+    }
+
+    fn handle_input(&self, input: &str) -> Result<Option<Action>> {
+        let action = match input {
+            "p" => Some(Action::NavigateToPreviousPage),
+            "/" => Some(Action::Search),
+            "z" => Some(Action::Undo),
+            "y" => Some(Action::Redo),
+            _ => None,
+        };
+
+        if action.is_some() {
+            return Ok(action);
         }
+
+        let item_id = match input.parse::<u32>() {
+            Ok(v) => v,
+            _ => return Ok(None),
+        };
+
+        let hit = self
+            .db
+            .search(&self.query)?
+            .into_iter()
+            .find(|hit| hit.id() == item_id);
+
+        Ok(hit.map(|hit| match hit {
+            SearchHit::Epic { epic_id, .. } => Action::NavigateToEpicDetail { epic_id },
+            SearchHit::Story {
+                epic_id, story_id, ..
+            } => Action::NavigateToStoryDetail { epic_id, story_id },
+        }))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+pub struct TrashPage {
+    pub db: Rc<JiraDatabase>,
+}
+
+impl Page for TrashPage {
+    fn draw_page(&self) -> Result<()> {
+        let items = self.db.list_trash()?;
+
+        println!("----------------------------- TRASH ------------------------------");
+
+        let mut table = Table::new(vec![
+            Column::fixed("id", 6),
+            Column::fixed("type", 5),
+            Column::fill("name", 12),
+            Column::fixed("deleted", 16),
+        ]);
+
+        for item in &items {
+            let kind = match item {
+                TrashedItem::Epic { .. } => "epic",
+                TrashedItem::Story { .. } => "story",
+            };
+
+            table.add_row(vec![
+                item.id().to_string(),
+                kind.to_owned(),
+                item.name().to_owned(),
+                format_timestamp(item.deleted_at()),
+            ]);
+        }
+
+        table.print();
+
+        println!();
+        println!();
+
+        println!("[p] previous | [r] restore | [x] delete for good | [e] empty trash | [/] search | [z] undo | [y] redo");
+
+        Ok(())
+    }
+
+    fn handle_input(&self, input: &str) -> Result<Option<Action>> {
+        let action = match input {
+            "p" => Some(Action::NavigateToPreviousPage),
+            "r" => Some(Action::RestoreFromTrash),
+            "x" => Some(Action::PurgeFromTrash),
+            "e" => Some(Action::EmptyTrash),
+            "/" => Some(Action::Search),
+            "z" => Some(Action::Undo),
+            "y" => Some(Action::Redo),
+            _ => None,
+        };
+
+        Ok(action)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Stories of an epic, or of all epics, in one column per status.
+pub struct BoardPage {
+    /// `None` for the board of all epics
+    pub epic_id: Option<u32>,
+    pub db: Rc<JiraDatabase>,
+}
+
+pub struct BoardColumn {
+    pub status: RecordStatus,
+    /// Sorted by story id
+    pub cards: Vec<BoardCard>,
+}
+
+pub struct BoardCard {
+    pub epic_id: u32,
+    pub story_id: u32,
+    pub story: Story,
+}
+
+impl BoardPage {
+    /// A column for every status of the workflow, in workflow order, followed by one
+    /// for each status left over from an older workflow.
+    pub fn columns(&self) -> Result<Vec<BoardColumn>> {
+        let epic_ids = match self.epic_id {
+            Some(epic_id) => vec![epic_id],
+            None => self
+                .db
+                .list_epics()?
+                .into_iter()
+                .map(|(epic_id, _)| epic_id)
+                .collect(),
+        };
+
+        let mut columns: Vec<_> = self
+            .db
+            .workflow
+            .statuses
+            .iter()
+            .map(|status| BoardColumn {
+                status: status.clone(),
+                cards: vec![],
+            })
+            .collect();
+
+        for epic_id in epic_ids {
+            for (story_id, story) in self.db.list_stories_of_epic(epic_id)? {
+                if !columns.iter().any(|column| column.status == story.status) {
+                    columns.push(BoardColumn {
+                        status: story.status.clone(),
+                        cards: vec![],
+                    });
+                }
+
+                if let Some(column) = columns
+                    .iter_mut()
+                    .find(|column| column.status == story.status)
+                {
+                    column.cards.push(BoardCard {
+                        epic_id,
+                        story_id,
+                        story,
+                    });
+                }
+            }
+        }
+
+        for column in &mut columns {
+            column.cards.sort_by_key(|card| card.story_id);
+        }
+
+        Ok(columns)
+    }
+}
+
+impl Page for BoardPage {
+    fn draw_page(&self) -> Result<()> {
+        let title = match self.epic_id {
+            Some(epic_id) => {
+                let epic = self
+                    .db
+                    .get_epic(epic_id)?
+                    .ok_or_else(|| anyhow!("could not find epic!"))?;
+                format!("epic: {} {}", epic_id, epic.name)
+            }
+            None => "epic: all".to_owned(),
+        };
+        let columns = self.columns()?;
+
+        println!("----------------------------- BOARD ------------------------------");
+        println!("{}", title);
+        println!();
+
+        let mut table = Table::new(
+            columns
+                .iter()
+                .map(|column| {
+                    let title = format!("{} ({})", column.status, column.cards.len());
+                    Column::fill(&title, 10)
+                })
+                .collect(),
+        );
+
+        let height = columns
+            .iter()
+            .map(|column| column.cards.len())
+            .max()
+            .unwrap_or_default();
+
+        for row in 0..height {
+            table.add_row(
+                columns
+                    .iter()
+                    .map(|column| {
+                        column
+                            .cards
+                            .get(row)
+                            .map(|card| format!("{} {}", card.story_id, card.story.name))
+                            .unwrap_or_default()
+                    })
+                    .collect(),
+            );
+        }
+
+        table.print();
+
+        println!();
+        println!();
+
+        println!("[p] previous | [/] search | [z] undo | [y] redo | [:id:] navigate to story | [:id:<] move story left | [:id:>] move story right");
+
+        Ok(())
+    }
+
+    fn handle_input(&self, input: &str) -> Result<Option<Action>> {
+        let action = match input {
+            "p" => Some(Action::NavigateToPreviousPage),
+            "/" => Some(Action::Search),
+            "z" => Some(Action::Undo),
+            "y" => Some(Action::Redo),
+            _ => None,
+        };
+
+        if action.is_some() {
+            return Ok(action);
+        }
+
+        // "12" opens story 12, "12<" and "12>" move it to the previous or next column
+        let (story_id, step) = match (input.strip_suffix('<'), input.strip_suffix('>')) {
+            (Some(story_id), _) => (story_id, -1),
+            (_, Some(story_id)) => (story_id, 1),
+            _ => (input, 0),
+        };
+
+        let Ok(story_id) = story_id.trim().parse::<u32>() else {
+            return Ok(None);
+        };
+
+        let columns = self.columns()?;
+        let Some((index, card)) = columns.iter().enumerate().find_map(|(index, column)| {
+            column
+                .cards
+                .iter()
+                .find(|card| card.story_id == story_id)
+                .map(|card| (index, card))
+        }) else {
+            return Ok(None);
+        };
+
+        if step == 0 {
+            return Ok(Some(Action::NavigateToStoryDetail {
+                epic_id: card.epic_id,
+                story_id,
+            }));
+        }
+
+        let Some(next_column) = index
+            .checked_add_signed(step)
+            .and_then(|index| columns.get(index))
+        else {
+            return Ok(None);
+        };
+
+        let from = &card.story.status;
+        let to = &next_column.status;
+
+        if !self.db.workflow.next_statuses(from).contains(to) {
+            return Ok(Some(Action::ShowMessage {
+                text: format!("Story {} can't go from {} to {}.", story_id, from, to),
+            }));
+        }
+
+        Ok(Some(Action::SetStoryStatus {
+            story_id,
+            status: to.clone(),
+        }))
     }
 
     fn as_any(&self) -> &dyn Any {
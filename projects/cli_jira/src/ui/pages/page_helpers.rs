@@ -1,15 +1,164 @@
-use ellipse::Ellipse;
+use chrono::{DateTime, Local, Utc};
+use std::fmt::Display;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use crate::models::{ChecklistItem, HistoryEntry, LinkedStory};
 
 pub fn get_column_string(text: &str, width: usize) -> String {
     if width <= 3 {
         return ".".repeat(width);
     }
 
-    if width >= text.len() {
-        let whitespace = " ".repeat(width - text.len());
+    fit_to_width(text, width)
+}
+
+/// Columns taken up by `text` in a terminal, e.g. 2 for most emoji and CJK characters.
+pub fn display_width(text: &str) -> usize {
+    text.width()
+}
+
+/// `text` padded with spaces to exactly `width` columns, or cut short with "..." if it
+/// doesn't fit. Never splits a character, so a cut before a wide one pads instead.
+pub fn fit_to_width(text: &str, width: usize) -> String {
+    let text_width = display_width(text);
+
+    if text_width <= width {
+        return format!("{}{}", text, " ".repeat(width - text_width));
+    }
+
+    if width <= 3 {
+        return ".".repeat(width);
+    }
+
+    let mut fitted = String::new();
+    let mut fitted_width = 0;
+
+    for grapheme in text.graphemes(true) {
+        let grapheme_width = grapheme.width();
+
+        if fitted_width + grapheme_width > width - 3 {
+            break;
+        }
+
+        fitted.push_str(grapheme);
+        fitted_width += grapheme_width;
+    }
+
+    format!("{}...{}", fitted, " ".repeat(width - 3 - fitted_width))
+}
+
+/// Splits `text` into lines of at most `width` columns, breaking between words where
+/// possible. Always returns at least one line.
+pub fn wrap_to_width(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = vec![];
+    let mut line = String::new();
+    let mut line_width = 0;
+
+    for word in text.split_whitespace() {
+        let word_width = display_width(word);
+
+        if line_width > 0 && line_width + 1 + word_width > width {
+            lines.push(std::mem::take(&mut line));
+            line_width = 0;
+        }
+
+        if word_width > width {
+            for grapheme in word.graphemes(true) {
+                let grapheme_width = grapheme.width();
+
+                if line_width > 0 && line_width + grapheme_width > width {
+                    lines.push(std::mem::take(&mut line));
+                    line_width = 0;
+                }
+
+                line.push_str(grapheme);
+                line_width += grapheme_width;
+            }
+
+            continue;
+        }
+
+        if line_width > 0 {
+            line.push(' ');
+            line_width += 1;
+        }
+
+        line.push_str(word);
+        line_width += word_width;
+    }
+
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+
+    lines
+}
+
+pub fn format_optional<T: Display>(value: Option<T>) -> String {
+    value.map_or("-".to_owned(), |value| value.to_string())
+}
+
+pub fn format_labels(labels: &[String]) -> String {
+    if labels.is_empty() {
+        return "-".to_owned();
+    }
+
+    labels.join(", ")
+}
+
+/// "blocked by 12, relates to 15", or "-" for a story without links.
+pub fn format_links(links: &[LinkedStory]) -> String {
+    if links.is_empty() {
+        return "-".to_owned();
+    }
+
+    links
+        .iter()
+        .map(|linked| format!("{} {}", linked.kind, linked.story_id))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
 
-        return format!("{text}{whitespace}");
+/// "done/total", or "-" for an epic without stories.
+pub fn format_progress(done: usize, total: usize) -> String {
+    if total == 0 {
+        return "-".to_owned();
     }
 
-    text.truncate_ellipse(width - 3).to_string()
+    format!("{}/{}", done, total)
+}
+
+/// Ticked off items of a checklist, e.g. "2/5", or "-" for none.
+pub fn format_checklist(checklist: &[ChecklistItem]) -> String {
+    let done = checklist.iter().filter(|item| item.done).count();
+
+    format_progress(done, checklist.len())
+}
+
+pub fn format_checkbox(done: bool) -> &'static str {
+    if done {
+        "[x]"
+    } else {
+        "[ ]"
+    }
+}
+
+pub fn format_timestamp(timestamp: Option<DateTime<Utc>>) -> String {
+    format_optional(timestamp.map(|timestamp| {
+        timestamp
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M")
+            .to_string()
+    }))
+}
+
+pub fn format_history_entry(entry: &HistoryEntry) -> String {
+    format!(
+        "{} {}: {}",
+        format_timestamp(Some(entry.timestamp)),
+        entry.author,
+        entry.change
+    )
 }
@@ -1,14 +1,50 @@
 use crate::{
     io_utils::get_user_input,
-    models::{Epic, RecordStatus, Story},
+    models::{Epic, LinkKind, LinkedStory, ListKind, Priority, RecordStatus, Story, TrashedItem},
+    ui::{ListView, SortKey},
 };
 
+#[allow(clippy::type_complexity)]
 pub struct Prompts {
-    pub create_epic: Box<dyn Fn() -> Epic>,
-    pub create_story: Box<dyn Fn() -> Story>,
+    /// Reads a new item, `None` to cancel.
+    pub create_epic: Box<dyn Fn() -> Option<Epic>>,
+    pub create_story: Box<dyn Fn() -> Option<Story>>,
+    pub edit_epic: Box<dyn Fn(&Epic) -> Epic>,
+    pub edit_story: Box<dyn Fn(&Story) -> Story>,
+    pub edit_labels: Box<dyn Fn(&[String]) -> Vec<String>>,
+    pub edit_story_details: Box<dyn Fn(&Story) -> Story>,
     pub delete_epic: Box<dyn Fn() -> bool>,
     pub delete_story: Box<dyn Fn() -> bool>,
-    pub update_status: Box<dyn Fn() -> Option<RecordStatus>>,
+    /// Picks one of the statuses the workflow allows next, `None` to cancel.
+    pub update_status: Box<dyn Fn(&[RecordStatus]) -> Option<RecordStatus>>,
+    pub move_story: Box<dyn Fn(&[(u32, Epic)]) -> Option<u32>>,
+    /// Reads how the story is linked to which other story, `None` to cancel.
+    pub link_story: Box<dyn Fn() -> Option<(LinkKind, u32)>>,
+    /// Picks one of the story's links by index, `None` to cancel.
+    pub unlink_story: Box<dyn Fn(&[LinkedStory]) -> Option<usize>>,
+    /// Asks whether to start a story that these unfinished stories block.
+    pub start_blocked_story: Box<dyn Fn(&[(u32, Story)]) -> bool>,
+    /// Reads the text of a new checklist item; empty to cancel.
+    pub add_checklist_item: Box<dyn Fn() -> String>,
+    /// Reads a comment; empty to cancel.
+    pub add_comment: Box<dyn Fn() -> String>,
+    /// Reads a search query; empty to cancel.
+    pub search: Box<dyn Fn() -> String>,
+    /// Edits the filter of a list given the workflow's statuses; sort order is kept.
+    pub filter_list: Box<dyn Fn(&ListView, ListKind, &[RecordStatus]) -> ListView>,
+    pub sort_list: Box<dyn Fn(SortKey) -> SortKey>,
+    /// Pick an item of the trash, `None` to cancel.
+    pub restore_from_trash: Box<dyn Fn(&[TrashedItem]) -> Option<u32>>,
+    pub purge_from_trash: Box<dyn Fn(&[TrashedItem]) -> Option<u32>>,
+    pub empty_trash: Box<dyn Fn() -> bool>,
+    /// Shows a notice until the user acknowledges it.
+    pub message: Box<dyn Fn(&str)>,
+}
+
+impl Default for Prompts {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Prompts {
@@ -16,67 +52,379 @@ impl Prompts {
         Self {
             create_epic: Box::new(create_epic_prompt),
             create_story: Box::new(create_story_prompt),
+            edit_epic: Box::new(edit_epic_prompt),
+            edit_story: Box::new(edit_story_prompt),
+            edit_labels: Box::new(edit_labels_prompt),
+            edit_story_details: Box::new(edit_story_details_prompt),
             delete_epic: Box::new(delete_epic_prompt),
             delete_story: Box::new(delete_story_prompt),
             update_status: Box::new(update_status_prompt),
+            move_story: Box::new(move_story_prompt),
+            link_story: Box::new(link_story_prompt),
+            unlink_story: Box::new(unlink_story_prompt),
+            start_blocked_story: Box::new(start_blocked_story_prompt),
+            add_checklist_item: Box::new(add_checklist_item_prompt),
+            add_comment: Box::new(add_comment_prompt),
+            search: Box::new(search_prompt),
+            filter_list: Box::new(filter_list_prompt),
+            sort_list: Box::new(sort_list_prompt),
+            restore_from_trash: Box::new(|items| pick_trashed_item_prompt(items, "restore")),
+            purge_from_trash: Box::new(|items| pick_trashed_item_prompt(items, "delete for good")),
+            empty_trash: Box::new(empty_trash_prompt),
+            message: Box::new(message_prompt),
         }
     }
 }
 
-fn create_epic_prompt() -> Epic {
+fn create_epic_prompt() -> Option<Epic> {
     println!("----------------------------");
     println!("Epic Name:");
     let name = get_user_input();
     println!("Epic Description:");
     let description = get_user_input();
 
-    Epic::new(name, description)
+    Some(Epic::new(name, description))
 }
 
-fn create_story_prompt() -> Story {
+fn create_story_prompt() -> Option<Story> {
     println!("----------------------------");
     println!("Story Name:");
     let name = get_user_input();
     println!("Story Description:");
     let description = get_user_input();
 
-    Story::new(name, description)
+    Some(Story::new(name, description))
+}
+
+// Empty input keeps the current value
+fn get_user_input_or(current: &str) -> String {
+    let input = get_user_input();
+
+    if input.is_empty() {
+        current.to_owned()
+    } else {
+        input
+    }
+}
+
+fn edit_epic_prompt(epic: &Epic) -> Epic {
+    println!("----------------------------");
+    println!("Epic Name [{}]:", epic.name);
+    let name = get_user_input_or(&epic.name);
+    println!("Epic Description [{}]:", epic.description);
+    let description = get_user_input_or(&epic.description);
+
+    Epic {
+        name,
+        description,
+        ..epic.clone()
+    }
+}
+
+fn edit_story_prompt(story: &Story) -> Story {
+    println!("----------------------------");
+    println!("Story Name [{}]:", story.name);
+    let name = get_user_input_or(&story.name);
+    println!("Story Description [{}]:", story.description);
+    let description = get_user_input_or(&story.description);
+
+    Story {
+        name,
+        description,
+        ..story.clone()
+    }
+}
+
+// Empty input keeps the current value, "-" clears it
+fn get_optional_user_input_or(current: Option<String>) -> Option<String> {
+    match get_user_input().as_str() {
+        "" => current,
+        "-" => None,
+        input => Some(input.to_owned()),
+    }
+}
+
+fn read_labels(current: &[String]) -> Vec<String> {
+    match get_optional_user_input_or(Some(current.join(", "))) {
+        Some(labels) => labels
+            .split(',')
+            .map(|label| label.trim().to_owned())
+            .filter(|label| !label.is_empty())
+            .collect(),
+        None => vec![],
+    }
+}
+
+fn edit_labels_prompt(labels: &[String]) -> Vec<String> {
+    println!("----------------------------");
+    println!(
+        "Labels, comma separated (- to clear) [{}]:",
+        labels.join(", ")
+    );
+
+    read_labels(labels)
+}
+
+fn edit_story_details_prompt(story: &Story) -> Story {
+    let current_priority = story
+        .priority
+        .map_or("none".to_owned(), |priority| priority.to_string());
+
+    println!("----------------------------");
+    println!(
+        "Priority (1 - LOW, 2 - MEDIUM, 3 - HIGH, 4 - CRITICAL, 0 - NONE) [{}]:",
+        current_priority
+    );
+    let priority = match get_user_input().as_str() {
+        "0" => None,
+        "1" => Some(Priority::Low),
+        "2" => Some(Priority::Medium),
+        "3" => Some(Priority::High),
+        "4" => Some(Priority::Critical),
+        _ => story.priority,
+    };
+
+    println!(
+        "Assignee (- to clear) [{}]:",
+        story.assignee.as_deref().unwrap_or_default()
+    );
+    let assignee = get_optional_user_input_or(story.assignee.clone());
+
+    println!(
+        "Labels, comma separated (- to clear) [{}]:",
+        story.labels.join(", ")
+    );
+    let labels = read_labels(&story.labels);
+
+    println!(
+        "Estimate in story points (- to clear) [{}]:",
+        story
+            .estimate
+            .map(|estimate| estimate.to_string())
+            .unwrap_or_default()
+    );
+    let estimate = match get_optional_user_input_or(story.estimate.map(|e| e.to_string())) {
+        Some(estimate) => estimate.parse::<u32>().ok().or(story.estimate),
+        None => None,
+    };
+
+    Story {
+        priority,
+        assignee,
+        labels,
+        estimate,
+        ..story.clone()
+    }
 }
 
 fn delete_epic_prompt() -> bool {
     println!("----------------------------");
-    println!("Are you sure you want to delete this epic? All stories in this epic will also be deleted [Y/n]:");
+    println!("Are you sure you want to move this epic to the trash? All stories in this epic will also be moved to the trash [Y/n]:");
     let response = get_user_input().to_lowercase();
 
-    match response.as_str() {
-        "y" => true,
-        "" => true,
-        _ => false,
-    }
+    matches!(response.as_str(), "y" | "")
 }
 
 fn delete_story_prompt() -> bool {
     println!("----------------------------");
-    println!("Are you sure you want to delete this story? [Y/n]:");
+    println!("Are you sure you want to move this story to the trash? [Y/n]:");
     let response = get_user_input().to_lowercase();
 
-    match response.as_str() {
-        "y" => true,
-        "" => true,
-        _ => false,
+    matches!(response.as_str(), "y" | "")
+}
+
+fn update_status_prompt(statuses: &[RecordStatus]) -> Option<RecordStatus> {
+    println!("----------------------------");
+
+    if statuses.is_empty() {
+        println!("This status is final and can't be changed, press enter to go back.");
+        get_user_input();
+        return None;
     }
+
+    println!("New Status ({}):", format_status_options(statuses));
+    let response = get_user_input();
+
+    let index = response.parse::<usize>().ok()?;
+
+    statuses.get(index.checked_sub(1)?).cloned()
 }
 
-fn update_status_prompt() -> Option<RecordStatus> {
+fn move_story_prompt(epics: &[(u32, Epic)]) -> Option<u32> {
     println!("----------------------------");
-    println!("New Status (1 - OPEN, 2 - IN-PROGRESS, 3 - RESOLVED, 4 - CLOSED):");
+    for (epic_id, epic) in epics {
+        println!("{} - {}", epic_id, epic.name);
+    }
+    println!("Move story to epic (empty to cancel):");
     let response = get_user_input();
 
-    match response.as_str() {
-        "1" => Some(RecordStatus::Open),
-        "2" => Some(RecordStatus::InProgress),
-        "3" => Some(RecordStatus::Resolved),
-        "4" => Some(RecordStatus::Closed),
-        _ => None,
+    let epic_id = response.parse::<u32>().ok()?;
+
+    epics
+        .iter()
+        .any(|(id, _)| *id == epic_id)
+        .then_some(epic_id)
+}
+
+fn link_story_prompt() -> Option<(LinkKind, u32)> {
+    println!("----------------------------");
+    println!("Link (blocks, blocked-by, relates-to, duplicates, duplicated-by; empty to cancel):");
+    let kind = get_user_input().parse::<LinkKind>().ok()?;
+    println!("Id of the other story:");
+    let story_id = get_user_input().parse::<u32>().ok()?;
+
+    Some((kind, story_id))
+}
+
+fn unlink_story_prompt(links: &[LinkedStory]) -> Option<usize> {
+    println!("----------------------------");
+    for (index, linked) in links.iter().enumerate() {
+        println!(
+            "{} - {} {} ({})",
+            index + 1,
+            linked.kind,
+            linked.story_id,
+            linked.story.name
+        );
+    }
+    println!("Link to remove (empty to cancel):");
+    let response = get_user_input();
+
+    let index = response.parse::<usize>().ok()?.checked_sub(1)?;
+
+    (index < links.len()).then_some(index)
+}
+
+fn start_blocked_story_prompt(blockers: &[(u32, Story)]) -> bool {
+    println!("----------------------------");
+    println!("This story is blocked by unfinished stories:");
+    for (story_id, story) in blockers {
+        println!("{} - {} [{}]", story_id, story.name, story.status);
+    }
+    println!("Start it anyway? [y/N]:");
+    let response = get_user_input().to_lowercase();
+
+    response == "y"
+}
+
+fn add_checklist_item_prompt() -> String {
+    println!("----------------------------");
+    println!("Checklist item (empty to cancel):");
+
+    get_user_input()
+}
+
+fn add_comment_prompt() -> String {
+    println!("----------------------------");
+    println!("Comment (empty to cancel):");
+
+    get_user_input()
+}
+
+fn message_prompt(text: &str) {
+    println!("----------------------------");
+    println!("{} Press enter to go back.", text);
+    get_user_input();
+}
+
+fn search_prompt() -> String {
+    println!("----------------------------");
+    println!("Search epics and stories (empty to cancel):");
+
+    get_user_input()
+}
+
+fn format_status_options(statuses: &[RecordStatus]) -> String {
+    statuses
+        .iter()
+        .enumerate()
+        .map(|(index, status)| format!("{} - {}", index + 1, status.to_string().to_uppercase()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn filter_list_prompt(view: &ListView, list: ListKind, statuses: &[RecordStatus]) -> ListView {
+    let hidden = view
+        .hidden_statuses
+        .iter()
+        .map(|status| status.to_string().to_uppercase())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    println!("----------------------------");
+    println!(
+        "Hide statuses, comma separated ({}) (- to show all) [{}]:",
+        format_status_options(statuses),
+        hidden
+    );
+    let hidden_statuses = match get_user_input().as_str() {
+        "" => view.hidden_statuses.clone(),
+        "-" => vec![],
+        input => input
+            .split(',')
+            .filter_map(|index| index.trim().parse::<usize>().ok()?.checked_sub(1))
+            .filter_map(|index| statuses.get(index).cloned())
+            .collect(),
+    };
+
+    println!(
+        "Only with label (- to clear) [{}]:",
+        view.label.as_deref().unwrap_or_default()
+    );
+    let label = get_optional_user_input_or(view.label.clone());
+
+    let assignee = match list {
+        ListKind::Epics => None,
+        ListKind::Stories => {
+            println!(
+                "Only assigned to (- to clear) [{}]:",
+                view.assignee.as_deref().unwrap_or_default()
+            );
+            get_optional_user_input_or(view.assignee.clone())
+        }
+    };
+
+    ListView {
+        hidden_statuses,
+        label,
+        assignee,
+        ..view.clone()
+    }
+}
+
+fn sort_list_prompt(current: SortKey) -> SortKey {
+    println!("----------------------------");
+    println!(
+        "Sort by (1 - ID, 2 - NAME, 3 - STATUS, 4 - CREATION DATE) [{}]:",
+        current.to_string().to_uppercase()
+    );
+
+    match get_user_input().as_str() {
+        "1" => SortKey::Id,
+        "2" => SortKey::Name,
+        "3" => SortKey::Status,
+        "4" => SortKey::Created,
+        _ => current,
     }
 }
+
+fn pick_trashed_item_prompt(items: &[TrashedItem], verb: &str) -> Option<u32> {
+    println!("----------------------------");
+    println!("Id of the item to {} (empty to cancel):", verb);
+    let response = get_user_input();
+
+    let item_id = response.parse::<u32>().ok()?;
+
+    items
+        .iter()
+        .any(|item| item.id() == item_id)
+        .then_some(item_id)
+}
+
+fn empty_trash_prompt() -> bool {
+    println!("----------------------------");
+    println!("Are you sure you want to delete everything in the trash for good? [y/N]:");
+    let response = get_user_input().to_lowercase();
+
+    response == "y"
+}
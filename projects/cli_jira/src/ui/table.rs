@@ -0,0 +1,216 @@
+use std::io::IsTerminal;
+
+use crate::ui::pages::page_helpers::{display_width, fit_to_width, wrap_to_width};
+
+/// Width used when the output isn't a terminal and `COLUMNS` isn't set.
+pub const DEFAULT_WIDTH: usize = 80;
+
+const SEPARATOR: &str = " | ";
+
+/// Width of the terminal the pages are printed to, in columns.
+pub fn terminal_width() -> usize {
+    if std::io::stdout().is_terminal() {
+        if let Ok((width, _)) = ratatui::crossterm::terminal::size() {
+            return width as usize;
+        }
+    }
+
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|width| width.parse().ok())
+        .unwrap_or(DEFAULT_WIDTH)
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ColumnWidth {
+    /// Always this many columns wide, e.g. for ids.
+    Fixed(usize),
+    /// At least `min` columns, growing up to the widest cell as long as the terminal
+    /// has room. The room left is shared evenly between these columns.
+    Fill { min: usize },
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Overflow {
+    /// Cut long cells short with "..."
+    Truncate,
+    /// Continue long cells on the following lines
+    Wrap,
+}
+
+pub struct Column {
+    pub title: String,
+    pub width: ColumnWidth,
+    pub overflow: Overflow,
+}
+
+impl Column {
+    pub fn fixed(title: &str, width: usize) -> Self {
+        Column {
+            title: title.to_owned(),
+            width: ColumnWidth::Fixed(width),
+            overflow: Overflow::Truncate,
+        }
+    }
+
+    pub fn fill(title: &str, min: usize) -> Self {
+        Column {
+            title: title.to_owned(),
+            width: ColumnWidth::Fill { min },
+            overflow: Overflow::Truncate,
+        }
+    }
+
+    pub fn wrapped(mut self) -> Self {
+        self.overflow = Overflow::Wrap;
+        self
+    }
+}
+
+/// Table printed by the pages: a centered header and one line per row, or more for
+/// wrapped cells, with the columns separated by " | ".
+pub struct Table {
+    columns: Vec<Column>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    pub fn new(columns: Vec<Column>) -> Self {
+        Table {
+            columns,
+            rows: vec![],
+        }
+    }
+
+    /// Adds a row; missing cells are left empty and extra ones are ignored.
+    pub fn add_row(&mut self, cells: Vec<String>) {
+        self.rows.push(cells);
+    }
+
+    pub fn print(&self) {
+        for line in self.render(terminal_width()) {
+            println!("{}", line);
+        }
+    }
+
+    /// The lines of the table, sized to fit `width` if the columns' minimum widths allow.
+    pub fn render(&self, width: usize) -> Vec<String> {
+        let widths = self.column_widths(width);
+        let mut lines = vec![];
+
+        let header: Vec<_> = self
+            .columns
+            .iter()
+            .zip(&widths)
+            .map(|(column, &width)| center(&column.title, width))
+            .collect();
+        lines.push(join_cells(header));
+
+        for row in &self.rows {
+            let cells: Vec<Vec<String>> = self
+                .columns
+                .iter()
+                .zip(&widths)
+                .enumerate()
+                .map(|(index, (column, &width))| {
+                    let text = row.get(index).map_or("", String::as_str);
+
+                    match column.overflow {
+                        Overflow::Truncate => vec![fit_to_width(text, width)],
+                        Overflow::Wrap => wrap_to_width(text, width)
+                            .iter()
+                            .map(|line| fit_to_width(line, width))
+                            .collect(),
+                    }
+                })
+                .collect();
+            let height = cells.iter().map(Vec::len).max().unwrap_or_default();
+
+            for line_index in 0..height {
+                let line: Vec<_> = cells
+                    .iter()
+                    .zip(&widths)
+                    .map(|(cell, &width)| {
+                        cell.get(line_index)
+                            .cloned()
+                            .unwrap_or_else(|| " ".repeat(width))
+                    })
+                    .collect();
+                lines.push(join_cells(line));
+            }
+        }
+
+        lines
+    }
+
+    fn column_widths(&self, width: usize) -> Vec<usize> {
+        let mut widths: Vec<_> = self
+            .columns
+            .iter()
+            .map(|column| match column.width {
+                ColumnWidth::Fixed(width) => width,
+                ColumnWidth::Fill { min } => min,
+            })
+            .collect();
+
+        // widest title or cell of every column, which fill columns grow towards
+        let natural: Vec<_> = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(index, column)| {
+                self.rows
+                    .iter()
+                    .filter_map(|row| row.get(index))
+                    .map(|cell| display_width(cell))
+                    .chain([display_width(&column.title)])
+                    .max()
+                    .unwrap_or_default()
+            })
+            .collect();
+
+        let used =
+            widths.iter().sum::<usize>() + SEPARATOR.len() * self.columns.len().saturating_sub(1);
+        let mut room = width.saturating_sub(used);
+
+        while room > 0 {
+            let mut grown = false;
+
+            for (index, column) in self.columns.iter().enumerate() {
+                let is_fill = matches!(column.width, ColumnWidth::Fill { .. });
+
+                if room > 0 && is_fill && widths[index] < natural[index] {
+                    widths[index] += 1;
+                    room -= 1;
+                    grown = true;
+                }
+            }
+
+            if !grown {
+                break;
+            }
+        }
+
+        widths
+    }
+}
+
+// Leaves out trailing empty cells and padding, so that lines don't end in a separator
+fn join_cells(mut cells: Vec<String>) -> String {
+    while cells.len() > 1 && cells.last().is_some_and(|cell| cell.trim().is_empty()) {
+        cells.pop();
+    }
+
+    cells.join(SEPARATOR).trim_end().to_owned()
+}
+
+fn center(text: &str, width: usize) -> String {
+    let text_width = display_width(text);
+
+    if text_width >= width {
+        return fit_to_width(text, width);
+    }
+
+    let left = (width - text_width) / 2;
+    fit_to_width(&format!("{}{}", " ".repeat(left), text), width)
+}
@@ -0,0 +1,269 @@
+use anyhow::Result;
+use std::collections::BTreeMap;
+
+use crate::db::JiraDatabase;
+use crate::models::{Change, Comment, DBState, Epic, Story, StoryLink};
+
+/// Older revisions are dropped once the stack grows past this.
+const MAX_REVISIONS: usize = 100;
+
+/// Some epics and stories as they were at one point, `None` for the ones that
+/// didn't exist. Epics are kept without their story list; which epic a story
+/// belongs to is stored next to the story instead.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct Snapshot {
+    pub epics: BTreeMap<u32, Option<Epic>>,
+    /// Story with the id of its epic
+    pub stories: BTreeMap<u32, Option<(u32, Story)>>,
+    /// Whether each comment was there
+    pub comments: BTreeMap<Comment, bool>,
+    /// Whether each link was there
+    pub links: BTreeMap<StoryLink, bool>,
+}
+
+impl Snapshot {
+    // Single items, to be filled in with `JiraDatabase::read_items`.
+
+    pub fn of_epic(epic_id: u32) -> Snapshot {
+        Snapshot {
+            epics: [(epic_id, None)].into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn of_story(story_id: u32) -> Snapshot {
+        Snapshot {
+            stories: [(story_id, None)].into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn of_comment(comment: &Comment) -> Snapshot {
+        Snapshot {
+            comments: [(comment.clone(), false)].into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn of_link(link: &StoryLink) -> Snapshot {
+        Snapshot {
+            links: [(link.clone(), false)].into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.epics.is_empty()
+            && self.stories.is_empty()
+            && self.comments.is_empty()
+            && self.links.is_empty()
+    }
+
+    /// Adds the items of `other` that `self` doesn't have yet.
+    pub fn add_missing(&mut self, other: Snapshot) {
+        for (epic_id, epic) in other.epics {
+            self.epics.entry(epic_id).or_insert(epic);
+        }
+        for (story_id, story) in other.stories {
+            self.stories.entry(story_id).or_insert(story);
+        }
+        for (comment, present) in other.comments {
+            self.comments.entry(comment).or_insert(present);
+        }
+        for (link, present) in other.links {
+            self.links.entry(link).or_insert(present);
+        }
+    }
+
+    /// Takes the items of `db_state` with the same ids as `self`.
+    pub fn of_same_items(&self, db_state: &DBState) -> Snapshot {
+        Snapshot {
+            epics: self
+                .epics
+                .keys()
+                .map(|&epic_id| (epic_id, epic_of(db_state, epic_id)))
+                .collect(),
+            stories: self
+                .stories
+                .keys()
+                .map(|&story_id| (story_id, story_of(db_state, story_id)))
+                .collect(),
+            comments: self
+                .comments
+                .keys()
+                .map(|comment| (comment.clone(), db_state.comments.contains(comment)))
+                .collect(),
+            links: self
+                .links
+                .keys()
+                .map(|link| (link.clone(), db_state.links.contains(link)))
+                .collect(),
+        }
+    }
+
+    /// Ids of the epics and stories, and of the stories with comments or links.
+    pub fn item_ids(&self) -> Vec<u32> {
+        let mut item_ids: Vec<u32> = self
+            .epics
+            .keys()
+            .chain(self.stories.keys())
+            .chain(self.comments.keys().map(|comment| &comment.story_id))
+            .chain(self.links.keys().flat_map(|link| [&link.from, &link.to]))
+            .copied()
+            .collect();
+
+        item_ids.sort();
+        item_ids.dedup();
+        item_ids
+    }
+}
+
+fn epic_of(db_state: &DBState, epic_id: u32) -> Option<Epic> {
+    db_state.epics.get(&epic_id).map(|epic| Epic {
+        stories: vec![],
+        ..epic.clone()
+    })
+}
+
+fn story_of(db_state: &DBState, story_id: u32) -> Option<(u32, Story)> {
+    let story = db_state.stories.get(&story_id)?;
+    let (&epic_id, _) = db_state
+        .epics
+        .iter()
+        .find(|(_, epic)| epic.stories.contains(&story_id))?;
+
+    Some((epic_id, story.clone()))
+}
+
+/// The items one action changed, before and after it.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Revision {
+    pub before: Snapshot,
+    pub after: Snapshot,
+}
+
+impl Revision {
+    /// Pairs two snapshots of the same items, leaving out the ones that are the same
+    /// in both; `None` if all of them are.
+    pub fn new(mut before: Snapshot, mut after: Snapshot) -> Option<Revision> {
+        retain_changed(&mut before.epics, &mut after.epics);
+        retain_changed(&mut before.stories, &mut after.stories);
+        retain_changed(&mut before.comments, &mut after.comments);
+        retain_changed(&mut before.links, &mut after.links);
+
+        if before.is_empty() {
+            return None;
+        }
+
+        Some(Revision { before, after })
+    }
+
+    /// Diffs two states of the board, `None` if no epic, story, comment or link changed.
+    pub fn between(before: &DBState, after: &DBState) -> Option<Revision> {
+        let mut changed = Snapshot::default();
+
+        for epic_id in before.epics.keys().chain(after.epics.keys()) {
+            if epic_of(before, *epic_id) != epic_of(after, *epic_id) {
+                changed.epics.insert(*epic_id, None);
+            }
+        }
+
+        for story_id in before.stories.keys().chain(after.stories.keys()) {
+            if story_of(before, *story_id) != story_of(after, *story_id) {
+                changed.stories.insert(*story_id, None);
+            }
+        }
+
+        for comment in before.comments.iter().chain(after.comments.iter()) {
+            if before.comments.contains(comment) != after.comments.contains(comment) {
+                changed.comments.insert(comment.clone(), false);
+            }
+        }
+
+        for link in before.links.iter().chain(after.links.iter()) {
+            if before.links.contains(link) != after.links.contains(link) {
+                changed.links.insert(link.clone(), false);
+            }
+        }
+
+        if changed.is_empty() {
+            return None;
+        }
+
+        Some(Revision {
+            before: changed.of_same_items(before),
+            after: changed.of_same_items(after),
+        })
+    }
+}
+
+fn retain_changed<K: Ord, V: PartialEq>(before: &mut BTreeMap<K, V>, after: &mut BTreeMap<K, V>) {
+    before.retain(|key, value| after.get(key) != Some(value));
+    after.retain(|key, _| before.contains_key(key));
+}
+
+/// Undo and redo history of a session.
+#[derive(Debug, Default)]
+pub struct UndoStack {
+    undo: Vec<Revision>,
+    redo: Vec<Revision>,
+}
+
+impl UndoStack {
+    /// Remembers the changes between the two states so they can be undone, see `push`.
+    pub fn record(&mut self, before: &DBState, after: &DBState) {
+        if let Some(revision) = Revision::between(before, after) {
+            self.push(revision);
+        }
+    }
+
+    /// Remembers a change so it can be undone. A new change makes the undone ones
+    /// impossible to redo.
+    pub fn push(&mut self, revision: Revision) {
+        self.undo.push(revision);
+        self.redo.clear();
+
+        if self.undo.len() > MAX_REVISIONS {
+            self.undo.remove(0);
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Puts the items touched by the last change back the way they were. Returns
+    /// false if there was nothing to undo.
+    pub fn undo(&mut self, db: &JiraDatabase) -> Result<bool> {
+        let Some(revision) = self.undo.pop() else {
+            return Ok(false);
+        };
+
+        if let Err(error) = db.restore(&revision.after, &revision.before, Change::Undone) {
+            self.undo.push(revision);
+            return Err(error);
+        }
+
+        self.redo.push(revision);
+        Ok(true)
+    }
+
+    /// Applies the last undone change again. Returns false if there was nothing to redo.
+    pub fn redo(&mut self, db: &JiraDatabase) -> Result<bool> {
+        let Some(revision) = self.redo.pop() else {
+            return Ok(false);
+        };
+
+        if let Err(error) = db.restore(&revision.before, &revision.after, Change::Redone) {
+            self.redo.push(revision);
+            return Err(error);
+        }
+
+        self.undo.push(revision);
+        Ok(true)
+    }
+}
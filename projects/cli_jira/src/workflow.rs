@@ -0,0 +1,231 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::models::RecordStatus;
+
+/// Statuses an epic or story can have and which changes between them are allowed.
+/// Configured per board in `config.toml`, e.g.
+///
+/// ```toml
+/// [workflow]
+/// statuses = ["Open", "InProgress", "Review", "Closed"]
+///
+/// [workflow.transitions]
+/// Open = ["InProgress"]
+/// InProgress = ["Review", "Open"]
+/// Review = ["Closed", "InProgress"]
+/// ```
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+#[serde(from = "WorkflowConfig")]
+pub struct Workflow {
+    /// All statuses, in the order they are offered. New items start in the first one.
+    pub statuses: Vec<RecordStatus>,
+    /// Allowed next statuses of each status; a status without an entry is final.
+    /// Leaving the table out altogether allows every change.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub transitions: BTreeMap<RecordStatus, Vec<RecordStatus>>,
+    /// Statuses that count as finished, for epic progress and the rules below. Left
+    /// out of the config, those of Resolved and Closed that the workflow has.
+    pub done: Vec<RecordStatus>,
+    /// Lets an epic be finished while some of its stories aren't.
+    pub allow_unfinished_epics: bool,
+    /// Moves an epic to the first reachable `done` status once all of its stories are done.
+    pub auto_resolve_epics: bool,
+}
+
+// `Workflow` as written in the config, where most fields may be left out
+#[derive(Deserialize)]
+struct WorkflowConfig {
+    #[serde(default = "default_statuses")]
+    statuses: Vec<RecordStatus>,
+    #[serde(default)]
+    transitions: BTreeMap<RecordStatus, Vec<RecordStatus>>,
+    done: Option<Vec<RecordStatus>>,
+    #[serde(default)]
+    allow_unfinished_epics: bool,
+    #[serde(default)]
+    auto_resolve_epics: bool,
+}
+
+impl From<WorkflowConfig> for Workflow {
+    fn from(config: WorkflowConfig) -> Self {
+        let done = config.done.unwrap_or_else(|| {
+            default_done_statuses()
+                .into_iter()
+                .filter(|status| config.statuses.contains(status))
+                .collect()
+        });
+
+        Workflow {
+            statuses: config.statuses,
+            transitions: config.transitions,
+            done,
+            allow_unfinished_epics: config.allow_unfinished_epics,
+            auto_resolve_epics: config.auto_resolve_epics,
+        }
+    }
+}
+
+fn default_statuses() -> Vec<RecordStatus> {
+    vec![
+        RecordStatus::Open,
+        RecordStatus::InProgress,
+        RecordStatus::Resolved,
+        RecordStatus::Closed,
+    ]
+}
+
+fn default_done_statuses() -> Vec<RecordStatus> {
+    vec![RecordStatus::Resolved, RecordStatus::Closed]
+}
+
+impl Default for Workflow {
+    /// Closed items can only be reopened as in progress, and nothing goes back to open
+    /// once it has been resolved.
+    fn default() -> Self {
+        use RecordStatus::*;
+
+        Workflow {
+            statuses: default_statuses(),
+            transitions: BTreeMap::from([
+                (Open, vec![InProgress, Resolved, Closed]),
+                (InProgress, vec![Open, Resolved, Closed]),
+                (Resolved, vec![InProgress, Closed]),
+                (Closed, vec![InProgress]),
+            ]),
+            done: default_done_statuses(),
+            allow_unfinished_epics: false,
+            auto_resolve_epics: false,
+        }
+    }
+}
+
+impl Workflow {
+    pub fn validate(&self) -> Result<()> {
+        if self.statuses.is_empty() {
+            return Err(anyhow!("workflow has no statuses"));
+        }
+
+        for (index, status) in self.statuses.iter().enumerate() {
+            if self.statuses[..index].contains(status) {
+                return Err(anyhow!("workflow status {} is listed twice", status));
+            }
+        }
+
+        for (from, targets) in &self.transitions {
+            for status in std::iter::once(from).chain(targets) {
+                if !self.statuses.contains(status) {
+                    return Err(anyhow!(
+                        "workflow transition uses {}, which is not one of its statuses",
+                        status
+                    ));
+                }
+            }
+        }
+
+        for status in &self.done {
+            if !self.statuses.contains(status) {
+                return Err(anyhow!(
+                    "workflow counts {} as done, which is not one of its statuses",
+                    status
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn initial_status(&self) -> RecordStatus {
+        self.statuses.first().cloned().unwrap_or_default()
+    }
+
+    /// Maps a status typed by the user to the workflow's spelling of it, so that
+    /// `review` matches a custom `Review` status.
+    pub fn resolve(&self, status: RecordStatus) -> RecordStatus {
+        let RecordStatus::Custom(name) = &status else {
+            return status;
+        };
+
+        self.statuses
+            .iter()
+            .find(|candidate| candidate.to_string().eq_ignore_ascii_case(name))
+            .cloned()
+            .unwrap_or(status)
+    }
+
+    pub fn is_done(&self, status: &RecordStatus) -> bool {
+        self.done.contains(status)
+    }
+
+    /// The status an epic in `from` is moved to once all of its stories are done, if
+    /// the workflow allows one.
+    pub fn auto_resolve_status(&self, from: &RecordStatus) -> Option<RecordStatus> {
+        let next_statuses = self.next_statuses(from);
+
+        self.done
+            .iter()
+            .find(|status| next_statuses.contains(status))
+            .cloned()
+    }
+
+    /// Statuses an item in `from` may move to, in workflow order.
+    pub fn next_statuses(&self, from: &RecordStatus) -> Vec<RecordStatus> {
+        // items left over from an older workflow may move anywhere
+        let allowed = |status: &RecordStatus| {
+            status != from
+                && (self.transitions.is_empty()
+                    || !self.statuses.contains(from)
+                    || self
+                        .transitions
+                        .get(from)
+                        .is_some_and(|targets| targets.contains(status)))
+        };
+
+        self.statuses
+            .iter()
+            .filter(|status| allowed(status))
+            .cloned()
+            .collect()
+    }
+
+    /// Fails with a message listing the legal next statuses if `from` can't move to `to`.
+    pub fn check_transition(&self, from: &RecordStatus, to: &RecordStatus) -> Result<()> {
+        if from == to {
+            return Ok(());
+        }
+
+        if !self.statuses.contains(to) {
+            return Err(anyhow!(
+                "unknown status '{}', expected one of: {}",
+                to,
+                format_statuses(&self.statuses)
+            ));
+        }
+
+        let next_statuses = self.next_statuses(from);
+
+        if next_statuses.contains(to) {
+            return Ok(());
+        }
+
+        if next_statuses.is_empty() {
+            return Err(anyhow!("{} is a final status and can't be changed", from));
+        }
+
+        Err(anyhow!(
+            "cannot change status from {} to {}, allowed: {}",
+            from,
+            to,
+            format_statuses(&next_statuses)
+        ))
+    }
+}
+
+fn format_statuses(statuses: &[RecordStatus]) -> String {
+    statuses
+        .iter()
+        .map(|status| status.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -0,0 +1,629 @@
+// Asserts compare with `true`/`false` on purpose, as throughout the suite.
+#![allow(clippy::bool_assert_comparison)]
+
+mod common;
+
+use clap::Parser;
+use cli_jira::cli::{
+    run, run_board_command, BoardCommand, Command, EpicCommand, StoryCommand, StoryDetailsArgs,
+};
+use cli_jira::config::{Backend, Config};
+use cli_jira::db::JiraDatabase;
+use cli_jira::models::{Epic, Priority, RecordStatus, Story};
+use cli_jira::workflow::Workflow;
+use common::MockDB;
+
+#[derive(Parser)]
+struct TestCli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+fn parse(args: &[&str]) -> Command {
+    TestCli::try_parse_from(std::iter::once("cli_jira").chain(args.iter().copied()))
+        .unwrap()
+        .command
+}
+
+fn run_to_string(db: &JiraDatabase, command: Command, json: bool) -> String {
+    let mut out = Vec::new();
+    run(db, command, json, &mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn should_parse_subcommands() {
+    assert_eq!(
+        parse(&["story", "create", "--epic", "3", "--name", "story"]),
+        Command::Story {
+            command: StoryCommand::Create {
+                epic: 3,
+                name: "story".to_owned(),
+                description: "".to_owned(),
+                details: StoryDetailsArgs::default(),
+            }
+        }
+    );
+    assert_eq!(
+        parse(&[
+            "story",
+            "edit",
+            "4",
+            "--priority",
+            "high",
+            "--label",
+            "a",
+            "--label",
+            "b",
+        ]),
+        Command::Story {
+            command: StoryCommand::Edit {
+                story_id: 4,
+                name: None,
+                description: None,
+                details: StoryDetailsArgs {
+                    priority: Some(Priority::High),
+                    labels: vec!["a".to_owned(), "b".to_owned()],
+                    ..Default::default()
+                },
+                clear_assignee: false,
+                clear_labels: false,
+            }
+        }
+    );
+    assert_eq!(
+        parse(&["story", "set-status", "7", "in-progress"]),
+        Command::Story {
+            command: StoryCommand::SetStatus {
+                story_id: 7,
+                status: RecordStatus::InProgress,
+            }
+        }
+    );
+    assert_eq!(
+        parse(&["epic", "list"]),
+        Command::Epic {
+            command: EpicCommand::List
+        }
+    );
+    // custom statuses are checked against the workflow when the command runs
+    assert_eq!(
+        parse(&["story", "set-status", "7", "done"]),
+        Command::Story {
+            command: StoryCommand::SetStatus {
+                story_id: 7,
+                status: RecordStatus::Custom("done".to_owned()),
+            }
+        }
+    );
+    assert_eq!(
+        TestCli::try_parse_from(["cli_jira", "story", "set-status", "7", " "]).is_err(),
+        true
+    );
+    assert_eq!(" High ".parse::<Priority>(), Ok(Priority::High));
+    assert_eq!(" ".parse::<Priority>().is_err(), true);
+}
+
+#[test]
+fn create_commands_should_print_new_ids() {
+    let db = JiraDatabase::with_database(Box::new(MockDB::new()), Workflow::default());
+
+    let output = run_to_string(&db, parse(&["epic", "create", "--name", "epic"]), false);
+    assert_eq!(output, "Created epic 1\n");
+
+    let output = run_to_string(
+        &db,
+        parse(&["story", "create", "--epic", "1", "--name", "story"]),
+        true,
+    );
+    assert_eq!(output, "{\"id\":2}\n");
+
+    let db_state = db.read_db().unwrap();
+    assert_eq!(db_state.epics[&1].name, "epic".to_owned());
+    assert_eq!(db_state.epics[&1].stories, vec![2]);
+    assert_eq!(db_state.stories[&2].name, "story".to_owned());
+}
+
+#[test]
+fn list_commands_should_print_json() {
+    let db = JiraDatabase::with_database(Box::new(MockDB::new()), Workflow::default());
+    let epic_id = db
+        .create_epic(Epic::new("epic".to_owned(), "".to_owned()))
+        .unwrap();
+    db.create_story(Story::new("story".to_owned(), "".to_owned()), epic_id)
+        .unwrap();
+
+    let output = run_to_string(&db, parse(&["story", "list", "--epic", "1"]), true);
+    let stories: serde_json::Value = serde_json::from_str(&output).unwrap();
+
+    assert_eq!(stories[0]["id"], 2);
+    assert_eq!(stories[0]["name"], "story");
+    assert_eq!(stories[0]["status"], "Open");
+
+    let output = run_to_string(&db, parse(&["epic", "list"]), false);
+    assert_eq!(output.lines().count(), 2);
+    assert_eq!(output.contains("epic"), true);
+}
+
+#[test]
+fn search_command_should_list_hits() {
+    let db = JiraDatabase::with_database(Box::new(MockDB::new()), Workflow::default());
+    let epic_id = db
+        .create_epic(Epic::new("Login".to_owned(), "".to_owned()))
+        .unwrap();
+    db.create_story(Story::new("Login page".to_owned(), "".to_owned()), epic_id)
+        .unwrap();
+
+    let output = run_to_string(&db, parse(&["search", "login", "page"]), false);
+    assert_eq!(output.lines().count(), 2);
+    assert_eq!(output.contains("Login page"), true);
+
+    let output = run_to_string(&db, parse(&["search", "login"]), true);
+    let hits: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(hits[0]["type"], "epic");
+    assert_eq!(hits[1]["story_id"], 2);
+}
+
+#[test]
+fn trash_commands_should_restore_and_purge() {
+    let db = JiraDatabase::with_database(Box::new(MockDB::new()), Workflow::default());
+    let epic_id = db
+        .create_epic(Epic::new("epic".to_owned(), "".to_owned()))
+        .unwrap();
+    let story_id = db
+        .create_story(Story::new("story".to_owned(), "".to_owned()), epic_id)
+        .unwrap();
+
+    run_to_string(&db, parse(&["story", "delete", "2"]), false);
+    run_to_string(&db, parse(&["epic", "delete", "1"]), false);
+
+    let output = run_to_string(&db, parse(&["trash", "list"]), true);
+    let items: serde_json::Value = serde_json::from_str(&output).unwrap();
+    // the story goes with its epic
+    assert_eq!(items.as_array().unwrap().len(), 1);
+    assert_eq!(items[0]["type"], "epic");
+
+    run_to_string(&db, parse(&["trash", "restore", "1"]), false);
+    assert_eq!(db.get_epic(epic_id).unwrap().is_some(), true);
+    assert_eq!(db.get_story(story_id).unwrap(), None);
+
+    let output = run_to_string(&db, parse(&["trash", "list"]), false);
+    assert_eq!(output.lines().count(), 2);
+
+    // just deleted, so not old enough
+    let output = run_to_string(&db, parse(&["trash", "purge", "--older-than", "30"]), false);
+    assert_eq!(output, "Purged 0 items\n");
+
+    run_to_string(&db, parse(&["trash", "purge", "2"]), false);
+    assert_eq!(db.read_db().unwrap().stories.is_empty(), true);
+
+    let mut out = Vec::new();
+    let result = run(&db, parse(&["trash", "restore", "1"]), false, &mut out);
+    assert_eq!(result.is_err(), true);
+
+    let result = TestCli::try_parse_from(["cli_jira", "trash", "purge"]);
+    assert_eq!(result.is_err(), true);
+}
+
+#[test]
+fn update_and_delete_commands_should_work() {
+    let db = JiraDatabase::with_database(Box::new(MockDB::new()), Workflow::default());
+    let epic_id = db
+        .create_epic(Epic::new("epic".to_owned(), "".to_owned()))
+        .unwrap();
+    let story_id = db
+        .create_story(Story::new("story".to_owned(), "".to_owned()), epic_id)
+        .unwrap();
+
+    run_to_string(&db, parse(&["story", "set-status", "2", "resolved"]), false);
+
+    let mut out = Vec::new();
+    let result = run(
+        &db,
+        parse(&["story", "set-status", "2", "done"]),
+        false,
+        &mut out,
+    );
+    assert_eq!(result.is_err(), true);
+
+    run_to_string(
+        &db,
+        parse(&["story", "edit", "2", "--name", "renamed"]),
+        false,
+    );
+
+    run_to_string(
+        &db,
+        parse(&["story", "edit", "2", "--assignee", "bob", "--estimate", "3"]),
+        false,
+    );
+    run_to_string(
+        &db,
+        parse(&["epic", "edit", "1", "--label", "backend"]),
+        false,
+    );
+
+    let story = db.get_story(story_id).unwrap().unwrap();
+    assert_eq!(story.status, RecordStatus::Resolved);
+    assert_eq!(story.name, "renamed".to_owned());
+    assert_eq!(story.assignee, Some("bob".to_owned()));
+    assert_eq!(story.estimate, Some(3));
+    assert_eq!(
+        db.get_epic(epic_id).unwrap().unwrap().labels,
+        vec!["backend".to_owned()]
+    );
+
+    let output = run_to_string(&db, parse(&["story", "history", "2"]), false);
+    // created, status, name, assignee, estimate
+    assert_eq!(output.lines().count(), 5);
+    assert_eq!(output.contains("status: Open -> Resolved"), true);
+
+    run_to_string(&db, parse(&["story", "delete", "2"]), false);
+
+    assert_eq!(db.get_story(story_id).unwrap(), None);
+    assert_eq!(db.get_epic(epic_id).unwrap().unwrap().stories.len(), 0);
+
+    let mut out = Vec::new();
+    let result = run(&db, parse(&["story", "delete", "2"]), false, &mut out);
+    assert_eq!(result.is_err(), true);
+}
+
+#[test]
+fn edit_commands_should_change_all_fields_at_once_and_clear_them() {
+    let db = JiraDatabase::with_database(Box::new(MockDB::new()), Workflow::default());
+    let epic_id = db
+        .create_epic(Epic::new("epic".to_owned(), "".to_owned()))
+        .unwrap();
+    let story_id = db
+        .create_story(Story::new("story".to_owned(), "".to_owned()), epic_id)
+        .unwrap();
+
+    run_to_string(
+        &db,
+        parse(&[
+            "story",
+            "edit",
+            "2",
+            "--name",
+            "renamed",
+            "--assignee",
+            "bob",
+            "--label",
+            "api",
+        ]),
+        false,
+    );
+
+    let story = db.get_story(story_id).unwrap().unwrap();
+    assert_eq!(story.name, "renamed".to_owned());
+    assert_eq!(story.assignee, Some("bob".to_owned()));
+    assert_eq!(story.labels, vec!["api".to_owned()]);
+
+    // one entry per field, all from the same change
+    let history = db.history_of(story_id).unwrap();
+    assert_eq!(history.len(), 4);
+    assert_eq!(history[1].timestamp, history[3].timestamp);
+
+    run_to_string(
+        &db,
+        parse(&["story", "edit", "2", "--clear-assignee", "--clear-labels"]),
+        false,
+    );
+
+    let story = db.get_story(story_id).unwrap().unwrap();
+    assert_eq!(story.assignee, None);
+    assert_eq!(story.labels.is_empty(), true);
+
+    run_to_string(
+        &db,
+        parse(&["epic", "edit", "1", "--label", "backend"]),
+        false,
+    );
+    run_to_string(&db, parse(&["epic", "edit", "1", "--clear-labels"]), false);
+    assert_eq!(
+        db.get_epic(epic_id).unwrap().unwrap().labels.is_empty(),
+        true
+    );
+
+    let result = TestCli::try_parse_from([
+        "cli_jira",
+        "story",
+        "edit",
+        "2",
+        "--assignee",
+        "bob",
+        "--clear-assignee",
+    ]);
+    assert_eq!(result.is_err(), true);
+}
+
+#[test]
+fn board_commands_should_update_config() {
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("config.toml");
+    let mut config = Config::default();
+    let mut out = Vec::new();
+
+    run_board_command(
+        &mut config,
+        &config_path,
+        BoardCommand::Add {
+            name: "mobile".to_owned(),
+            path: None,
+            backend: Some(Backend::Sqlite),
+        },
+        false,
+        &mut out,
+    )
+    .unwrap();
+    run_board_command(
+        &mut config,
+        &config_path,
+        BoardCommand::Use {
+            name: "mobile".to_owned(),
+        },
+        false,
+        &mut out,
+    )
+    .unwrap();
+
+    let saved = Config::load(&config_path).unwrap();
+    assert_eq!(saved, config);
+    assert_eq!(saved.current_board(), "mobile");
+    assert_eq!(saved.boards["mobile"].backend, Backend::Sqlite);
+
+    let result = run_board_command(
+        &mut config,
+        &config_path,
+        BoardCommand::Use {
+            name: "unknown".to_owned(),
+        },
+        false,
+        &mut out,
+    );
+    assert_eq!(result.is_err(), true);
+
+    let mut out = Vec::new();
+    run_board_command(
+        &mut config,
+        &config_path,
+        BoardCommand::List,
+        true,
+        &mut out,
+    )
+    .unwrap();
+    let boards: serde_json::Value = serde_json::from_slice(&out).unwrap();
+
+    assert_eq!(boards[0]["name"], "default");
+    assert_eq!(boards[0]["current"], false);
+    assert_eq!(boards[1]["name"], "mobile");
+    assert_eq!(boards[1]["current"], true);
+
+    run_board_command(
+        &mut config,
+        &config_path,
+        BoardCommand::Remove {
+            name: "mobile".to_owned(),
+        },
+        false,
+        &mut out,
+    )
+    .unwrap();
+
+    assert_eq!(Config::load(&config_path).unwrap(), Config::default());
+}
+
+#[test]
+fn export_and_import_commands_should_work() {
+    let db = JiraDatabase::with_database(Box::new(MockDB::new()), Workflow::default());
+    let epic_id = db
+        .create_epic(Epic::new("epic".to_owned(), "".to_owned()))
+        .unwrap();
+    db.create_story(Story::new("story".to_owned(), "".to_owned()), epic_id)
+        .unwrap();
+
+    let output = run_to_string(&db, parse(&["export", "--format", "markdown"]), false);
+    assert_eq!(output.starts_with("# Board"), true);
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("board.csv");
+    let path = path.to_str().unwrap();
+
+    let output = run_to_string(
+        &db,
+        parse(&["export", "--format", "csv", "--epic", "1", "-o", path]),
+        false,
+    );
+    assert_eq!(output, "");
+
+    let output = run_to_string(&db, parse(&["import", "--dry-run", path]), false);
+    assert_eq!(
+        output,
+        "epic - 'epic'\nstory - 'story' in epic 'epic'\nWould create 1 epics and 1 stories\n"
+    );
+    assert_eq!(db.list_epics().unwrap().len(), 1);
+
+    let output = run_to_string(&db, parse(&["import", path]), true);
+    let summary: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(summary["items"][0]["id"], 3);
+    assert_eq!(db.list_stories_of_epic(3).unwrap().len(), 1);
+
+    let mut out = Vec::new();
+    let result = run(
+        &db,
+        parse(&["export", "--format", "json", "--epic", "9"]),
+        false,
+        &mut out,
+    );
+    assert_eq!(result.is_err(), true);
+}
+
+#[test]
+fn check_command_should_report_and_repair_problems() {
+    let db = JiraDatabase::with_database(Box::new(MockDB::new()), Workflow::default());
+    let epic_id = db
+        .create_epic(Epic::new("epic".to_owned(), "".to_owned()))
+        .unwrap();
+
+    let output = run_to_string(&db, parse(&["check"]), false);
+    assert_eq!(output, "No problems found\n");
+
+    let mut db_state = db.read_db().unwrap();
+    db_state.epics.get_mut(&epic_id).unwrap().stories.push(7);
+    db.database.write_db(&db_state).unwrap();
+
+    let mut out = Vec::new();
+    let result = run(&db, parse(&["fsck"]), false, &mut out);
+    assert_eq!(result.is_err(), true);
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "epic 1 lists story 7, which doesn't exist\n"
+    );
+
+    let output = run_to_string(&db, parse(&["check", "--repair"]), true);
+    let problems: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(problems[0]["type"], "dangling_story");
+
+    assert_eq!(db.check().unwrap().is_empty(), true);
+}
+
+#[test]
+fn checklist_commands_should_work() {
+    let db = JiraDatabase::with_database(Box::new(MockDB::new()), Workflow::default());
+    let epic_id = db
+        .create_epic(Epic::new("epic".to_owned(), "".to_owned()))
+        .unwrap();
+    db.create_story(Story::new("story".to_owned(), "".to_owned()), epic_id)
+        .unwrap();
+
+    let output = run_to_string(
+        &db,
+        parse(&["story", "checklist", "add", "2", "write tests"]),
+        false,
+    );
+    assert_eq!(output, "Added checklist item 1 of story 2\n");
+
+    let output = run_to_string(
+        &db,
+        parse(&["story", "checklist", "add", "2", "review"]),
+        true,
+    );
+    let added: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(added["number"], 2);
+
+    let output = run_to_string(
+        &db,
+        parse(&["story", "checklist", "toggle", "2", "1"]),
+        false,
+    );
+    assert_eq!(output, "Ticked off checklist item 1 of story 2\n");
+
+    let mut out = Vec::new();
+    let result = run(
+        &db,
+        parse(&["story", "checklist", "remove", "2", "0"]),
+        false,
+        &mut out,
+    );
+    assert_eq!(result.is_err(), true);
+
+    run_to_string(
+        &db,
+        parse(&["story", "checklist", "remove", "2", "2"]),
+        false,
+    );
+
+    let output = run_to_string(&db, parse(&["story", "show", "2"]), false);
+    assert_eq!(output.ends_with("[x] 1. write tests\n"), true);
+}
+
+#[test]
+fn comment_commands_should_work() {
+    let db = JiraDatabase::with_database(Box::new(MockDB::new()), Workflow::default());
+    let epic_id = db
+        .create_epic(Epic::new("epic".to_owned(), "".to_owned()))
+        .unwrap();
+    db.create_story(Story::new("story".to_owned(), "".to_owned()), epic_id)
+        .unwrap();
+
+    let output = run_to_string(
+        &db,
+        parse(&["story", "comment", "2", "needs a design review"]),
+        false,
+    );
+    assert_eq!(output, "Commented on story 2\n");
+
+    let output = run_to_string(&db, parse(&["story", "comments", "2"]), false);
+    assert_eq!(output.trim_end().ends_with(": needs a design review"), true);
+
+    let output = run_to_string(&db, parse(&["story", "comments", "2"]), true);
+    let comments: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(comments[0]["text"], "needs a design review");
+
+    let mut out = Vec::new();
+    let result = run(&db, parse(&["story", "comments", "9"]), false, &mut out);
+    assert_eq!(result.is_err(), true);
+}
+
+#[test]
+fn link_commands_should_work() {
+    let db = JiraDatabase::with_database(Box::new(MockDB::new()), Workflow::default());
+    let epic_id = db
+        .create_epic(Epic::new("epic".to_owned(), "".to_owned()))
+        .unwrap();
+    let other_epic_id = db
+        .create_epic(Epic::new("other".to_owned(), "".to_owned()))
+        .unwrap();
+    db.create_story(Story::new("schema".to_owned(), "".to_owned()), epic_id)
+        .unwrap();
+    db.create_story(Story::new("login".to_owned(), "".to_owned()), other_epic_id)
+        .unwrap();
+
+    let output = run_to_string(&db, parse(&["story", "link", "3", "blocks", "4"]), false);
+    assert_eq!(output, "Linked story 3\n");
+
+    let output = run_to_string(&db, parse(&["story", "links", "4"]), false);
+    assert_eq!(output, "blocked by 3 - schema [Open]\n");
+
+    let output = run_to_string(&db, parse(&["story", "show", "4"]), false);
+    assert_eq!(output.contains("links: blocked by 3\n"), true);
+
+    // starting a blocked story works, the warning goes to stderr
+    let output = run_to_string(
+        &db,
+        parse(&["story", "set-status", "4", "in-progress"]),
+        false,
+    );
+    assert_eq!(output, "Updated story 4\n");
+
+    let output = run_to_string(&db, parse(&["story", "set-status", "4", "open"]), true);
+    assert_eq!(output, "{\"id\":4}\n");
+    let output = run_to_string(
+        &db,
+        parse(&["story", "set-status", "4", "in-progress"]),
+        true,
+    );
+    let result: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(result["blocked_by"], serde_json::json!([3]));
+
+    let mut out = Vec::new();
+    let result = run(
+        &db,
+        parse(&["story", "link", "4", "blocks", "3"]),
+        false,
+        &mut out,
+    );
+    assert_eq!(result.is_err(), true);
+    assert_eq!(
+        TestCli::try_parse_from(["cli_jira", "story", "link", "3", "follows", "4"]).is_err(),
+        true
+    );
+
+    let output = run_to_string(
+        &db,
+        parse(&["story", "unlink", "4", "blocked-by", "3"]),
+        false,
+    );
+    assert_eq!(output, "Unlinked story 4\n");
+    assert_eq!(db.links_of(4).unwrap().is_empty(), true);
+}
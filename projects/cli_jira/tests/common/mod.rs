@@ -1,5 +1,5 @@
 use anyhow::Result;
-use cli_jira::db::Database;
+use cli_jira::db::StateDatabase;
 use cli_jira::models::DBState;
 use std::{cell::RefCell, collections::HashMap};
 
@@ -15,18 +15,21 @@ impl MockDB {
                 last_item_id: 0,
                 epics: HashMap::new(),
                 stories: HashMap::new(),
+                history: vec![],
+                comments: vec![],
+                links: vec![],
             }),
         }
     }
 }
 
-impl Database for MockDB {
-    fn read_db(&self) -> Result<DBState> {
+impl StateDatabase for MockDB {
+    fn load_state(&self) -> Result<DBState> {
         let state = self.last_written_state.borrow().clone();
         Ok(state)
     }
 
-    fn write_db(&self, db_state: &DBState) -> Result<()> {
+    fn save_state(&self, db_state: &DBState) -> Result<()> {
         let latest_state = &self.last_written_state;
         *latest_state.borrow_mut() = db_state.clone();
         Ok(())
@@ -0,0 +1,257 @@
+// Asserts compare with `true`/`false` on purpose, as throughout the suite.
+#![allow(clippy::bool_assert_comparison)]
+
+use cli_jira::config::{
+    Backend, BoardConfig, BoardLocation, Config, DEFAULT_BOARD, LEGACY_DB_PATH,
+};
+use cli_jira::db::JiraDatabase;
+use cli_jira::models::{Epic, RecordStatus};
+use cli_jira::workflow::Workflow;
+
+use std::path::{Path, PathBuf};
+
+fn sample_config() -> Config {
+    toml::from_str(
+        r#"
+        default_board = "backend"
+
+        [boards.backend]
+        path = "/boards/backend.json"
+
+        [boards.mobile]
+        path = "/boards/mobile.sqlite"
+        backend = "sqlite"
+
+        [boards.web]
+        "#,
+    )
+    .unwrap()
+}
+
+#[test]
+fn load_should_return_empty_config_if_file_is_missing() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let config = Config::load(&dir.path().join("config.toml")).unwrap();
+
+    assert_eq!(config, Config::default());
+    assert_eq!(config.current_board(), DEFAULT_BOARD);
+}
+
+#[test]
+fn load_should_fail_with_invalid_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("config.toml");
+    std::fs::write(&config_path, "boards = 3").unwrap();
+
+    assert_eq!(Config::load(&config_path).is_err(), true);
+}
+
+#[test]
+fn save_should_round_trip() {
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("nested").join("config.toml");
+
+    let config = sample_config();
+    config.save(&config_path).unwrap();
+
+    assert_eq!(Config::load(&config_path).unwrap(), config);
+}
+
+#[test]
+fn resolve_should_use_default_board() {
+    let config = sample_config();
+
+    assert_eq!(
+        config.resolve(None, None, None).unwrap(),
+        BoardLocation {
+            name: "backend".to_owned(),
+            path: PathBuf::from("/boards/backend.json"),
+            backend: Backend::Json,
+            workflow: Workflow::default(),
+            author: None,
+        }
+    );
+}
+
+#[test]
+fn resolve_should_prefer_command_line_overrides() {
+    let config = sample_config();
+
+    let location = config.resolve(Some("mobile"), None, None).unwrap();
+    assert_eq!(location.path, PathBuf::from("/boards/mobile.sqlite"));
+    assert_eq!(location.backend, Backend::Sqlite);
+
+    let location = config
+        .resolve(Some("mobile"), Some(Path::new("/tmp/other.json")), None)
+        .unwrap();
+    assert_eq!(location.name, "mobile".to_owned());
+    assert_eq!(location.path, PathBuf::from("/tmp/other.json"));
+    assert_eq!(location.backend, Backend::Json);
+
+    let location = config
+        .resolve(None, Some(Path::new("/tmp/other.db")), None)
+        .unwrap();
+    assert_eq!(location.backend, Backend::Sqlite);
+
+    let location = config
+        .resolve(None, Some(Path::new("/tmp/other.db")), Some(Backend::Json))
+        .unwrap();
+    assert_eq!(location.backend, Backend::Json);
+}
+
+#[test]
+fn resolve_should_put_boards_without_path_in_data_dir() {
+    let config = sample_config();
+
+    let location = config.resolve(Some("web"), None, None).unwrap();
+    assert_eq!(location.path.ends_with("cli_jira/web.json"), true);
+
+    let location = Config::default()
+        .resolve(None, None, Some(Backend::Sqlite))
+        .unwrap();
+    assert_eq!(location.name, DEFAULT_BOARD.to_owned());
+    assert_eq!(location.path.ends_with("cli_jira/default.sqlite"), true);
+}
+
+#[test]
+fn resolve_should_keep_using_legacy_default_board() {
+    // tests run in the crate directory, which has the old `data/db.json`
+    assert_eq!(Path::new(LEGACY_DB_PATH).exists(), true);
+
+    let moved = dirs::data_dir()
+        .unwrap()
+        .join("cli_jira")
+        .join("default.json")
+        .exists();
+    let location = Config::default().resolve(None, None, None).unwrap();
+    assert_eq!(location.path == Path::new(LEGACY_DB_PATH), !moved);
+
+    let location = sample_config().resolve(Some("web"), None, None).unwrap();
+    assert_eq!(location.path.ends_with("cli_jira/web.json"), true);
+}
+
+#[test]
+fn resolve_should_fail_for_unknown_board() {
+    let config = sample_config();
+
+    assert_eq!(config.resolve(Some("unknown"), None, None).is_err(), true);
+}
+
+#[test]
+fn open_should_initialize_missing_database() {
+    let dir = tempfile::tempdir().unwrap();
+
+    for backend in [Backend::Json, Backend::Sqlite] {
+        let location = BoardLocation {
+            name: DEFAULT_BOARD.to_owned(),
+            path: dir
+                .path()
+                .join("first")
+                .join("run")
+                .join(format!("{:?}", backend)),
+            backend,
+            workflow: Workflow::default(),
+            author: None,
+        };
+
+        let db = JiraDatabase::open(&location).unwrap();
+        let db_state = db.read_db().unwrap();
+
+        assert_eq!(db_state.last_item_id, 0);
+        assert_eq!(db_state.epics.is_empty(), true);
+        assert_eq!(location.path.exists(), true);
+    }
+}
+
+#[test]
+fn author_of_the_config_should_sign_changes() {
+    let config: Config = toml::from_str(r#"author = "Carol""#).unwrap();
+    assert_eq!(config.author, Some("Carol".to_owned()));
+
+    let dir = tempfile::tempdir().unwrap();
+    let location = config
+        .resolve(None, Some(&dir.path().join("db.json")), None)
+        .unwrap();
+    assert_eq!(location.author, Some("Carol".to_owned()));
+
+    let mut db = JiraDatabase::open(&location).unwrap();
+    let epic_id = db
+        .create_epic(Epic::new("".to_owned(), "".to_owned()))
+        .unwrap();
+    assert_eq!(db.author(), "Carol".to_owned());
+    assert_eq!(
+        db.history_of(epic_id).unwrap()[0].author,
+        "Carol".to_owned()
+    );
+
+    // without one, the environment is used again
+    db.author = None;
+    assert_ne!(db.author(), "Carol".to_owned());
+}
+
+#[test]
+fn board_config_should_default_to_json() {
+    let config: Config = toml::from_str("[boards.x]").unwrap();
+
+    assert_eq!(config.boards["x"], BoardConfig::default());
+    assert_eq!(config.boards["x"].backend, Backend::Json);
+}
+
+#[test]
+fn resolve_should_pick_board_workflow_over_top_level_one() {
+    let config: Config = toml::from_str(
+        r#"
+        [workflow]
+        statuses = ["Open", "Review", "Closed"]
+
+        [boards.web]
+
+        [boards.mobile.workflow]
+        statuses = ["Todo", "Done"]
+
+        [boards.mobile.workflow.transitions]
+        Todo = ["Done"]
+        "#,
+    )
+    .unwrap();
+
+    let workflow = config.resolve(Some("web"), None, None).unwrap().workflow;
+    assert_eq!(
+        workflow.statuses,
+        vec![
+            RecordStatus::Open,
+            RecordStatus::Custom("Review".to_owned()),
+            RecordStatus::Closed
+        ]
+    );
+    assert_eq!(workflow.transitions.is_empty(), true);
+
+    let workflow = config.resolve(Some("mobile"), None, None).unwrap().workflow;
+    assert_eq!(
+        workflow.initial_status(),
+        RecordStatus::Custom("Todo".to_owned())
+    );
+
+    let workflow = Config::default()
+        .resolve(None, None, None)
+        .unwrap()
+        .workflow;
+    assert_eq!(workflow, Workflow::default());
+}
+
+#[test]
+fn resolve_should_fail_for_invalid_workflow() {
+    let config: Config = toml::from_str(
+        r#"
+        [workflow]
+        statuses = ["Open", "Closed"]
+
+        [workflow.transitions]
+        Open = ["Review"]
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(config.resolve(None, None, None).is_err(), true);
+}
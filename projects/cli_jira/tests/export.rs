@@ -0,0 +1,148 @@
+// Asserts compare with `true`/`false` on purpose, as throughout the suite.
+#![allow(clippy::bool_assert_comparison)]
+
+mod common;
+
+use cli_jira::csv;
+use cli_jira::db::JiraDatabase;
+use cli_jira::export::{to_csv, to_json, to_markdown, JsonExport, JSON_EXPORT_VERSION};
+use cli_jira::models::{Epic, Priority, RecordStatus, Story};
+use cli_jira::workflow::Workflow;
+use common::MockDB;
+
+// epic 1 "Backend" with stories 2 (resolved) and 3, epic 4 in the trash
+fn sample_db() -> JiraDatabase {
+    let db = JiraDatabase::with_database(Box::new(MockDB::new()), Workflow::default());
+
+    let epic_id = db
+        .create_epic(Epic {
+            labels: vec!["api".to_owned()],
+            ..Epic::new("Backend".to_owned(), "Server, \"v2\"".to_owned())
+        })
+        .unwrap();
+    let story_id = db
+        .create_story(
+            Story {
+                priority: Some(Priority::High),
+                assignee: Some("alice".to_owned()),
+                estimate: Some(3),
+                ..Story::new("Login *page*".to_owned(), "".to_owned())
+            },
+            epic_id,
+        )
+        .unwrap();
+    db.update_story_status(story_id, RecordStatus::Resolved)
+        .unwrap();
+    db.create_story(Story::new("Logout".to_owned(), "".to_owned()), epic_id)
+        .unwrap();
+
+    let epic_id = db
+        .create_epic(Epic::new("Old".to_owned(), "".to_owned()))
+        .unwrap();
+    db.delete_epic(epic_id).unwrap();
+
+    db
+}
+
+#[test]
+fn export_state_should_leave_out_the_trash() {
+    let db = sample_db();
+
+    let db_state = db.export_state(None).unwrap();
+    assert_eq!(db_state.epics.len(), 1);
+    assert_eq!(db_state.stories.len(), 2);
+    assert_eq!(
+        db_state.history.iter().all(|entry| entry.item_id != 4),
+        true
+    );
+
+    let db_state = db.export_state(Some(1)).unwrap();
+    assert_eq!(db_state.epics.len(), 1);
+
+    assert_eq!(db.export_state(Some(4)).is_err(), true);
+    assert_eq!(db.export_state(Some(999)).is_err(), true);
+}
+
+#[test]
+fn to_csv_should_write_a_row_per_item() {
+    let db = sample_db();
+    let output = to_csv(&db.export_state(None).unwrap());
+    let records = csv::parse(&output).unwrap();
+
+    assert_eq!(records.len(), 4);
+    assert_eq!(
+        records[0].fields.join(","),
+        "type,id,epic_id,name,description,status,priority,assignee,labels,estimate,created_at,updated_at"
+    );
+    assert_eq!(
+        records[1].fields[..6],
+        ["epic", "1", "", "Backend", "Server, \"v2\"", "Open"]
+    );
+    assert_eq!(
+        records[2].fields[..10],
+        [
+            "story",
+            "2",
+            "1",
+            "Login *page*",
+            "",
+            "Resolved",
+            "high",
+            "alice",
+            "",
+            "3"
+        ]
+    );
+    assert_eq!(records[3].fields[3], "Logout");
+    // quoted because of the comma and quotes
+    assert_eq!(output.contains("\"Server, \"\"v2\"\"\""), true);
+}
+
+#[test]
+fn to_markdown_should_tick_done_stories() {
+    let db = sample_db();
+    let output = to_markdown(&db.export_state(None).unwrap(), &db.workflow);
+
+    assert_eq!(output.contains("1 epics, 2 stories, 1 done"), true);
+    assert_eq!(output.contains("## [ ] 1. Backend"), true);
+    assert_eq!(
+        output.contains(
+            "- [x] 2. Login \\*page\\* (Resolved | priority: High | assignee: alice | points: 3)"
+        ),
+        true
+    );
+    assert_eq!(output.contains("- [ ] 3. Logout (Open)"), true);
+    assert_eq!(output.contains("Old"), false);
+}
+
+#[test]
+fn to_json_should_be_versioned() {
+    let db = sample_db();
+    let db_state = db.export_state(Some(1)).unwrap();
+
+    let export: JsonExport = serde_json::from_str(&to_json(&db_state).unwrap()).unwrap();
+
+    assert_eq!(export.version, JSON_EXPORT_VERSION);
+    assert_eq!(export.board, db_state);
+}
+
+#[test]
+fn csv_should_round_trip_fields() {
+    let fields = [
+        "plain",
+        "with, comma",
+        "with \"quotes\"",
+        "two\nlines",
+        " padded ",
+        "",
+    ];
+    let line = csv::write_record(&fields);
+
+    let records = csv::parse(&format!("{}\r\n\r\n{}\n", line, line)).unwrap();
+
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].fields, fields);
+    assert_eq!(records[1].line, 4);
+    assert_eq!(csv::parse("a,\"b").is_err(), true);
+    assert_eq!(csv::parse("a,b\"c").is_err(), true);
+}
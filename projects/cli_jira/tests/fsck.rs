@@ -0,0 +1,168 @@
+// Asserts compare with `true`/`false` on purpose, as throughout the suite.
+#![allow(clippy::bool_assert_comparison)]
+
+mod common;
+
+use cli_jira::db::{JiraDatabase, StateDatabase};
+use cli_jira::fsck::{check, repair, Problem, RECOVERED_EPIC_NAME};
+use cli_jira::models::{Comment, DBState, Epic, LinkKind, Story, StoryLink};
+use cli_jira::workflow::Workflow;
+use common::MockDB;
+use std::collections::HashMap;
+
+fn epic(stories: Vec<u32>) -> Epic {
+    Epic {
+        stories,
+        ..Epic::new("epic".to_owned(), "".to_owned())
+    }
+}
+
+fn story() -> Story {
+    Story::new("story".to_owned(), "".to_owned())
+}
+
+// epic 1 lists 3 and the missing 9, epic 2 lists 3 twice and 4, story 5 is in no
+// epic, story 2 clashes with epic 2 and ids go up to 5 while 4 was handed out last
+fn broken_state() -> DBState {
+    DBState {
+        last_item_id: 4,
+        epics: HashMap::from([(1, epic(vec![3, 9])), (2, epic(vec![3, 4, 3]))]),
+        stories: HashMap::from([(2, story()), (3, story()), (4, story()), (5, story())]),
+        history: vec![],
+        comments: vec![],
+        links: vec![],
+    }
+}
+
+#[test]
+fn check_should_find_nothing_on_a_sound_board() {
+    let db_state = DBState {
+        last_item_id: 3,
+        epics: HashMap::from([(1, epic(vec![2, 3]))]),
+        stories: HashMap::from([(2, story()), (3, story())]),
+        history: vec![],
+        comments: vec![],
+        links: vec![],
+    };
+
+    assert_eq!(check(&db_state), vec![]);
+    assert_eq!(repair(&db_state), db_state);
+}
+
+#[test]
+fn check_should_find_every_kind_of_problem() {
+    assert_eq!(
+        check(&broken_state()),
+        vec![
+            Problem::DanglingStory {
+                epic_id: 1,
+                story_id: 9
+            },
+            Problem::OrphanedStory { story_id: 2 },
+            Problem::OrphanedStory { story_id: 5 },
+            Problem::DuplicateStory {
+                story_id: 3,
+                epic_ids: vec![1, 2, 2]
+            },
+            Problem::DuplicateId { item_id: 2 },
+            Problem::IdAboveLastItemId {
+                item_id: 5,
+                last_item_id: 4
+            },
+        ]
+    );
+    assert_eq!(
+        Problem::DanglingStory {
+            epic_id: 1,
+            story_id: 9
+        }
+        .to_string(),
+        "epic 1 lists story 9, which doesn't exist"
+    );
+}
+
+#[test]
+fn repair_should_fix_every_problem() {
+    let repaired = repair(&broken_state());
+
+    assert_eq!(check(&repaired), vec![]);
+    // the story clashing with epic 2 became 6, and the orphans went into epic 7
+    assert_eq!(repaired.last_item_id, 7);
+    assert_eq!(repaired.epics[&1].stories, vec![3]);
+    assert_eq!(repaired.epics[&2].stories, vec![4]);
+    assert_eq!(repaired.stories.contains_key(&2), false);
+    assert_eq!(repaired.stories.contains_key(&6), true);
+    assert_eq!(repaired.epics[&7].name, RECOVERED_EPIC_NAME);
+    assert_eq!(repaired.epics[&7].stories, vec![5, 6]);
+}
+
+#[test]
+fn repair_should_move_comments_along_with_their_story() {
+    let mut db_state = broken_state();
+    db_state.comments = vec![
+        Comment {
+            story_id: 2,
+            timestamp: chrono::Utc::now(),
+            author: "alice".to_owned(),
+            text: "on the clashing story".to_owned(),
+        },
+        Comment {
+            story_id: 3,
+            timestamp: chrono::Utc::now(),
+            author: "bob".to_owned(),
+            text: "on another story".to_owned(),
+        },
+    ];
+
+    let repaired = repair(&db_state);
+
+    // story 2 became 6
+    assert_eq!(repaired.comments[0].story_id, 6);
+    assert_eq!(repaired.comments[1].story_id, 3);
+}
+
+#[test]
+fn dangling_links_should_be_found_and_dropped() {
+    let mut db_state = broken_state();
+    db_state.links = vec![
+        StoryLink::new(2, LinkKind::Blocks, 3),
+        StoryLink::new(3, LinkKind::RelatesTo, 9),
+    ];
+
+    let problems = check(&db_state);
+    let dangling = Problem::DanglingLink {
+        from: 3,
+        kind: LinkKind::RelatesTo,
+        to: 9,
+    };
+
+    assert_eq!(problems.len(), 7);
+    assert_eq!(problems.last(), Some(&dangling));
+    assert_eq!(
+        dangling.to_string(),
+        "link \"3 relates to 9\" refers to a story that doesn't exist"
+    );
+
+    let repaired = repair(&db_state);
+
+    assert_eq!(check(&repaired), vec![]);
+    // the link of story 2 follows it to its new id
+    assert_eq!(repaired.links, vec![StoryLink::new(6, LinkKind::Blocks, 3)]);
+}
+
+#[test]
+fn jira_database_should_check_and_repair() {
+    let mock = MockDB::new();
+    mock.save_state(&broken_state()).unwrap();
+
+    let db = JiraDatabase::with_database(Box::new(mock), Workflow::default());
+
+    assert_eq!(db.check().unwrap().len(), 6);
+    assert_eq!(db.repair().unwrap().len(), 6);
+    assert_eq!(db.check().unwrap(), vec![]);
+    assert_eq!(db.repair().unwrap(), vec![]);
+
+    // new ids don't clash with the recovered ones
+    let epic_id = db.create_epic(epic(vec![])).unwrap();
+    assert_eq!(epic_id, 8);
+}
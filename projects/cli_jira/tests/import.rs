@@ -0,0 +1,177 @@
+// Asserts compare with `true`/`false` on purpose, as throughout the suite.
+#![allow(clippy::bool_assert_comparison)]
+
+mod common;
+
+use cli_jira::db::JiraDatabase;
+use cli_jira::export::to_csv;
+use cli_jira::import::import_csv;
+use cli_jira::models::{Epic, Priority, RecordStatus};
+use cli_jira::workflow::Workflow;
+use common::MockDB;
+
+fn empty_db() -> JiraDatabase {
+    JiraDatabase::with_database(Box::new(MockDB::new()), Workflow::default())
+}
+
+const SAMPLE: &str = "\
+type,id,epic_id,name,status,priority,labels,estimate
+story,,10,Login page,in progress,high,\"auth, ui\",3
+epic,10,,Backend,,,api,
+story,,1,Existing epic's story,,,,
+";
+
+#[test]
+fn import_csv_should_only_list_items_on_a_dry_run() {
+    let db = empty_db();
+    db.create_epic(Epic::new("Existing".to_owned(), "".to_owned()))
+        .unwrap();
+
+    let summary = import_csv(&db, SAMPLE, true).unwrap();
+
+    assert_eq!(summary.dry_run, true);
+    assert_eq!(summary.epic_count(), 1);
+    assert_eq!(summary.story_count(), 2);
+    // epics first
+    assert_eq!(summary.items[0].name, "Backend");
+    assert_eq!(summary.items[1].epic, Some("Backend".to_owned()));
+    assert_eq!(summary.items[2].epic, Some("Existing".to_owned()));
+    assert_eq!(summary.items.iter().all(|item| item.id.is_none()), true);
+    assert_eq!(db.list_epics().unwrap().len(), 1);
+}
+
+#[test]
+fn import_csv_should_create_epics_and_stories() {
+    let db = empty_db();
+    db.create_epic(Epic::new("Existing".to_owned(), "".to_owned()))
+        .unwrap();
+
+    let summary = import_csv(&db, SAMPLE, false).unwrap();
+
+    assert_eq!(summary.dry_run, false);
+    assert_eq!(summary.items[0].id, Some(2));
+
+    let epic = db.get_epic(2).unwrap().unwrap();
+    assert_eq!(epic.name, "Backend");
+    assert_eq!(epic.labels, vec!["api".to_owned()]);
+
+    let stories = db.list_stories_of_epic(2).unwrap();
+    assert_eq!(stories.len(), 1);
+    let (story_id, story) = &stories[0];
+    assert_eq!(summary.items[1].id, Some(*story_id));
+    assert_eq!(story.status, RecordStatus::InProgress);
+    assert_eq!(story.priority, Some(Priority::High));
+    assert_eq!(story.labels, vec!["auth".to_owned(), "ui".to_owned()]);
+    assert_eq!(story.estimate, Some(3));
+
+    assert_eq!(db.list_stories_of_epic(1).unwrap().len(), 1);
+}
+
+#[test]
+fn import_csv_should_report_all_invalid_rows_and_create_nothing() {
+    let db = empty_db();
+    let input = "\
+type,id,epic_id,name,status,priority
+epic,1,,Backend,,
+story,,1,,,
+story,,1,Login,Blocked,
+story,,2,Logout,,
+story,,1,Signup,,urgent
+task,,,Something,,
+";
+
+    let error = import_csv(&db, input, false).unwrap_err().to_string();
+
+    assert_eq!(error.contains("line 3: name is required"), true);
+    assert_eq!(
+        error.contains("line 4: status 'Blocked' is not part of the workflow"),
+        true
+    );
+    assert_eq!(error.contains("line 5: unknown epic 2"), true);
+    assert_eq!(error.contains("line 6: invalid priority 'urgent'"), true);
+    assert_eq!(error.contains("line 7: unknown type 'task'"), true);
+    assert_eq!(db.list_epics().unwrap().is_empty(), true);
+
+    let error = import_csv(&db, "type,name,notes\n", false).unwrap_err();
+    assert_eq!(error.to_string().contains("unknown column 'notes'"), true);
+    assert_eq!(import_csv(&db, "id,name\n", false).is_err(), true);
+    assert_eq!(import_csv(&db, "", false).is_err(), true);
+}
+
+#[test]
+fn import_csv_should_apply_the_epic_status_rules() {
+    let db = empty_db();
+    let input = "\
+type,id,epic_id,name,status
+epic,1,,Backend,closed
+story,,1,Login,closed
+story,,1,Logout,open
+";
+
+    let error = import_csv(&db, input, false).unwrap_err().to_string();
+    assert_eq!(
+        error.contains("line 2: epic 1 is Closed but has 1 unfinished stories"),
+        true
+    );
+    assert_eq!(db.list_epics().unwrap().is_empty(), true);
+
+    let mut db = empty_db();
+    db.workflow = Workflow {
+        auto_resolve_epics: true,
+        ..Workflow::default()
+    };
+    let summary = import_csv(&db, &input.replace("Logout,open", "Logout,resolved"), false).unwrap();
+
+    let epic_id = summary.items[0].id.unwrap();
+    assert_eq!(
+        db.get_epic(epic_id).unwrap().unwrap().status,
+        RecordStatus::Closed
+    );
+
+    // epics left open are resolved once all their stories are done
+    let summary = import_csv(
+        &db,
+        &input
+            .replace("Backend,closed", "Backend,open")
+            .replace("Logout,open", "Logout,resolved"),
+        false,
+    )
+    .unwrap();
+
+    let epic_id = summary.items[0].id.unwrap();
+    assert_eq!(
+        db.get_epic(epic_id).unwrap().unwrap().status,
+        RecordStatus::Resolved
+    );
+}
+
+#[test]
+fn import_csv_should_read_its_own_exports() {
+    let db = empty_db();
+    import_csv(
+        &db,
+        SAMPLE.replace(",1,Existing", ",10,Existing").as_str(),
+        false,
+    )
+    .unwrap();
+
+    let copy = empty_db();
+    let summary = import_csv(&copy, &to_csv(&db.export_state(None).unwrap()), false).unwrap();
+
+    assert_eq!(summary.epic_count(), 1);
+    assert_eq!(summary.story_count(), 2);
+
+    let original = db.list_stories_of_epic(1).unwrap();
+    let copied = copy.list_stories_of_epic(1).unwrap();
+    assert_eq!(
+        original
+            .iter()
+            .map(|(_, story)| &story.name)
+            .collect::<Vec<_>>(),
+        copied
+            .iter()
+            .map(|(_, story)| &story.name)
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(original[0].1.status, copied[0].1.status);
+}
@@ -1,14 +1,17 @@
+// Asserts compare with `true`/`false` on purpose, as throughout the suite.
+#![allow(clippy::bool_assert_comparison)]
+
 mod common;
 
 use cli_jira::db::JiraDatabase;
-use cli_jira::models::{Epic, RecordStatus, Story};
+use cli_jira::models::{Change, Epic, LinkKind, Priority, RecordStatus, Story, StoryLink};
+use cli_jira::workflow::Workflow;
 use common::MockDB;
+use std::collections::BTreeMap;
 
 #[test]
 fn create_epic_should_work() {
-    let db = JiraDatabase {
-        database: Box::new(MockDB::new()),
-    };
+    let db = JiraDatabase::with_database(Box::new(MockDB::new()), Workflow::default());
     let epic = Epic::new("".to_owned(), "".to_owned());
 
     let result = db.create_epic(epic.clone());
@@ -22,14 +25,32 @@ fn create_epic_should_work() {
 
     assert_eq!(id, expected_id);
     assert_eq!(db_state.last_item_id, expected_id);
-    assert_eq!(db_state.epics.get(&id), Some(&epic));
+
+    let stored = db_state.epics.get(&id).unwrap();
+
+    assert_eq!(stored.created_at.is_some(), true);
+    assert_eq!(stored.updated_at, stored.created_at);
+    assert_eq!(
+        stored,
+        &Epic {
+            created_at: stored.created_at,
+            updated_at: stored.updated_at,
+            ..epic
+        }
+    );
+    assert_eq!(
+        db.history_of(id)
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.change)
+            .collect::<Vec<_>>(),
+        vec![Change::Created]
+    );
 }
 
 #[test]
 fn create_story_should_error_if_invalid_epic_id() {
-    let db = JiraDatabase {
-        database: Box::new(MockDB::new()),
-    };
+    let db = JiraDatabase::with_database(Box::new(MockDB::new()), Workflow::default());
     let story = Story::new("".to_owned(), "".to_owned());
 
     let non_existent_epic_id = 999;
@@ -40,9 +61,7 @@ fn create_story_should_error_if_invalid_epic_id() {
 
 #[test]
 fn create_story_should_work() {
-    let db = JiraDatabase {
-        database: Box::new(MockDB::new()),
-    };
+    let db = JiraDatabase::with_database(Box::new(MockDB::new()), Workflow::default());
     let epic = Epic::new("".to_owned(), "".to_owned());
     let story = Story::new("".to_owned(), "".to_owned());
 
@@ -65,14 +84,23 @@ fn create_story_should_work() {
         db_state.epics.get(&epic_id).unwrap().stories.contains(&id),
         true
     );
-    assert_eq!(db_state.stories.get(&id), Some(&story));
+
+    let stored = db_state.stories.get(&id).unwrap();
+
+    assert_eq!(stored.created_at.is_some(), true);
+    assert_eq!(
+        stored,
+        &Story {
+            created_at: stored.created_at,
+            updated_at: stored.updated_at,
+            ..story
+        }
+    );
 }
 
 #[test]
 fn delete_epic_should_error_if_invalid_epic_id() {
-    let db = JiraDatabase {
-        database: Box::new(MockDB::new()),
-    };
+    let db = JiraDatabase::with_database(Box::new(MockDB::new()), Workflow::default());
 
     let non_existent_epic_id = 999;
 
@@ -82,9 +110,7 @@ fn delete_epic_should_error_if_invalid_epic_id() {
 
 #[test]
 fn delete_epic_should_work() {
-    let db = JiraDatabase {
-        database: Box::new(MockDB::new()),
-    };
+    let db = JiraDatabase::with_database(Box::new(MockDB::new()), Workflow::default());
     let epic = Epic::new("".to_owned(), "".to_owned());
     let story = Story::new("".to_owned(), "".to_owned());
 
@@ -106,15 +132,17 @@ fn delete_epic_should_work() {
     let expected_last_id = 2;
 
     assert_eq!(db_state.last_item_id, expected_last_id);
-    assert_eq!(db_state.epics.get(&epic_id), None);
-    assert_eq!(db_state.stories.get(&story_id), None);
+    // kept in the trash until purged
+    assert_eq!(db_state.epics[&epic_id].deleted_at.is_some(), true);
+    assert_eq!(db_state.epics[&epic_id].stories, vec![story_id]);
+    assert_eq!(db.get_epic(epic_id).unwrap(), None);
+    assert_eq!(db.get_story(story_id).unwrap(), None);
+    assert_eq!(db.list_epics().unwrap().is_empty(), true);
 }
 
 #[test]
 fn delete_story_should_error_if_invalid_epic_id() {
-    let db = JiraDatabase {
-        database: Box::new(MockDB::new()),
-    };
+    let db = JiraDatabase::with_database(Box::new(MockDB::new()), Workflow::default());
     let epic = Epic::new("".to_owned(), "".to_owned());
     let story = Story::new("".to_owned(), "".to_owned());
 
@@ -136,9 +164,7 @@ fn delete_story_should_error_if_invalid_epic_id() {
 
 #[test]
 fn delete_story_should_error_if_story_not_found_in_epic() {
-    let db = JiraDatabase {
-        database: Box::new(MockDB::new()),
-    };
+    let db = JiraDatabase::with_database(Box::new(MockDB::new()), Workflow::default());
     let epic = Epic::new("".to_owned(), "".to_owned());
     let story = Story::new("".to_owned(), "".to_owned());
 
@@ -158,9 +184,7 @@ fn delete_story_should_error_if_story_not_found_in_epic() {
 
 #[test]
 fn delete_story_should_work() {
-    let db = JiraDatabase {
-        database: Box::new(MockDB::new()),
-    };
+    let db = JiraDatabase::with_database(Box::new(MockDB::new()), Workflow::default());
     let epic = Epic::new("".to_owned(), "".to_owned());
     let story = Story::new("".to_owned(), "".to_owned());
 
@@ -182,23 +206,22 @@ fn delete_story_should_work() {
     let expected_last_id = 2;
 
     assert_eq!(db_state.last_item_id, expected_last_id);
+    assert_eq!(db_state.stories[&story_id].deleted_at.is_some(), true);
     assert_eq!(
-        db_state
-            .epics
-            .get(&epic_id)
+        db.get_epic(epic_id)
+            .unwrap()
             .unwrap()
             .stories
             .contains(&story_id),
         false
     );
-    assert_eq!(db_state.stories.get(&story_id), None);
+    assert_eq!(db.get_story(story_id).unwrap(), None);
+    assert_eq!(db.list_stories_of_epic(epic_id).unwrap().is_empty(), true);
 }
 
 #[test]
 fn update_epic_status_should_error_if_invalid_epic_id() {
-    let db = JiraDatabase {
-        database: Box::new(MockDB::new()),
-    };
+    let db = JiraDatabase::with_database(Box::new(MockDB::new()), Workflow::default());
 
     let non_existent_epic_id = 999;
 
@@ -208,9 +231,7 @@ fn update_epic_status_should_error_if_invalid_epic_id() {
 
 #[test]
 fn update_epic_status_should_work() {
-    let db = JiraDatabase {
-        database: Box::new(MockDB::new()),
-    };
+    let db = JiraDatabase::with_database(Box::new(MockDB::new()), Workflow::default());
     let epic = Epic::new("".to_owned(), "".to_owned());
 
     let result = db.create_epic(epic);
@@ -233,9 +254,7 @@ fn update_epic_status_should_work() {
 
 #[test]
 fn update_story_status_should_error_if_invalid_story_id() {
-    let db = JiraDatabase {
-        database: Box::new(MockDB::new()),
-    };
+    let db = JiraDatabase::with_database(Box::new(MockDB::new()), Workflow::default());
 
     let non_existent_story_id = 999;
 
@@ -245,9 +264,7 @@ fn update_story_status_should_error_if_invalid_story_id() {
 
 #[test]
 fn update_story_status_should_work() {
-    let db = JiraDatabase {
-        database: Box::new(MockDB::new()),
-    };
+    let db = JiraDatabase::with_database(Box::new(MockDB::new()), Workflow::default());
     let epic = Epic::new("".to_owned(), "".to_owned());
     let story = Story::new("".to_owned(), "".to_owned());
 
@@ -270,3 +287,780 @@ fn update_story_status_should_work() {
         RecordStatus::Closed
     );
 }
+
+#[test]
+fn update_epic_should_error_if_invalid_epic_id() {
+    let db = JiraDatabase::with_database(Box::new(MockDB::new()), Workflow::default());
+
+    let non_existent_epic_id = 999;
+
+    let result = db.update_epic(non_existent_epic_id, "".to_owned(), "".to_owned());
+    assert_eq!(result.is_err(), true);
+}
+
+#[test]
+fn update_epic_should_work() {
+    let db = JiraDatabase::with_database(Box::new(MockDB::new()), Workflow::default());
+    let epic = Epic::new("".to_owned(), "".to_owned());
+
+    let epic_id = db.create_epic(epic).unwrap();
+
+    let result = db.update_epic(epic_id, "name".to_owned(), "description".to_owned());
+
+    assert_eq!(result.is_ok(), true);
+
+    let db_state = db.read_db().unwrap();
+    let epic = db_state.epics.get(&epic_id).unwrap();
+
+    assert_eq!(epic.name, "name".to_owned());
+    assert_eq!(epic.description, "description".to_owned());
+    assert_eq!(epic.status, RecordStatus::Open);
+}
+
+#[test]
+fn update_story_should_error_if_invalid_story_id() {
+    let db = JiraDatabase::with_database(Box::new(MockDB::new()), Workflow::default());
+
+    let non_existent_story_id = 999;
+
+    let result = db.update_story(non_existent_story_id, "".to_owned(), "".to_owned());
+    assert_eq!(result.is_err(), true);
+}
+
+#[test]
+fn update_story_should_work() {
+    let db = JiraDatabase::with_database(Box::new(MockDB::new()), Workflow::default());
+    let epic = Epic::new("".to_owned(), "".to_owned());
+    let story = Story::new("".to_owned(), "".to_owned());
+
+    let epic_id = db.create_epic(epic).unwrap();
+    let story_id = db.create_story(story, epic_id).unwrap();
+
+    let result = db.update_story(story_id, "name".to_owned(), "description".to_owned());
+
+    assert_eq!(result.is_ok(), true);
+
+    let db_state = db.read_db().unwrap();
+    let story = db_state.stories.get(&story_id).unwrap();
+
+    assert_eq!(story.name, "name".to_owned());
+    assert_eq!(story.description, "description".to_owned());
+    assert_eq!(db_state.epics[&epic_id].stories, vec![story_id]);
+}
+
+#[test]
+fn update_epic_labels_should_work() {
+    let db = JiraDatabase::with_database(Box::new(MockDB::new()), Workflow::default());
+    let epic_id = db
+        .create_epic(Epic::new("".to_owned(), "".to_owned()))
+        .unwrap();
+
+    let result = db.update_epic_labels(epic_id, vec!["backend".to_owned()]);
+
+    assert_eq!(result.is_ok(), true);
+    assert_eq!(
+        db.get_epic(epic_id).unwrap().unwrap().labels,
+        vec!["backend".to_owned()]
+    );
+    assert_eq!(db.update_epic_labels(999, vec![]).is_err(), true);
+}
+
+#[test]
+fn update_story_details_should_error_if_invalid_story_id() {
+    let db = JiraDatabase::with_database(Box::new(MockDB::new()), Workflow::default());
+
+    let result = db.update_story_details(999, None, None, vec![], None);
+    assert_eq!(result.is_err(), true);
+}
+
+#[test]
+fn update_story_details_should_work() {
+    let db = JiraDatabase::with_database(Box::new(MockDB::new()), Workflow::default());
+    let epic_id = db
+        .create_epic(Epic::new("".to_owned(), "".to_owned()))
+        .unwrap();
+    let story_id = db
+        .create_story(Story::new("name".to_owned(), "".to_owned()), epic_id)
+        .unwrap();
+
+    let result = db.update_story_details(
+        story_id,
+        Some(Priority::Critical),
+        Some("alice".to_owned()),
+        vec!["api".to_owned()],
+        Some(8),
+    );
+
+    assert_eq!(result.is_ok(), true);
+
+    let story = db.get_story(story_id).unwrap().unwrap();
+
+    assert_eq!(story.name, "name".to_owned());
+    assert_eq!(story.priority, Some(Priority::Critical));
+    assert_eq!(story.assignee, Some("alice".to_owned()));
+    assert_eq!(story.labels, vec!["api".to_owned()]);
+    assert_eq!(story.estimate, Some(8));
+}
+
+#[test]
+fn checklist_items_should_be_added_toggled_and_removed() {
+    let db = JiraDatabase::with_database(Box::new(MockDB::new()), Workflow::default());
+    let epic_id = db
+        .create_epic(Epic::new("".to_owned(), "".to_owned()))
+        .unwrap();
+    let story_id = db
+        .create_story(Story::new("name".to_owned(), "".to_owned()), epic_id)
+        .unwrap();
+
+    assert_eq!(
+        db.add_checklist_item(story_id, " write tests ".to_owned())
+            .unwrap(),
+        0
+    );
+    assert_eq!(
+        db.add_checklist_item(story_id, "review".to_owned())
+            .unwrap(),
+        1
+    );
+    assert_eq!(
+        db.add_checklist_item(story_id, "  ".to_owned()).is_err(),
+        true
+    );
+    assert_eq!(
+        db.add_checklist_item(999, "review".to_owned()).is_err(),
+        true
+    );
+
+    assert_eq!(db.toggle_checklist_item(story_id, 0).unwrap(), true);
+    assert_eq!(db.toggle_checklist_item(story_id, 1).unwrap(), true);
+    assert_eq!(db.toggle_checklist_item(story_id, 1).unwrap(), false);
+    assert_eq!(db.toggle_checklist_item(story_id, 2).is_err(), true);
+
+    let checklist = db.get_story(story_id).unwrap().unwrap().checklist;
+    assert_eq!(checklist.len(), 2);
+    assert_eq!(checklist[0].text, "write tests");
+    assert_eq!(checklist[0].done, true);
+    assert_eq!(checklist[1].done, false);
+
+    assert_eq!(db.remove_checklist_item(story_id, 2).is_err(), true);
+    db.remove_checklist_item(story_id, 0).unwrap();
+
+    let checklist = db.get_story(story_id).unwrap().unwrap().checklist;
+    assert_eq!(checklist.len(), 1);
+    assert_eq!(checklist[0].text, "review");
+
+    let changes: Vec<_> = db
+        .history_of(story_id)
+        .unwrap()
+        .into_iter()
+        .map(|entry| entry.change)
+        .collect();
+    assert_eq!(
+        changes[1..],
+        [
+            Change::ChecklistItemAdded {
+                text: "write tests".to_owned()
+            },
+            Change::ChecklistItemAdded {
+                text: "review".to_owned()
+            },
+            Change::ChecklistItemToggled {
+                text: "write tests".to_owned(),
+                done: true
+            },
+            Change::ChecklistItemToggled {
+                text: "review".to_owned(),
+                done: true
+            },
+            Change::ChecklistItemToggled {
+                text: "review".to_owned(),
+                done: false
+            },
+            Change::ChecklistItemRemoved {
+                text: "write tests".to_owned()
+            },
+        ]
+    );
+}
+
+#[test]
+fn comments_should_be_added_and_listed() {
+    let db = JiraDatabase::with_database(Box::new(MockDB::new()), Workflow::default());
+    let epic_id = db
+        .create_epic(Epic::new("".to_owned(), "".to_owned()))
+        .unwrap();
+    let story_id = db
+        .create_story(Story::new("name".to_owned(), "".to_owned()), epic_id)
+        .unwrap();
+    let other_story_id = db
+        .create_story(Story::new("other".to_owned(), "".to_owned()), epic_id)
+        .unwrap();
+
+    let comment = db.add_comment(story_id, " first \n".to_owned()).unwrap();
+    db.add_comment(other_story_id, "elsewhere".to_owned())
+        .unwrap();
+    db.add_comment(story_id, "second".to_owned()).unwrap();
+
+    assert_eq!(comment.story_id, story_id);
+    assert_eq!(comment.text, "first");
+    assert_eq!(comment.author, db.author());
+    assert_eq!(db.add_comment(story_id, " ".to_owned()).is_err(), true);
+    assert_eq!(db.add_comment(999, "text".to_owned()).is_err(), true);
+
+    let texts: Vec<_> = db
+        .comments_of(story_id)
+        .unwrap()
+        .into_iter()
+        .map(|comment| comment.text)
+        .collect();
+    assert_eq!(texts, vec!["first".to_owned(), "second".to_owned()]);
+
+    // comments don't show up in the history, and stay with a story in the trash
+    assert_eq!(db.history_of(story_id).unwrap().len(), 1);
+
+    db.delete_story(epic_id, story_id).unwrap();
+    assert_eq!(db.comments_of(story_id).unwrap().len(), 2);
+    assert_eq!(db.add_comment(story_id, "text".to_owned()).is_err(), true);
+
+    let exported = db.export_state(None).unwrap();
+    assert_eq!(exported.comments.len(), 1);
+    assert_eq!(exported.comments[0].story_id, other_story_id);
+}
+
+#[test]
+fn links_should_be_added_listed_and_removed() {
+    let db = JiraDatabase::with_database(Box::new(MockDB::new()), Workflow::default());
+    let epic_id = db
+        .create_epic(Epic::new("".to_owned(), "".to_owned()))
+        .unwrap();
+    let other_epic_id = db
+        .create_epic(Epic::new("".to_owned(), "".to_owned()))
+        .unwrap();
+    let blocker_id = db
+        .create_story(Story::new("blocker".to_owned(), "".to_owned()), epic_id)
+        .unwrap();
+    let story_id = db
+        .create_story(Story::new("story".to_owned(), "".to_owned()), other_epic_id)
+        .unwrap();
+
+    db.link_stories(story_id, LinkKind::BlockedBy, blocker_id)
+        .unwrap();
+    db.link_stories(blocker_id, LinkKind::RelatesTo, story_id)
+        .unwrap();
+
+    // stored one way round, whichever story the link was added from
+    assert_eq!(
+        db.read_db().unwrap().links,
+        vec![
+            StoryLink::new(blocker_id, LinkKind::Blocks, story_id),
+            StoryLink::new(story_id, LinkKind::RelatesTo, blocker_id),
+        ]
+    );
+
+    let links: Vec<_> = db
+        .links_of(story_id)
+        .unwrap()
+        .into_iter()
+        .map(|linked| (linked.kind, linked.story_id, linked.story.name))
+        .collect();
+    assert_eq!(
+        links,
+        vec![
+            (LinkKind::BlockedBy, blocker_id, "blocker".to_owned()),
+            (LinkKind::RelatesTo, blocker_id, "blocker".to_owned()),
+        ]
+    );
+    assert_eq!(db.links_of(blocker_id).unwrap()[0].kind, LinkKind::Blocks);
+
+    assert_eq!(
+        db.history_of(story_id).unwrap()[1].change,
+        Change::Linked {
+            kind: LinkKind::BlockedBy,
+            story_id: blocker_id
+        }
+    );
+    assert_eq!(
+        db.history_of(blocker_id).unwrap()[1].change,
+        Change::Linked {
+            kind: LinkKind::Blocks,
+            story_id
+        }
+    );
+
+    db.unlink_stories(story_id, LinkKind::RelatesTo, blocker_id)
+        .unwrap();
+
+    assert_eq!(db.links_of(story_id).unwrap().len(), 1);
+    assert_eq!(
+        db.unlink_stories(story_id, LinkKind::RelatesTo, blocker_id)
+            .is_err(),
+        true
+    );
+    assert_eq!(
+        db.history_of(blocker_id).unwrap().last().unwrap().change,
+        Change::Unlinked {
+            kind: LinkKind::RelatesTo,
+            story_id
+        }
+    );
+}
+
+#[test]
+fn link_stories_should_reject_invalid_links() {
+    let db = JiraDatabase::with_database(Box::new(MockDB::new()), Workflow::default());
+    let epic_id = db
+        .create_epic(Epic::new("".to_owned(), "".to_owned()))
+        .unwrap();
+    let [a, b, c] = [(); 3].map(|_| {
+        db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap()
+    });
+
+    db.link_stories(a, LinkKind::Blocks, b).unwrap();
+    db.link_stories(b, LinkKind::Blocks, c).unwrap();
+    db.link_stories(a, LinkKind::RelatesTo, b).unwrap();
+
+    assert_eq!(db.link_stories(a, LinkKind::Blocks, a).is_err(), true);
+    assert_eq!(db.link_stories(a, LinkKind::Blocks, 999).is_err(), true);
+    assert_eq!(db.link_stories(b, LinkKind::BlockedBy, a).is_err(), true);
+    assert_eq!(db.link_stories(b, LinkKind::RelatesTo, a).is_err(), true);
+    // a blocks b blocks c, so c can't block a
+    assert_eq!(db.link_stories(c, LinkKind::Blocks, a).is_err(), true);
+    assert_eq!(db.link_stories(a, LinkKind::BlockedBy, c).is_err(), true);
+    // cycles only count within a kind
+    assert_eq!(db.link_stories(c, LinkKind::Duplicates, a).is_ok(), true);
+    assert_eq!(db.link_stories(a, LinkKind::Duplicates, c).is_err(), true);
+
+    db.delete_story(epic_id, c).unwrap();
+    assert_eq!(db.link_stories(c, LinkKind::RelatesTo, a).is_err(), true);
+
+    assert_eq!(db.read_db().unwrap().links.len(), 4);
+}
+
+#[test]
+fn blockers_on_start_should_list_unfinished_blockers() {
+    let db = JiraDatabase::with_database(Box::new(MockDB::new()), Workflow::default());
+    let epic_id = db
+        .create_epic(Epic::new("".to_owned(), "".to_owned()))
+        .unwrap();
+    let [story_id, open_id, closed_id, trashed_id] = [(); 4].map(|_| {
+        db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap()
+    });
+
+    for blocker_id in [open_id, closed_id, trashed_id] {
+        db.link_stories(story_id, LinkKind::BlockedBy, blocker_id)
+            .unwrap();
+    }
+    db.update_story_status(closed_id, RecordStatus::Closed)
+        .unwrap();
+    db.delete_story(epic_id, trashed_id).unwrap();
+
+    let blocker_ids = |status: RecordStatus| -> Vec<u32> {
+        db.blockers_on_start(story_id, status)
+            .unwrap()
+            .into_iter()
+            .map(|(blocker_id, _)| blocker_id)
+            .collect()
+    };
+
+    assert_eq!(blocker_ids(RecordStatus::InProgress), vec![open_id]);
+    assert_eq!(blocker_ids(RecordStatus::Resolved), Vec::<u32>::new());
+    assert_eq!(
+        db.blockers_on_start(open_id, RecordStatus::InProgress)
+            .unwrap()
+            .is_empty(),
+        true
+    );
+
+    // only a warning: the status can still be set
+    db.update_story_status(story_id, RecordStatus::InProgress)
+        .unwrap();
+    assert_eq!(blocker_ids(RecordStatus::InProgress), Vec::<u32>::new());
+}
+
+#[test]
+fn links_should_be_deleted_with_their_stories() {
+    let db = JiraDatabase::with_database(Box::new(MockDB::new()), Workflow::default());
+    let epic_id = db
+        .create_epic(Epic::new("".to_owned(), "".to_owned()))
+        .unwrap();
+    let other_epic_id = db
+        .create_epic(Epic::new("".to_owned(), "".to_owned()))
+        .unwrap();
+    let [a, b] = [(); 2].map(|_| {
+        db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap()
+    });
+    let c = db
+        .create_story(Story::new("".to_owned(), "".to_owned()), other_epic_id)
+        .unwrap();
+
+    db.link_stories(a, LinkKind::Blocks, b).unwrap();
+    db.link_stories(b, LinkKind::Blocks, c).unwrap();
+    db.link_stories(a, LinkKind::RelatesTo, c).unwrap();
+
+    // hidden while in the trash, back on restore
+    db.delete_story(epic_id, b).unwrap();
+    assert_eq!(db.links_of(a).unwrap().len(), 1);
+    assert_eq!(db.export_state(None).unwrap().links.len(), 1);
+
+    db.restore_from_trash(b).unwrap();
+    assert_eq!(db.links_of(a).unwrap().len(), 2);
+
+    db.delete_story(epic_id, b).unwrap();
+    db.purge_from_trash(b).unwrap();
+    assert_eq!(
+        db.read_db().unwrap().links,
+        vec![StoryLink::new(a, LinkKind::RelatesTo, c)]
+    );
+
+    db.delete_epic(other_epic_id).unwrap();
+    db.purge_from_trash(other_epic_id).unwrap();
+    assert_eq!(db.read_db().unwrap().links, vec![]);
+}
+
+#[test]
+fn move_story_should_error_if_invalid_ids() {
+    let db = JiraDatabase::with_database(Box::new(MockDB::new()), Workflow::default());
+    let epic_id = db
+        .create_epic(Epic::new("".to_owned(), "".to_owned()))
+        .unwrap();
+    let other_epic_id = db
+        .create_epic(Epic::new("".to_owned(), "".to_owned()))
+        .unwrap();
+    let story_id = db
+        .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+        .unwrap();
+
+    let non_existent_id = 999;
+
+    assert_eq!(
+        db.move_story(non_existent_id, epic_id, other_epic_id)
+            .is_err(),
+        true
+    );
+    assert_eq!(
+        db.move_story(story_id, non_existent_id, other_epic_id)
+            .is_err(),
+        true
+    );
+    assert_eq!(
+        db.move_story(story_id, epic_id, non_existent_id).is_err(),
+        true
+    );
+    // story is not in the source epic
+    assert_eq!(
+        db.move_story(story_id, other_epic_id, epic_id).is_err(),
+        true
+    );
+    assert_eq!(db.move_story(story_id, epic_id, epic_id).is_err(), true);
+}
+
+#[test]
+fn move_story_should_work() {
+    let db = JiraDatabase::with_database(Box::new(MockDB::new()), Workflow::default());
+    let epic_id = db
+        .create_epic(Epic::new("".to_owned(), "".to_owned()))
+        .unwrap();
+    let other_epic_id = db
+        .create_epic(Epic::new("".to_owned(), "".to_owned()))
+        .unwrap();
+    let story = Story::new("story".to_owned(), "".to_owned());
+    let story_id = db.create_story(story.clone(), epic_id).unwrap();
+
+    let result = db.move_story(story_id, epic_id, other_epic_id);
+
+    assert_eq!(result.is_ok(), true);
+
+    let db_state = db.read_db().unwrap();
+
+    assert_eq!(db_state.epics[&epic_id].stories.is_empty(), true);
+    assert_eq!(db_state.epics[&other_epic_id].stories, vec![story_id]);
+    assert_eq!(db_state.stories[&story_id].name, story.name);
+    assert_eq!(
+        db.history_of(story_id).unwrap().last().unwrap().change,
+        Change::Moved {
+            from_epic_id: epic_id,
+            to_epic_id: other_epic_id
+        }
+    );
+}
+
+#[test]
+fn updates_should_record_history() {
+    let db = JiraDatabase::with_database(Box::new(MockDB::new()), Workflow::default());
+    let epic_id = db
+        .create_epic(Epic::new("".to_owned(), "".to_owned()))
+        .unwrap();
+    let story_id = db
+        .create_story(Story::new("name".to_owned(), "".to_owned()), epic_id)
+        .unwrap();
+    let created_at = db.get_story(story_id).unwrap().unwrap().created_at;
+
+    db.update_story_status(story_id, RecordStatus::InProgress)
+        .unwrap();
+    db.update_story(story_id, "renamed".to_owned(), "".to_owned())
+        .unwrap();
+    db.update_story_details(story_id, None, Some("alice".to_owned()), vec![], None)
+        .unwrap();
+
+    let history = db.history_of(story_id).unwrap();
+    let changes: Vec<_> = history.iter().map(|entry| entry.change.clone()).collect();
+
+    assert_eq!(
+        changes,
+        vec![
+            Change::Created,
+            Change::StatusChanged {
+                from: RecordStatus::Open,
+                to: RecordStatus::InProgress
+            },
+            Change::FieldChanged {
+                field: "name".to_owned(),
+                from: "name".to_owned(),
+                to: "renamed".to_owned()
+            },
+            Change::FieldChanged {
+                field: "assignee".to_owned(),
+                from: "".to_owned(),
+                to: "alice".to_owned()
+            },
+        ]
+    );
+    assert_eq!(
+        history
+            .windows(2)
+            .all(|pair| pair[0].timestamp <= pair[1].timestamp),
+        true
+    );
+
+    let story = db.get_story(story_id).unwrap().unwrap();
+    assert_eq!(story.created_at, created_at);
+    assert_eq!(story.updated_at >= created_at, true);
+    assert_eq!(db.history_of(epic_id).unwrap().len(), 1);
+}
+
+#[test]
+fn updates_without_changes_should_not_record_history() {
+    let db = JiraDatabase::with_database(Box::new(MockDB::new()), Workflow::default());
+    let epic_id = db
+        .create_epic(Epic::new("name".to_owned(), "".to_owned()))
+        .unwrap();
+    let updated_at = db.get_epic(epic_id).unwrap().unwrap().updated_at;
+
+    db.update_epic_status(epic_id, RecordStatus::Open).unwrap();
+    db.update_epic(epic_id, "name".to_owned(), "".to_owned())
+        .unwrap();
+
+    assert_eq!(db.history_of(epic_id).unwrap().len(), 1);
+    assert_eq!(
+        db.get_epic(epic_id).unwrap().unwrap().updated_at,
+        updated_at
+    );
+}
+
+#[test]
+fn update_status_should_follow_workflow() {
+    let review = RecordStatus::Custom("Review".to_owned());
+    let db = JiraDatabase::with_database(
+        Box::new(MockDB::new()),
+        Workflow {
+            statuses: vec![review.clone(), RecordStatus::Closed],
+            transitions: BTreeMap::from([(review.clone(), vec![RecordStatus::Closed])]),
+            ..Default::default()
+        },
+    );
+    let epic_id = db
+        .create_epic(Epic::new("".to_owned(), "".to_owned()))
+        .unwrap();
+    let story_id = db
+        .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+        .unwrap();
+
+    // Open is not part of this workflow, so new items start in its first status
+    assert_eq!(db.get_story(story_id).unwrap().unwrap().status, review);
+
+    assert_eq!(
+        db.update_story_status(story_id, RecordStatus::Open)
+            .is_err(),
+        true
+    );
+    assert_eq!(
+        db.update_story_status(story_id, RecordStatus::Closed)
+            .is_ok(),
+        true
+    );
+    assert_eq!(
+        db.update_story_status(story_id, RecordStatus::Custom("review".to_owned()))
+            .is_err(),
+        true
+    );
+    assert_eq!(
+        db.get_story(story_id).unwrap().unwrap().status,
+        RecordStatus::Closed
+    );
+}
+
+#[test]
+fn update_epic_status_should_refuse_finishing_epic_with_unfinished_stories() {
+    let db = JiraDatabase::with_database(Box::new(MockDB::new()), Workflow::default());
+    let epic_id = db
+        .create_epic(Epic::new("".to_owned(), "".to_owned()))
+        .unwrap();
+    let story_id = db
+        .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+        .unwrap();
+    db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+        .unwrap();
+
+    db.update_story_status(story_id, RecordStatus::Closed)
+        .unwrap();
+
+    assert_eq!(db.epic_progress(epic_id).unwrap(), (1, 2));
+    assert_eq!(
+        db.update_epic_status(epic_id, RecordStatus::Closed)
+            .is_err(),
+        true
+    );
+    assert_eq!(
+        db.next_epic_statuses(epic_id).unwrap(),
+        vec![RecordStatus::InProgress]
+    );
+    assert_eq!(
+        db.update_epic_status(epic_id, RecordStatus::InProgress)
+            .is_ok(),
+        true
+    );
+
+    let mut db = db;
+    db.workflow = Workflow {
+        allow_unfinished_epics: true,
+        ..Workflow::default()
+    };
+
+    assert_eq!(
+        db.update_epic_status(epic_id, RecordStatus::Closed).is_ok(),
+        true
+    );
+}
+
+#[test]
+fn epics_should_auto_resolve_when_enabled() {
+    let db = JiraDatabase::with_database(
+        Box::new(MockDB::new()),
+        Workflow {
+            auto_resolve_epics: true,
+            ..Workflow::default()
+        },
+    );
+    let epic_id = db
+        .create_epic(Epic::new("".to_owned(), "".to_owned()))
+        .unwrap();
+    let first_id = db
+        .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+        .unwrap();
+    let second_id = db
+        .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+        .unwrap();
+
+    db.update_story_status(first_id, RecordStatus::Resolved)
+        .unwrap();
+    assert_eq!(
+        db.get_epic(epic_id).unwrap().unwrap().status,
+        RecordStatus::Open
+    );
+
+    db.update_story_status(second_id, RecordStatus::Closed)
+        .unwrap();
+    assert_eq!(
+        db.get_epic(epic_id).unwrap().unwrap().status,
+        RecordStatus::Resolved
+    );
+    assert_eq!(
+        db.history_of(epic_id).unwrap().last().unwrap().change,
+        Change::StatusChanged {
+            from: RecordStatus::Open,
+            to: RecordStatus::Resolved
+        }
+    );
+}
+
+#[test]
+fn trash_should_restore_and_purge_items() {
+    let db = JiraDatabase::with_database(Box::new(MockDB::new()), Workflow::default());
+    let epic_id = db
+        .create_epic(Epic::new("Login".to_owned(), "".to_owned()))
+        .unwrap();
+    let story_id = db
+        .create_story(Story::new("Login page".to_owned(), "".to_owned()), epic_id)
+        .unwrap();
+    let other_story_id = db
+        .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+        .unwrap();
+    db.update_story_status(other_story_id, RecordStatus::Closed)
+        .unwrap();
+
+    db.delete_story(epic_id, story_id).unwrap();
+
+    assert_eq!(db.epic_progress(epic_id).unwrap(), (1, 1));
+    assert_eq!(db.search("page").unwrap().is_empty(), true);
+    assert_eq!(db.list_trash().unwrap().len(), 1);
+    assert_eq!(
+        db.update_story(story_id, "".to_owned(), "".to_owned())
+            .is_err(),
+        true
+    );
+
+    db.delete_epic(epic_id).unwrap();
+
+    // a story can't come back while its epic is in the trash
+    assert_eq!(db.restore_from_trash(story_id).is_err(), true);
+    assert_eq!(db.restore_from_trash(other_story_id).is_err(), true);
+
+    db.restore_from_trash(epic_id).unwrap();
+    db.restore_from_trash(story_id).unwrap();
+
+    assert_eq!(db.list_trash().unwrap().is_empty(), true);
+    assert_eq!(db.list_stories_of_epic(epic_id).unwrap().len(), 2);
+    assert_eq!(
+        db.history_of(story_id).unwrap().last().unwrap().change,
+        Change::Restored
+    );
+
+    db.delete_story(epic_id, story_id).unwrap();
+    assert_eq!(db.restore_from_trash(other_story_id).is_err(), true);
+
+    db.purge_from_trash(story_id).unwrap();
+
+    let db_state = db.read_db().unwrap();
+    assert_eq!(db_state.stories.contains_key(&story_id), false);
+    assert_eq!(db_state.epics[&epic_id].stories, vec![other_story_id]);
+}
+
+#[test]
+fn purge_trash_should_only_purge_items_deleted_before_cutoff() {
+    let db = JiraDatabase::with_database(Box::new(MockDB::new()), Workflow::default());
+    let epic_id = db
+        .create_epic(Epic::new("".to_owned(), "".to_owned()))
+        .unwrap();
+    let story_id = db
+        .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+        .unwrap();
+
+    db.delete_epic(epic_id).unwrap();
+
+    let cutoff = db.read_db().unwrap().epics[&epic_id].deleted_at.unwrap();
+
+    assert_eq!(
+        db.purge_trash(cutoff - chrono::Duration::days(1))
+            .unwrap()
+            .is_empty(),
+        true
+    );
+    assert_eq!(db.purge_trash(cutoff).unwrap(), vec![epic_id]);
+
+    let db_state = db.read_db().unwrap();
+    assert_eq!(db_state.epics.is_empty(), true);
+    assert_eq!(db_state.stories.contains_key(&story_id), false);
+}
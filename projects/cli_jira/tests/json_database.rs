@@ -1,16 +1,20 @@
+// Asserts compare with `true`/`false` on purpose, as throughout the suite.
+#![allow(clippy::bool_assert_comparison)]
+
 mod common;
 
-use cli_jira::db::{Database, JSONFileDatabase};
-use cli_jira::models::{DBState, Epic, RecordStatus, Story};
+use cli_jira::db::{Database, JSONFileDatabase, JiraDatabase, StateDatabase, BACKUP_COUNT};
+use cli_jira::models::{Change, Comment, DBState, Epic, HistoryEntry, RecordStatus, Story};
 
 use std::collections::HashMap;
 use std::io::Write;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
 
 #[test]
 fn read_db_should_fail_with_invalid_path() {
-    let db = JSONFileDatabase {
-        file_path: "INVALID_PATH".to_owned(),
-    };
+    let db = JSONFileDatabase::new("INVALID_PATH".to_owned());
     assert_eq!(db.read_db().is_err(), true);
 }
 
@@ -21,13 +25,13 @@ fn read_db_should_fail_with_invalid_json() {
     let file_contents = r#"{ "last_item_id": 0 epics: {} stories {} }"#;
     write!(tmpfile, "{}", file_contents).unwrap();
 
-    let db = JSONFileDatabase {
-        file_path: tmpfile
+    let db = JSONFileDatabase::new(
+        tmpfile
             .path()
             .to_str()
             .expect("failed to convert tmpfile path to str")
             .to_string(),
-    };
+    );
 
     let result = db.read_db();
 
@@ -41,13 +45,13 @@ fn read_db_should_parse_json_file() {
     let file_contents = r#"{ "last_item_id": 0, "epics": {}, "stories": {} }"#;
     write!(tmpfile, "{}", file_contents).unwrap();
 
-    let db = JSONFileDatabase {
-        file_path: tmpfile
+    let db = JSONFileDatabase::new(
+        tmpfile
             .path()
             .to_str()
             .expect("failed to convert tmpfile path to str")
             .to_string(),
-    };
+    );
 
     let result = db.read_db();
 
@@ -61,24 +65,26 @@ fn write_db_should_work() {
     let file_contents = r#"{ "last_item_id": 0, "epics": {}, "stories": {} }"#;
     write!(tmpfile, "{}", file_contents).unwrap();
 
-    let db = JSONFileDatabase {
-        file_path: tmpfile
+    let db = JSONFileDatabase::new(
+        tmpfile
             .path()
             .to_str()
             .expect("failed to convert tmpfile path to str")
             .to_string(),
-    };
+    );
 
     let story = Story {
         name: "epic 1".to_owned(),
         description: "epic 1".to_owned(),
         status: RecordStatus::Open,
+        ..Default::default()
     };
     let epic = Epic {
         name: "epic 1".to_owned(),
         description: "epic 1".to_owned(),
         status: RecordStatus::Open,
         stories: vec![2],
+        ..Default::default()
     };
 
     let mut stories = HashMap::new();
@@ -91,11 +97,324 @@ fn write_db_should_work() {
         last_item_id: 2,
         epics,
         stories,
+        history: vec![HistoryEntry {
+            item_id: 2,
+            timestamp: chrono::Utc::now(),
+            author: "alice".to_owned(),
+            change: Change::Created,
+        }],
+        comments: vec![Comment {
+            story_id: 2,
+            timestamp: chrono::Utc::now(),
+            author: "bob".to_owned(),
+            text: "looks good".to_owned(),
+        }],
+        links: vec![],
     };
 
-    let write_result = db.write_db(&state);
+    let write_result = db.save_state(&state);
     let read_result = db.read_db().unwrap();
 
     assert_eq!(write_result.is_ok(), true);
     assert_eq!(read_result, state);
 }
+
+#[test]
+fn write_db_should_not_leave_temp_files_behind() {
+    let dir = tempfile::tempdir().unwrap();
+    let file_path = dir.path().join("db.json");
+
+    let db = JSONFileDatabase::new(file_path.to_str().unwrap().to_string());
+
+    let state = DBState {
+        last_item_id: 0,
+        epics: HashMap::new(),
+        stories: HashMap::new(),
+        history: vec![],
+        comments: vec![],
+        links: vec![],
+    };
+
+    assert_eq!(db.save_state(&state).is_ok(), true);
+
+    let entries: Vec<_> = std::fs::read_dir(dir.path()).unwrap().collect();
+
+    assert_eq!(entries.len(), 1);
+    assert_eq!(db.read_db().unwrap(), state);
+}
+
+#[test]
+fn write_db_should_keep_rolling_backups() {
+    let dir = tempfile::tempdir().unwrap();
+    let file_path = dir.path().join("db.json");
+
+    let db = JSONFileDatabase::new(file_path.to_str().unwrap().to_string());
+
+    let mut state = DBState {
+        last_item_id: 0,
+        epics: HashMap::new(),
+        stories: HashMap::new(),
+        history: vec![],
+        comments: vec![],
+        links: vec![],
+    };
+
+    for last_item_id in 0..(BACKUP_COUNT as u32 + 3) {
+        state.last_item_id = last_item_id;
+        db.save_state(&state).unwrap();
+    }
+
+    for index in 1..=BACKUP_COUNT {
+        let backup = JSONFileDatabase::new(db.backup_path(index));
+        let expected_id = BACKUP_COUNT as u32 + 2 - index as u32;
+
+        assert_eq!(backup.read_db().unwrap().last_item_id, expected_id);
+    }
+
+    assert_eq!(
+        std::path::Path::new(&db.backup_path(BACKUP_COUNT + 1)).exists(),
+        false
+    );
+}
+
+#[test]
+fn each_operation_should_write_the_file_once() {
+    let dir = tempfile::tempdir().unwrap();
+    let file_path = dir.path().join("db.json").to_str().unwrap().to_string();
+
+    let db = JSONFileDatabase::new(file_path.clone());
+    db.create_if_missing().unwrap();
+
+    let jira = JiraDatabase::new(file_path);
+    let epic_id = jira
+        .create_epic(Epic::new("".to_owned(), "".to_owned()))
+        .unwrap();
+    let story_id = jira
+        .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+        .unwrap();
+
+    let result = db.read_db().unwrap();
+    assert_eq!(result.stories.contains_key(&story_id), true);
+    assert_eq!(
+        jira.history_of(story_id).unwrap()[0].change,
+        Change::Created
+    );
+
+    // the backup before the story is the board with just the epic
+    let backup = JSONFileDatabase::new(db.backup_path(1)).read_db().unwrap();
+    assert_eq!(backup.epics.contains_key(&epic_id), true);
+    assert_eq!(backup.stories.is_empty(), true);
+    assert_eq!(
+        JSONFileDatabase::new(db.backup_path(2))
+            .read_db()
+            .unwrap()
+            .epics
+            .is_empty(),
+        true
+    );
+
+    // a failed operation writes nothing
+    assert_eq!(
+        jira.create_story(Story::new("".to_owned(), "".to_owned()), 999)
+            .is_err(),
+        true
+    );
+    assert_eq!(db.read_db().unwrap(), result);
+    assert_eq!(
+        JSONFileDatabase::new(db.backup_path(1)).read_db().unwrap(),
+        backup
+    );
+}
+
+#[test]
+fn read_db_should_recover_from_last_good_backup() {
+    let dir = tempfile::tempdir().unwrap();
+    let file_path = dir.path().join("db.json");
+
+    let db = JSONFileDatabase::new(file_path.to_str().unwrap().to_string());
+
+    let mut state = DBState {
+        last_item_id: 1,
+        epics: HashMap::new(),
+        stories: HashMap::new(),
+        history: vec![],
+        comments: vec![],
+        links: vec![],
+    };
+    db.save_state(&state).unwrap();
+
+    state.last_item_id = 2;
+    db.save_state(&state).unwrap();
+
+    // simulate a crash that left a truncated file behind
+    std::fs::write(&file_path, r#"{ "last_item_id": 3, "ep"#).unwrap();
+
+    let result = db.read_db().unwrap();
+
+    assert_eq!(result.last_item_id, 1);
+
+    let restored = std::fs::read_to_string(&file_path).unwrap();
+    assert_eq!(serde_json::from_str::<DBState>(&restored).unwrap(), result);
+
+    let corrupt = std::fs::read_to_string(db.corrupt_path()).unwrap();
+    assert_eq!(corrupt, r#"{ "last_item_id": 3, "ep"#);
+}
+
+#[test]
+fn read_db_should_not_recover_when_file_cannot_be_opened() {
+    let dir = tempfile::tempdir().unwrap();
+    let file_path = dir.path().join("db.json");
+
+    let db = JSONFileDatabase::new(file_path.to_str().unwrap().to_string());
+
+    let state = DBState {
+        last_item_id: 1,
+        epics: HashMap::new(),
+        stories: HashMap::new(),
+        history: vec![],
+        comments: vec![],
+        links: vec![],
+    };
+    db.save_state(&state).unwrap();
+    db.save_state(&state).unwrap();
+
+    // reading a directory fails with an I/O error, not because of its content
+    std::fs::remove_file(&file_path).unwrap();
+    std::fs::create_dir(&file_path).unwrap();
+
+    assert_eq!(db.read_db().is_err(), true);
+    assert_eq!(file_path.is_dir(), true);
+    assert_eq!(std::path::Path::new(&db.corrupt_path()).exists(), false);
+}
+
+fn empty_db_file(dir: &tempfile::TempDir) -> String {
+    let file_path = dir.path().join("db.json").to_str().unwrap().to_string();
+    std::fs::write(
+        &file_path,
+        r#"{ "last_item_id": 0, "epics": {}, "stories": {} }"#,
+    )
+    .unwrap();
+    file_path
+}
+
+#[test]
+fn lock_db_should_fail_while_lock_is_held() {
+    let dir = tempfile::tempdir().unwrap();
+    let file_path = empty_db_file(&dir);
+
+    let db = JSONFileDatabase::new(file_path.clone());
+    let other_db = JSONFileDatabase::new(file_path);
+
+    let lock = db.lock_db().unwrap();
+
+    let result = other_db.lock_with_timeout(Duration::from_millis(50));
+    assert_eq!(result.is_err(), true);
+
+    drop(lock);
+
+    let result = other_db.lock_with_timeout(Duration::from_millis(50));
+    assert_eq!(result.is_ok(), true);
+}
+
+#[test]
+fn concurrent_threads_should_not_lose_writes() {
+    let dir = tempfile::tempdir().unwrap();
+    let file_path = empty_db_file(&dir);
+
+    let threads = 8;
+    let epics_per_thread = 10;
+
+    let handles: Vec<_> = (0..threads)
+        .map(|_| {
+            let file_path = file_path.clone();
+
+            thread::spawn(move || {
+                let db = JiraDatabase::new(file_path);
+
+                for _ in 0..epics_per_thread {
+                    db.create_epic(Epic::new("".to_owned(), "".to_owned()))
+                        .unwrap();
+                }
+            })
+        })
+        .collect();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    let db_state = JiraDatabase::new(file_path).read_db().unwrap();
+
+    assert_eq!(db_state.last_item_id, threads * epics_per_thread);
+    assert_eq!(db_state.epics.len(), (threads * epics_per_thread) as usize);
+}
+
+const CHILD_DB_ENV: &str = "CLI_JIRA_TEST_CHILD_DB";
+
+// Runs as a separate process spawned by `concurrent_processes_should_not_lose_writes`.
+#[test]
+fn child_process_writer() {
+    let Ok(file_path) = std::env::var(CHILD_DB_ENV) else {
+        return;
+    };
+
+    let db = JiraDatabase::new(file_path);
+
+    for _ in 0..10 {
+        db.create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+    }
+}
+
+#[test]
+fn concurrent_processes_should_not_lose_writes() {
+    let dir = tempfile::tempdir().unwrap();
+    let file_path = empty_db_file(&dir);
+
+    let processes = 4;
+
+    let children: Vec<_> = (0..processes)
+        .map(|_| {
+            Command::new(std::env::current_exe().unwrap())
+                .args(["--exact", "child_process_writer", "--test-threads=1"])
+                .env(CHILD_DB_ENV, &file_path)
+                .stdout(Stdio::null())
+                .spawn()
+                .unwrap()
+        })
+        .collect();
+
+    for mut child in children {
+        assert_eq!(child.wait().unwrap().success(), true);
+    }
+
+    let db_state = JiraDatabase::new(file_path).read_db().unwrap();
+
+    assert_eq!(db_state.last_item_id, processes * 10);
+    assert_eq!(db_state.epics.len(), (processes * 10) as usize);
+}
+
+#[test]
+fn read_db_should_default_missing_optional_fields() {
+    let mut tmpfile = tempfile::NamedTempFile::new().unwrap();
+
+    let file_contents = r#"{
+        "last_item_id": 2,
+        "epics": { "1": { "name": "e", "description": "", "status": "Open", "stories": [2] } },
+        "stories": { "2": { "name": "s", "description": "", "status": "Closed" } }
+    }"#;
+    write!(tmpfile, "{}", file_contents).unwrap();
+
+    let db = JSONFileDatabase::new(tmpfile.path().to_str().unwrap().to_string());
+
+    let db_state = db.read_db().unwrap();
+    let story = &db_state.stories[&2];
+
+    assert_eq!(db_state.epics[&1].labels.is_empty(), true);
+    assert_eq!(story.priority, None);
+    assert_eq!(story.assignee, None);
+    assert_eq!(story.labels.is_empty(), true);
+    assert_eq!(story.estimate, None);
+    assert_eq!(story.created_at, None);
+    assert_eq!(db_state.history.is_empty(), true);
+}
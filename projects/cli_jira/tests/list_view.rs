@@ -0,0 +1,120 @@
+// Asserts compare with `true`/`false` on purpose, as throughout the suite.
+#![allow(clippy::bool_assert_comparison)]
+
+use chrono::{TimeZone, Utc};
+use cli_jira::models::{Epic, RecordStatus, Story};
+use cli_jira::ui::{ListView, SortKey};
+use cli_jira::workflow::Workflow;
+
+fn story(name: &str, status: RecordStatus, day: u32) -> Story {
+    Story {
+        status,
+        created_at: Some(Utc.with_ymd_and_hms(2024, 1, day, 0, 0, 0).unwrap()),
+        ..Story::new(name.to_owned(), "".to_owned())
+    }
+}
+
+fn sample_stories() -> Vec<(u32, Story)> {
+    vec![
+        (1, story("charlie", RecordStatus::Closed, 3)),
+        (
+            2,
+            Story {
+                assignee: Some("Alice".to_owned()),
+                labels: vec!["api".to_owned()],
+                ..story("alpha", RecordStatus::InProgress, 2)
+            },
+        ),
+        (
+            3,
+            Story {
+                labels: vec!["API".to_owned(), "ui".to_owned()],
+                ..story("Bravo", RecordStatus::Open, 1)
+            },
+        ),
+    ]
+}
+
+fn ids<T>(items: Vec<(u32, T)>) -> Vec<u32> {
+    items.into_iter().map(|(id, _)| id).collect()
+}
+
+#[test]
+fn default_view_should_list_everything_by_id() {
+    let items = ListView::default().apply(sample_stories(), &Workflow::default());
+
+    assert_eq!(ids(items), vec![1, 2, 3]);
+}
+
+#[test]
+fn apply_should_sort_by_the_chosen_key() {
+    let workflow = Workflow::default();
+    let view = |sort| ListView {
+        sort,
+        ..Default::default()
+    };
+
+    assert_eq!(
+        ids(view(SortKey::Name).apply(sample_stories(), &workflow)),
+        vec![2, 3, 1]
+    );
+    assert_eq!(
+        ids(view(SortKey::Status).apply(sample_stories(), &workflow)),
+        vec![3, 2, 1]
+    );
+    assert_eq!(
+        ids(view(SortKey::Created).apply(sample_stories(), &workflow)),
+        vec![3, 2, 1]
+    );
+}
+
+#[test]
+fn apply_should_filter_by_status_label_and_assignee() {
+    let workflow = Workflow::default();
+
+    let hide_closed = ListView {
+        hidden_statuses: vec![RecordStatus::Closed],
+        ..Default::default()
+    };
+    let api_label = ListView {
+        label: Some("api".to_owned()),
+        ..Default::default()
+    };
+    let alice = ListView {
+        assignee: Some("alice".to_owned()),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        ids(hide_closed.apply(sample_stories(), &workflow)),
+        vec![2, 3]
+    );
+    assert_eq!(
+        ids(api_label.apply(sample_stories(), &workflow)),
+        vec![2, 3]
+    );
+    assert_eq!(ids(alice.apply(sample_stories(), &workflow)), vec![2]);
+
+    // epics have no assignee, so nothing matches
+    let epics = vec![(1, Epic::new("".to_owned(), "".to_owned()))];
+    assert_eq!(alice.apply(epics, &workflow).is_empty(), true);
+}
+
+#[test]
+fn describe_should_summarize_the_active_view() {
+    assert_eq!(ListView::default().describe(), "sorted by id");
+    assert_eq!(ListView::default().is_filtered(), false);
+
+    let view = ListView {
+        hidden_statuses: vec![RecordStatus::Resolved, RecordStatus::Closed],
+        label: Some("api".to_owned()),
+        assignee: None,
+        sort: SortKey::Created,
+    };
+
+    assert_eq!(view.is_filtered(), true);
+    assert_eq!(
+        view.describe(),
+        "hiding Resolved, Closed | label: api | sorted by creation date"
+    );
+}
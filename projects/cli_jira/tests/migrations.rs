@@ -0,0 +1,245 @@
+// Asserts compare with `true`/`false` on purpose, as throughout the suite.
+#![allow(clippy::bool_assert_comparison)]
+
+use cli_jira::config::{Backend, BoardLocation};
+use cli_jira::db::{JSONFileDatabase, JiraDatabase, StateDatabase, SCHEMA_VERSION};
+use cli_jira::models::{Change, Priority, RecordStatus};
+use cli_jira::workflow::Workflow;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn fixture_path(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/json")
+        .join(name)
+}
+
+// Copies a file of `tests/fixtures/json` into a fresh directory and opens it as a board
+fn open_fixture(name: &str) -> (tempfile::TempDir, PathBuf, JiraDatabase) {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("db.json");
+
+    fs::copy(fixture_path(name), &path).unwrap();
+
+    let db = JiraDatabase::open(&BoardLocation {
+        name: "default".to_owned(),
+        path: path.clone(),
+        backend: Backend::Json,
+        workflow: Workflow::default(),
+        author: None,
+    })
+    .unwrap();
+
+    (dir, path, db)
+}
+
+fn file_version(path: &Path) -> u64 {
+    let value: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+    value["version"].as_u64().unwrap()
+}
+
+fn json_database(path: &Path) -> JSONFileDatabase {
+    JSONFileDatabase::new(path.to_str().unwrap().to_owned())
+}
+
+// Checks the upgraded file and the copy of the original kept next to it
+fn assert_upgraded(name: &str, path: &Path) {
+    let original = fs::read_to_string(fixture_path(name)).unwrap();
+    let version = serde_json::from_str::<serde_json::Value>(&original).unwrap()["version"]
+        .as_u64()
+        .unwrap_or(0);
+    let db = json_database(path);
+
+    assert_eq!(file_version(path), SCHEMA_VERSION as u64);
+    assert_eq!(
+        fs::read_to_string(db.upgrade_backup_path(version as u32)).unwrap(),
+        original
+    );
+}
+
+#[test]
+fn baseline_file_should_be_upgraded() {
+    let (_dir, path, db) = open_fixture("v0_baseline.json");
+
+    assert_upgraded("v0_baseline.json", &path);
+
+    let db_state = db.read_db().unwrap();
+    assert_eq!(db_state.last_item_id, 3);
+    assert_eq!(db_state.epics[&1].status, RecordStatus::InProgress);
+    assert_eq!(db_state.epics[&1].stories, vec![2, 3]);
+    assert_eq!(db_state.stories[&2].status, RecordStatus::Closed);
+    assert_eq!(db_state.stories[&2].priority, None);
+    assert_eq!(db_state.stories[&2].created_at, None);
+    assert_eq!(db_state.history.is_empty(), true);
+}
+
+#[test]
+fn file_with_story_details_should_be_upgraded() {
+    let (_dir, path, db) = open_fixture("v0_story_details.json");
+
+    assert_upgraded("v0_story_details.json", &path);
+
+    let story = db.get_story(2).unwrap().unwrap();
+    assert_eq!(story.priority, Some(Priority::High));
+    assert_eq!(story.assignee, Some("alice".to_owned()));
+    assert_eq!(story.labels, vec!["auth".to_owned()]);
+    assert_eq!(story.estimate, Some(3));
+    assert_eq!(
+        db.get_epic(1).unwrap().unwrap().labels,
+        vec!["api".to_owned()]
+    );
+}
+
+#[test]
+fn file_with_history_should_be_upgraded() {
+    let (_dir, path, db) = open_fixture("v0_history.json");
+
+    assert_upgraded("v0_history.json", &path);
+
+    let history = db.history_of(2).unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0].change, Change::Created);
+    assert_eq!(
+        history[1].change,
+        Change::StatusChanged {
+            from: RecordStatus::Open,
+            to: RecordStatus::Closed
+        }
+    );
+    assert_eq!(
+        db.get_story(2)
+            .unwrap()
+            .unwrap()
+            .created_at
+            .unwrap()
+            .to_rfc3339(),
+        "2024-01-02T11:00:00+00:00"
+    );
+}
+
+#[test]
+fn file_with_trash_should_be_upgraded() {
+    let (_dir, path, db) = open_fixture("v0_trash.json");
+
+    assert_upgraded("v0_trash.json", &path);
+
+    assert_eq!(
+        db.get_epic(1).unwrap().unwrap().status,
+        RecordStatus::Custom("Review".to_owned())
+    );
+    assert_eq!(db.get_story(4).unwrap(), None);
+    assert_eq!(db.list_trash().unwrap().len(), 1);
+}
+
+#[test]
+fn v1_file_should_be_upgraded() {
+    let (_dir, path, db) = open_fixture("v1.json");
+
+    assert_upgraded("v1.json", &path);
+
+    let db_state = db.read_db().unwrap();
+    assert_eq!(db_state.stories.len(), 2);
+    assert_eq!(db_state.stories[&2].checklist.is_empty(), true);
+}
+
+#[test]
+fn v2_file_should_be_upgraded() {
+    let (_dir, path, db) = open_fixture("v2.json");
+
+    assert_upgraded("v2.json", &path);
+
+    let story = db.get_story(3).unwrap().unwrap();
+    assert_eq!(story.checklist.len(), 1);
+    assert_eq!(story.checklist[0].done, true);
+    assert_eq!(
+        db.history_of(3).unwrap()[0].change,
+        Change::ChecklistItemAdded {
+            text: "Clear session".to_owned()
+        }
+    );
+    assert_eq!(db.comments_of(3).unwrap().is_empty(), true);
+}
+
+#[test]
+fn v3_file_should_be_upgraded() {
+    let (_dir, path, db) = open_fixture("v3.json");
+
+    assert_upgraded("v3.json", &path);
+
+    let comments = db.comments_of(3).unwrap();
+    assert_eq!(comments.len(), 1);
+    assert_eq!(comments[0].author, "bob");
+    assert_eq!(db.links_of(3).unwrap().is_empty(), true);
+}
+
+#[test]
+fn current_file_should_be_left_alone() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("db.json");
+    let json_db = json_database(&path);
+
+    // written the way this build writes boards
+    let db_state = JSONFileDatabase::read_file(fixture_path("v3.json").to_str().unwrap()).unwrap();
+    json_db.save_state(&db_state).unwrap();
+    assert_eq!(file_version(&path), SCHEMA_VERSION as u64);
+
+    let current = fs::read_to_string(&path).unwrap();
+
+    let db = JiraDatabase::open(&BoardLocation {
+        name: "default".to_owned(),
+        path: path.clone(),
+        backend: Backend::Json,
+        workflow: Workflow::default(),
+        author: None,
+    })
+    .unwrap();
+
+    assert_eq!(fs::read_to_string(&path).unwrap(), current);
+    assert_eq!(
+        Path::new(&json_db.upgrade_backup_path(SCHEMA_VERSION)).exists(),
+        false
+    );
+    assert_eq!(db.read_db().unwrap().stories.len(), 2);
+}
+
+#[test]
+fn reads_should_upgrade_older_files_in_memory() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("db.json");
+    fs::write(
+        &path,
+        r#"{ "last_item_id": 0, "epics": {}, "stories": {} }"#,
+    )
+    .unwrap();
+
+    let db = json_database(&path);
+
+    assert_eq!(db.load_state().unwrap().last_item_id, 0);
+    assert_eq!(Path::new(&db.upgrade_backup_path(0)).exists(), false);
+
+    // the next write stores the current version
+    db.save_state(&db.load_state().unwrap()).unwrap();
+    assert_eq!(file_version(&path), SCHEMA_VERSION as u64);
+}
+
+#[test]
+fn file_of_a_newer_version_should_not_be_read_or_overwritten() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("db.json");
+    let db = json_database(&path);
+
+    db.save_state(&Default::default()).unwrap();
+
+    let newer = format!(
+        r#"{{ "version": {}, "last_item_id": 0, "epics": {{}}, "stories": {{}} }}"#,
+        SCHEMA_VERSION + 1
+    );
+    fs::write(&path, &newer).unwrap();
+
+    let error = db.load_state().unwrap_err();
+    assert_eq!(error.to_string().contains("newer version"), true);
+    db.upgrade().unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), newer);
+}
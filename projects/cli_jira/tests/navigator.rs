@@ -1,24 +1,32 @@
+// Asserts compare with `true`/`false` on purpose, as throughout the suite.
+#![allow(clippy::bool_assert_comparison)]
+
 mod common;
 
 use std::rc::Rc;
 
 use cli_jira::{
     db::JiraDatabase,
-    models::{Action, Epic, RecordStatus, Story},
+    models::{Action, Epic, LinkKind, ListKind, Priority, RecordStatus, Story},
     navigator::Navigator,
-    ui::{EpicDetail, HomePage, Prompts, StoryDetail},
+    ui::{
+        BoardPage, EpicDetail, HomePage, ListView, Prompts, SearchPage, SortKey, StoryDetail,
+        TrashPage,
+    },
+    workflow::Workflow,
 };
 
 use common::MockDB;
 
 #[test]
 fn should_start_on_home_page() {
-    let db = Rc::new(JiraDatabase {
-        database: Box::new(MockDB::new()),
-    });
+    let db = Rc::new(JiraDatabase::with_database(
+        Box::new(MockDB::new()),
+        Workflow::default(),
+    ));
     let nav = Navigator::new(db);
 
-    assert_eq!(nav._get_page_count(), 1);
+    assert_eq!(nav.page_count(), 1);
 
     let current_page = nav.get_current_page().unwrap();
     let home_page = current_page.as_any().downcast_ref::<HomePage>();
@@ -28,15 +36,16 @@ fn should_start_on_home_page() {
 
 #[test]
 fn handle_action_should_navigate_pages() {
-    let db = Rc::new(JiraDatabase {
-        database: Box::new(MockDB::new()),
-    });
+    let db = Rc::new(JiraDatabase::with_database(
+        Box::new(MockDB::new()),
+        Workflow::default(),
+    ));
 
     let mut nav = Navigator::new(db);
 
     nav.handle_action(Action::NavigateToEpicDetail { epic_id: 1 })
         .unwrap();
-    assert_eq!(nav._get_page_count(), 2);
+    assert_eq!(nav.page_count(), 2);
 
     let current_page = nav.get_current_page().unwrap();
     let epic_detail_page = current_page.as_any().downcast_ref::<EpicDetail>();
@@ -47,38 +56,39 @@ fn handle_action_should_navigate_pages() {
         story_id: 2,
     })
     .unwrap();
-    assert_eq!(nav._get_page_count(), 3);
+    assert_eq!(nav.page_count(), 3);
 
     let current_page = nav.get_current_page().unwrap();
     let story_detail_page = current_page.as_any().downcast_ref::<StoryDetail>();
     assert_eq!(story_detail_page.is_some(), true);
 
     nav.handle_action(Action::NavigateToPreviousPage).unwrap();
-    assert_eq!(nav._get_page_count(), 2);
+    assert_eq!(nav.page_count(), 2);
 
     let current_page = nav.get_current_page().unwrap();
     let epic_detail_page = current_page.as_any().downcast_ref::<EpicDetail>();
     assert_eq!(epic_detail_page.is_some(), true);
 
     nav.handle_action(Action::NavigateToPreviousPage).unwrap();
-    assert_eq!(nav._get_page_count(), 1);
+    assert_eq!(nav.page_count(), 1);
 
     let current_page = nav.get_current_page().unwrap();
     let home_page = current_page.as_any().downcast_ref::<HomePage>();
     assert_eq!(home_page.is_some(), true);
 
     nav.handle_action(Action::NavigateToPreviousPage).unwrap();
-    assert_eq!(nav._get_page_count(), 0);
+    assert_eq!(nav.page_count(), 0);
 
     nav.handle_action(Action::NavigateToPreviousPage).unwrap();
-    assert_eq!(nav._get_page_count(), 0);
+    assert_eq!(nav.page_count(), 0);
 }
 
 #[test]
 fn handle_action_should_clear_pages_on_exit() {
-    let db = Rc::new(JiraDatabase {
-        database: Box::new(MockDB::new()),
-    });
+    let db = Rc::new(JiraDatabase::with_database(
+        Box::new(MockDB::new()),
+        Workflow::default(),
+    ));
 
     let mut nav = Navigator::new(db);
 
@@ -91,19 +101,20 @@ fn handle_action_should_clear_pages_on_exit() {
     .unwrap();
     nav.handle_action(Action::Exit).unwrap();
 
-    assert_eq!(nav._get_page_count(), 0);
+    assert_eq!(nav.page_count(), 0);
 }
 
 #[test]
 fn handle_action_should_handle_create_epic() {
-    let db = Rc::new(JiraDatabase {
-        database: Box::new(MockDB::new()),
-    });
+    let db = Rc::new(JiraDatabase::with_database(
+        Box::new(MockDB::new()),
+        Workflow::default(),
+    ));
 
     let mut nav = Navigator::new(Rc::clone(&db));
 
     let mut prompts = Prompts::new();
-    prompts.create_epic = Box::new(|| Epic::new("name".to_owned(), "description".to_owned()));
+    prompts.create_epic = Box::new(|| Some(Epic::new("name".to_owned(), "description".to_owned())));
 
     nav._set_prompts(prompts);
 
@@ -119,9 +130,10 @@ fn handle_action_should_handle_create_epic() {
 
 #[test]
 fn handle_action_should_handle_update_epic() {
-    let db = Rc::new(JiraDatabase {
-        database: Box::new(MockDB::new()),
-    });
+    let db = Rc::new(JiraDatabase::with_database(
+        Box::new(MockDB::new()),
+        Workflow::default(),
+    ));
     let epic_id = db
         .create_epic(Epic::new("".to_owned(), "".to_owned()))
         .unwrap();
@@ -129,7 +141,10 @@ fn handle_action_should_handle_update_epic() {
     let mut nav = Navigator::new(Rc::clone(&db));
 
     let mut prompts = Prompts::new();
-    prompts.update_status = Box::new(|| Some(RecordStatus::InProgress));
+    prompts.update_status = Box::new(|statuses| {
+        assert_eq!(statuses.contains(&RecordStatus::InProgress), true);
+        Some(RecordStatus::InProgress)
+    });
 
     nav._set_prompts(prompts);
 
@@ -145,9 +160,10 @@ fn handle_action_should_handle_update_epic() {
 
 #[test]
 fn handle_action_should_handle_delete_epic() {
-    let db = Rc::new(JiraDatabase {
-        database: Box::new(MockDB::new()),
-    });
+    let db = Rc::new(JiraDatabase::with_database(
+        Box::new(MockDB::new()),
+        Workflow::default(),
+    ));
     let epic_id = db
         .create_epic(Epic::new("".to_owned(), "".to_owned()))
         .unwrap();
@@ -161,15 +177,15 @@ fn handle_action_should_handle_delete_epic() {
 
     nav.handle_action(Action::DeleteEpic { epic_id }).unwrap();
 
-    let db_state = db.read_db().unwrap();
-    assert_eq!(db_state.epics.len(), 0);
+    assert_eq!(db.list_epics().unwrap().len(), 0);
 }
 
 #[test]
 fn handle_action_should_handle_create_story() {
-    let db = Rc::new(JiraDatabase {
-        database: Box::new(MockDB::new()),
-    });
+    let db = Rc::new(JiraDatabase::with_database(
+        Box::new(MockDB::new()),
+        Workflow::default(),
+    ));
     let epic_id = db
         .create_epic(Epic::new("".to_owned(), "".to_owned()))
         .unwrap();
@@ -177,7 +193,8 @@ fn handle_action_should_handle_create_story() {
     let mut nav = Navigator::new(Rc::clone(&db));
 
     let mut prompts = Prompts::new();
-    prompts.create_story = Box::new(|| Story::new("name".to_owned(), "description".to_owned()));
+    prompts.create_story =
+        Box::new(|| Some(Story::new("name".to_owned(), "description".to_owned())));
 
     nav._set_prompts(prompts);
 
@@ -193,9 +210,10 @@ fn handle_action_should_handle_create_story() {
 
 #[test]
 fn handle_action_should_handle_update_story() {
-    let db = Rc::new(JiraDatabase {
-        database: Box::new(MockDB::new()),
-    });
+    let db = Rc::new(JiraDatabase::with_database(
+        Box::new(MockDB::new()),
+        Workflow::default(),
+    ));
     let epic_id = db
         .create_epic(Epic::new("".to_owned(), "".to_owned()))
         .unwrap();
@@ -206,7 +224,10 @@ fn handle_action_should_handle_update_story() {
     let mut nav = Navigator::new(Rc::clone(&db));
 
     let mut prompts = Prompts::new();
-    prompts.update_status = Box::new(|| Some(RecordStatus::InProgress));
+    prompts.update_status = Box::new(|statuses| {
+        assert_eq!(statuses.contains(&RecordStatus::InProgress), true);
+        Some(RecordStatus::InProgress)
+    });
 
     nav._set_prompts(prompts);
 
@@ -222,9 +243,10 @@ fn handle_action_should_handle_update_story() {
 
 #[test]
 fn handle_action_should_handle_delete_story() {
-    let db = Rc::new(JiraDatabase {
-        database: Box::new(MockDB::new()),
-    });
+    let db = Rc::new(JiraDatabase::with_database(
+        Box::new(MockDB::new()),
+        Workflow::default(),
+    ));
     let epic_id = db
         .create_epic(Epic::new("".to_owned(), "".to_owned()))
         .unwrap();
@@ -242,6 +264,664 @@ fn handle_action_should_handle_delete_story() {
     nav.handle_action(Action::DeleteStory { epic_id, story_id })
         .unwrap();
 
+    assert_eq!(db.list_stories_of_epic(epic_id).unwrap().len(), 0);
+}
+
+#[test]
+fn handle_action_should_handle_edit_epic() {
+    let db = Rc::new(JiraDatabase::with_database(
+        Box::new(MockDB::new()),
+        Workflow::default(),
+    ));
+    let epic_id = db
+        .create_epic(Epic::new("name".to_owned(), "description".to_owned()))
+        .unwrap();
+
+    let mut nav = Navigator::new(Rc::clone(&db));
+
+    let mut prompts = Prompts::new();
+    prompts.edit_epic = Box::new(|epic| Epic {
+        name: format!("{} edited", epic.name),
+        ..epic.clone()
+    });
+
+    nav._set_prompts(prompts);
+
+    nav.handle_action(Action::EditEpic { epic_id }).unwrap();
+
+    let db_state = db.read_db().unwrap();
+    let epic = db_state.epics.get(&epic_id).unwrap();
+    assert_eq!(epic.name, "name edited".to_owned());
+    assert_eq!(epic.description, "description".to_owned());
+}
+
+#[test]
+fn handle_action_should_handle_edit_story() {
+    let db = Rc::new(JiraDatabase::with_database(
+        Box::new(MockDB::new()),
+        Workflow::default(),
+    ));
+    let epic_id = db
+        .create_epic(Epic::new("".to_owned(), "".to_owned()))
+        .unwrap();
+    let story_id = db
+        .create_story(
+            Story::new("name".to_owned(), "description".to_owned()),
+            epic_id,
+        )
+        .unwrap();
+
+    let mut nav = Navigator::new(Rc::clone(&db));
+
+    let mut prompts = Prompts::new();
+    prompts.edit_story = Box::new(|story| Story {
+        description: format!("{} edited", story.description),
+        ..story.clone()
+    });
+
+    nav._set_prompts(prompts);
+
+    nav.handle_action(Action::EditStory { story_id }).unwrap();
+
+    let db_state = db.read_db().unwrap();
+    let story = db_state.stories.get(&story_id).unwrap();
+    assert_eq!(story.name, "name".to_owned());
+    assert_eq!(story.description, "description edited".to_owned());
+}
+
+#[test]
+fn handle_action_should_handle_edit_epic_labels() {
+    let db = Rc::new(JiraDatabase::with_database(
+        Box::new(MockDB::new()),
+        Workflow::default(),
+    ));
+    let epic_id = db
+        .create_epic(Epic::new("".to_owned(), "".to_owned()))
+        .unwrap();
+
+    let mut nav = Navigator::new(Rc::clone(&db));
+
+    let mut prompts = Prompts::new();
+    prompts.edit_labels = Box::new(|_| vec!["backend".to_owned()]);
+
+    nav._set_prompts(prompts);
+
+    nav.handle_action(Action::EditEpicLabels { epic_id })
+        .unwrap();
+
+    let epic = db.get_epic(epic_id).unwrap().unwrap();
+    assert_eq!(epic.labels, vec!["backend".to_owned()]);
+}
+
+#[test]
+fn handle_action_should_handle_edit_story_details() {
+    let db = Rc::new(JiraDatabase::with_database(
+        Box::new(MockDB::new()),
+        Workflow::default(),
+    ));
+    let epic_id = db
+        .create_epic(Epic::new("".to_owned(), "".to_owned()))
+        .unwrap();
+    let story_id = db
+        .create_story(Story::new("name".to_owned(), "".to_owned()), epic_id)
+        .unwrap();
+
+    let mut nav = Navigator::new(Rc::clone(&db));
+
+    let mut prompts = Prompts::new();
+    prompts.edit_story_details = Box::new(|story| Story {
+        priority: Some(Priority::Low),
+        estimate: Some(2),
+        ..story.clone()
+    });
+
+    nav._set_prompts(prompts);
+
+    nav.handle_action(Action::EditStoryDetails { story_id })
+        .unwrap();
+
+    let story = db.get_story(story_id).unwrap().unwrap();
+    assert_eq!(story.name, "name".to_owned());
+    assert_eq!(story.priority, Some(Priority::Low));
+    assert_eq!(story.estimate, Some(2));
+}
+
+#[test]
+fn handle_action_should_handle_checklist() {
+    let db = Rc::new(JiraDatabase::with_database(
+        Box::new(MockDB::new()),
+        Workflow::default(),
+    ));
+    let epic_id = db
+        .create_epic(Epic::new("".to_owned(), "".to_owned()))
+        .unwrap();
+    let story_id = db
+        .create_story(Story::new("name".to_owned(), "".to_owned()), epic_id)
+        .unwrap();
+
+    let mut nav = Navigator::new(Rc::clone(&db));
+
+    let mut prompts = Prompts::new();
+    prompts.add_checklist_item = Box::new(|| "write tests".to_owned());
+    nav._set_prompts(prompts);
+
+    nav.handle_action(Action::AddChecklistItem { story_id })
+        .unwrap();
+    nav.handle_action(Action::ToggleChecklistItem { story_id, index: 0 })
+        .unwrap();
+
+    let checklist = db.get_story(story_id).unwrap().unwrap().checklist;
+    assert_eq!(checklist.len(), 1);
+    assert_eq!(checklist[0].text, "write tests");
+    assert_eq!(checklist[0].done, true);
+
+    // checklist changes are undone like any other change
+    nav.handle_action(Action::RemoveChecklistItem { story_id, index: 0 })
+        .unwrap();
+    assert_eq!(
+        db.get_story(story_id)
+            .unwrap()
+            .unwrap()
+            .checklist
+            .is_empty(),
+        true
+    );
+
+    nav.handle_action(Action::Undo).unwrap();
+    assert_eq!(
+        db.get_story(story_id).unwrap().unwrap().checklist,
+        checklist
+    );
+
+    // an empty item cancels
+    let mut prompts = Prompts::new();
+    prompts.add_checklist_item = Box::new(|| "  ".to_owned());
+    nav._set_prompts(prompts);
+
+    nav.handle_action(Action::AddChecklistItem { story_id })
+        .unwrap();
+    assert_eq!(db.get_story(story_id).unwrap().unwrap().checklist.len(), 1);
+}
+
+#[test]
+fn handle_action_should_handle_comments() {
+    let db = Rc::new(JiraDatabase::with_database(
+        Box::new(MockDB::new()),
+        Workflow::default(),
+    ));
+    let epic_id = db
+        .create_epic(Epic::new("".to_owned(), "".to_owned()))
+        .unwrap();
+    let story_id = db
+        .create_story(Story::new("name".to_owned(), "".to_owned()), epic_id)
+        .unwrap();
+
+    let mut nav = Navigator::new(Rc::clone(&db));
+
+    let mut prompts = Prompts::new();
+    prompts.add_comment = Box::new(|| "looks good".to_owned());
+    nav._set_prompts(prompts);
+
+    nav.handle_action(Action::NavigateToStoryDetail { epic_id, story_id })
+        .unwrap();
+
+    let comment_page = |nav: &Navigator| {
+        nav.get_current_page()
+            .unwrap()
+            .as_any()
+            .downcast_ref::<StoryDetail>()
+            .unwrap()
+            .comment_page
+            .get()
+    };
+
+    nav.handle_action(Action::ShowComments { page: 1 }).unwrap();
+    assert_eq!(comment_page(&nav), 1);
+
+    // the new comment is shown, and can be undone
+    nav.handle_action(Action::AddComment { story_id }).unwrap();
+    assert_eq!(comment_page(&nav), 0);
+    assert_eq!(db.comments_of(story_id).unwrap()[0].text, "looks good");
+    assert_eq!(nav.can_undo(), true);
+
+    nav.handle_action(Action::Undo).unwrap();
+    assert_eq!(db.comments_of(story_id).unwrap().is_empty(), true);
+}
+
+#[test]
+fn handle_action_should_handle_links() {
+    let db = Rc::new(JiraDatabase::with_database(
+        Box::new(MockDB::new()),
+        Workflow::default(),
+    ));
+    let epic_id = db
+        .create_epic(Epic::new("".to_owned(), "".to_owned()))
+        .unwrap();
+    let story_id = db
+        .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+        .unwrap();
+    let other_story_id = db
+        .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+        .unwrap();
+
+    let mut nav = Navigator::new(Rc::clone(&db));
+
+    let shown = Rc::new(std::cell::RefCell::new(vec![]));
+    let target = Rc::new(std::cell::Cell::new(other_story_id));
+    let mut prompts = Prompts::new();
+    prompts.link_story = {
+        let target = Rc::clone(&target);
+        Box::new(move || Some((LinkKind::Duplicates, target.get())))
+    };
+    prompts.unlink_story = Box::new(|links| {
+        assert_eq!(links.len(), 1);
+        Some(0)
+    });
+    prompts.message = {
+        let shown = Rc::clone(&shown);
+        Box::new(move |text| shown.borrow_mut().push(text.to_owned()))
+    };
+    nav._set_prompts(prompts);
+
+    nav.handle_action(Action::LinkStory { story_id }).unwrap();
+
+    let links = db.links_of(other_story_id).unwrap();
+    assert_eq!(links.len(), 1);
+    assert_eq!(links[0].kind, LinkKind::DuplicatedBy);
+    assert_eq!(links[0].story_id, story_id);
+
+    // the same link again, or one to a missing story, is refused with a message
+    nav.handle_action(Action::LinkStory { story_id }).unwrap();
+    target.set(999);
+    nav.handle_action(Action::LinkStory { story_id }).unwrap();
+    assert_eq!(shown.borrow().len(), 2);
+    assert_eq!(
+        shown.borrow()[0],
+        format!(
+            "Link \"{} duplicates {}\" already exists",
+            story_id, other_story_id
+        )
+    );
+    assert_eq!(db.links_of(story_id).unwrap().len(), 1);
+
+    nav.handle_action(Action::UnlinkStory { story_id }).unwrap();
+    assert_eq!(db.links_of(story_id).unwrap().is_empty(), true);
+
+    // both the link and its removal can be undone
+    nav.handle_action(Action::Undo).unwrap();
+    assert_eq!(db.links_of(story_id).unwrap().len(), 1);
+    nav.handle_action(Action::Undo).unwrap();
+    assert_eq!(db.links_of(story_id).unwrap().is_empty(), true);
+    assert_eq!(nav.can_undo(), false);
+}
+
+#[test]
+fn starting_a_blocked_story_should_be_confirmed() {
+    let db = Rc::new(JiraDatabase::with_database(
+        Box::new(MockDB::new()),
+        Workflow::default(),
+    ));
+    let epic_id = db
+        .create_epic(Epic::new("".to_owned(), "".to_owned()))
+        .unwrap();
+    let story_id = db
+        .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+        .unwrap();
+    let blocker_id = db
+        .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+        .unwrap();
+    db.link_stories(blocker_id, LinkKind::Blocks, story_id)
+        .unwrap();
+
+    let mut nav = Navigator::new(Rc::clone(&db));
+
+    let mut prompts = Prompts::new();
+    prompts.update_status = Box::new(|_| Some(RecordStatus::InProgress));
+    prompts.start_blocked_story = Box::new(move |blockers| {
+        assert_eq!(blockers[0].0, blocker_id);
+        false
+    });
+    nav._set_prompts(prompts);
+
+    let status = |story_id| db.get_story(story_id).unwrap().unwrap().status;
+
+    nav.handle_action(Action::UpdateStoryStatus { story_id })
+        .unwrap();
+    nav.handle_action(Action::SetStoryStatus {
+        story_id,
+        status: RecordStatus::InProgress,
+    })
+    .unwrap();
+    assert_eq!(status(story_id), RecordStatus::Open);
+
+    // nothing to confirm for the blocker itself
+    nav.handle_action(Action::UpdateStoryStatus {
+        story_id: blocker_id,
+    })
+    .unwrap();
+    assert_eq!(status(blocker_id), RecordStatus::InProgress);
+
+    let mut prompts = Prompts::new();
+    prompts.start_blocked_story = Box::new(|_| true);
+    nav._set_prompts(prompts);
+
+    nav.handle_action(Action::SetStoryStatus {
+        story_id,
+        status: RecordStatus::InProgress,
+    })
+    .unwrap();
+    assert_eq!(status(story_id), RecordStatus::InProgress);
+}
+
+#[test]
+fn handle_action_should_handle_move_story() {
+    let db = Rc::new(JiraDatabase::with_database(
+        Box::new(MockDB::new()),
+        Workflow::default(),
+    ));
+    let epic_id = db
+        .create_epic(Epic::new("".to_owned(), "".to_owned()))
+        .unwrap();
+    let other_epic_id = db
+        .create_epic(Epic::new("".to_owned(), "".to_owned()))
+        .unwrap();
+    let story_id = db
+        .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+        .unwrap();
+
+    let mut nav = Navigator::new(Rc::clone(&db));
+
+    let mut prompts = Prompts::new();
+    prompts.move_story = Box::new(move |epics| {
+        assert_eq!(epics.len(), 1);
+        assert_eq!(epics[0].0, other_epic_id);
+        Some(other_epic_id)
+    });
+
+    nav._set_prompts(prompts);
+
+    nav.handle_action(Action::NavigateToEpicDetail { epic_id })
+        .unwrap();
+    nav.handle_action(Action::NavigateToStoryDetail { epic_id, story_id })
+        .unwrap();
+    nav.handle_action(Action::MoveStory { epic_id, story_id })
+        .unwrap();
+
+    assert_eq!(nav.page_count(), 2);
+
     let db_state = db.read_db().unwrap();
-    assert_eq!(db_state.stories.len(), 0);
+    assert_eq!(db_state.epics[&epic_id].stories.is_empty(), true);
+    assert_eq!(db_state.epics[&other_epic_id].stories, vec![story_id]);
+}
+
+#[test]
+fn handle_action_should_handle_search() {
+    let db = Rc::new(JiraDatabase::with_database(
+        Box::new(MockDB::new()),
+        Workflow::default(),
+    ));
+
+    let mut nav = Navigator::new(Rc::clone(&db));
+
+    let mut prompts = Prompts::new();
+    prompts.search = Box::new(|| " login ".to_owned());
+
+    nav._set_prompts(prompts);
+
+    nav.handle_action(Action::Search).unwrap();
+    nav.handle_action(Action::Search).unwrap();
+
+    assert_eq!(nav.page_count(), 2);
+
+    let current_page = nav.get_current_page().unwrap();
+    let search_page = current_page.as_any().downcast_ref::<SearchPage>();
+
+    assert_eq!(search_page.is_some(), true);
+    assert_eq!(search_page.unwrap().query, "login".to_owned());
+
+    let mut prompts = Prompts::new();
+    prompts.search = Box::new(String::new);
+
+    nav._set_prompts(prompts);
+
+    nav.handle_action(Action::Search).unwrap();
+    assert_eq!(nav.page_count(), 2);
+}
+
+#[test]
+fn handle_action_should_handle_filter_and_sort_list() {
+    let db = Rc::new(JiraDatabase::with_database(
+        Box::new(MockDB::new()),
+        Workflow::default(),
+    ));
+
+    let mut nav = Navigator::new(db);
+
+    let mut prompts = Prompts::new();
+    prompts.filter_list = Box::new(|view, list, statuses| {
+        assert_eq!(list, ListKind::Stories);
+        assert_eq!(statuses.len(), 4);
+
+        ListView {
+            hidden_statuses: vec![RecordStatus::Closed],
+            assignee: Some("alice".to_owned()),
+            ..view.clone()
+        }
+    });
+    prompts.sort_list = Box::new(|_| SortKey::Name);
+
+    nav._set_prompts(prompts);
+
+    nav.handle_action(Action::SortList {
+        list: ListKind::Stories,
+    })
+    .unwrap();
+    nav.handle_action(Action::FilterList {
+        list: ListKind::Stories,
+    })
+    .unwrap();
+
+    let expected = ListView {
+        hidden_statuses: vec![RecordStatus::Closed],
+        label: None,
+        assignee: Some("alice".to_owned()),
+        sort: SortKey::Name,
+    };
+    assert_eq!(nav.get_list_view(ListKind::Stories), expected);
+    assert_eq!(nav.get_list_view(ListKind::Epics), ListView::default());
+
+    // every epic page shares the story view for the rest of the session
+    nav.handle_action(Action::NavigateToEpicDetail { epic_id: 1 })
+        .unwrap();
+    nav.handle_action(Action::NavigateToPreviousPage).unwrap();
+    nav.handle_action(Action::NavigateToEpicDetail { epic_id: 2 })
+        .unwrap();
+
+    let current_page = nav.get_current_page().unwrap();
+    let epic_detail_page = current_page.as_any().downcast_ref::<EpicDetail>().unwrap();
+    assert_eq!(*epic_detail_page.view.borrow(), expected);
+}
+
+#[test]
+fn handle_action_should_undo_and_redo_changes() {
+    let db = Rc::new(JiraDatabase::with_database(
+        Box::new(MockDB::new()),
+        Workflow::default(),
+    ));
+
+    let epic_id = db
+        .create_epic(Epic::new("".to_owned(), "".to_owned()))
+        .unwrap();
+    let story_id = db
+        .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+        .unwrap();
+    let before = db.read_db().unwrap();
+
+    let mut nav = Navigator::new(Rc::clone(&db));
+
+    let mut prompts = Prompts::new();
+    prompts.delete_epic = Box::new(|| true);
+
+    nav._set_prompts(prompts);
+
+    // nothing to undo yet
+    nav.handle_action(Action::Undo).unwrap();
+    assert_eq!(nav.can_undo(), false);
+
+    nav.handle_action(Action::NavigateToEpicDetail { epic_id })
+        .unwrap();
+    nav.handle_action(Action::DeleteEpic { epic_id }).unwrap();
+    assert_eq!(nav.page_count(), 1);
+    assert_eq!(nav.can_undo(), true);
+
+    nav.handle_action(Action::Undo).unwrap();
+
+    let db_state = db.read_db().unwrap();
+    assert_eq!(db_state.epics, before.epics);
+    assert_eq!(db_state.stories, before.stories);
+    assert_eq!(nav.can_redo(), true);
+
+    // redoing the delete leaves the pages of the deleted items
+    nav.handle_action(Action::NavigateToEpicDetail { epic_id })
+        .unwrap();
+    nav.handle_action(Action::NavigateToStoryDetail { epic_id, story_id })
+        .unwrap();
+    nav.handle_action(Action::Redo).unwrap();
+
+    assert_eq!(db.get_epic(epic_id).unwrap(), None);
+    assert_eq!(nav.page_count(), 1);
+    assert_eq!(nav.can_redo(), false);
+}
+
+#[test]
+fn handle_action_should_handle_trash() {
+    let db = Rc::new(JiraDatabase::with_database(
+        Box::new(MockDB::new()),
+        Workflow::default(),
+    ));
+
+    let epic_id = db
+        .create_epic(Epic::new("".to_owned(), "".to_owned()))
+        .unwrap();
+    let other_epic_id = db
+        .create_epic(Epic::new("".to_owned(), "".to_owned()))
+        .unwrap();
+    db.delete_epic(epic_id).unwrap();
+    db.delete_epic(other_epic_id).unwrap();
+
+    let mut nav = Navigator::new(Rc::clone(&db));
+
+    let mut prompts = Prompts::new();
+    prompts.restore_from_trash = Box::new(move |items| {
+        assert_eq!(items.len(), 2);
+        Some(epic_id)
+    });
+    prompts.purge_from_trash = Box::new(|_| None);
+    prompts.empty_trash = Box::new(|| true);
+
+    nav._set_prompts(prompts);
+
+    nav.handle_action(Action::NavigateToTrash).unwrap();
+
+    let current_page = nav.get_current_page().unwrap();
+    assert_eq!(current_page.as_any().is::<TrashPage>(), true);
+
+    nav.handle_action(Action::RestoreFromTrash).unwrap();
+    assert_eq!(db.get_epic(epic_id).unwrap().is_some(), true);
+
+    nav.handle_action(Action::PurgeFromTrash).unwrap();
+    assert_eq!(db.list_trash().unwrap().len(), 1);
+
+    nav.handle_action(Action::EmptyTrash).unwrap();
+    assert_eq!(db.list_trash().unwrap().is_empty(), true);
+    assert_eq!(db.read_db().unwrap().epics.len(), 1);
+
+    // purging can be undone within the session
+    nav.handle_action(Action::Undo).unwrap();
+    assert_eq!(db.list_trash().unwrap().len(), 1);
+}
+
+#[test]
+fn handle_action_should_handle_board() {
+    let db = Rc::new(JiraDatabase::with_database(
+        Box::new(MockDB::new()),
+        Workflow::default(),
+    ));
+
+    let epic_id = db
+        .create_epic(Epic::new("".to_owned(), "".to_owned()))
+        .unwrap();
+    let story_id = db
+        .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+        .unwrap();
+
+    let mut nav = Navigator::new(Rc::clone(&db));
+
+    nav.handle_action(Action::NavigateToEpicDetail { epic_id })
+        .unwrap();
+    nav.handle_action(Action::NavigateToBoard {
+        epic_id: Some(epic_id),
+    })
+    .unwrap();
+
+    let current_page = nav.get_current_page().unwrap();
+    let board_page = current_page.as_any().downcast_ref::<BoardPage>();
+
+    assert_eq!(board_page.is_some(), true);
+    assert_eq!(board_page.unwrap().epic_id, Some(epic_id));
+
+    nav.handle_action(Action::SetStoryStatus {
+        story_id,
+        status: RecordStatus::InProgress,
+    })
+    .unwrap();
+    assert_eq!(
+        db.get_story(story_id).unwrap().unwrap().status,
+        RecordStatus::InProgress
+    );
+
+    // the workflow still applies
+    nav.handle_action(Action::SetStoryStatus {
+        story_id,
+        status: RecordStatus::Closed,
+    })
+    .unwrap();
+    let result = nav.handle_action(Action::SetStoryStatus {
+        story_id,
+        status: RecordStatus::Open,
+    });
+    assert_eq!(result.is_err(), true);
+
+    nav.handle_action(Action::Undo).unwrap();
+    assert_eq!(
+        db.get_story(story_id).unwrap().unwrap().status,
+        RecordStatus::InProgress
+    );
+    assert_eq!(nav.page_count(), 3);
+}
+
+#[test]
+fn handle_action_should_show_messages() {
+    let db = Rc::new(JiraDatabase::with_database(
+        Box::new(MockDB::new()),
+        Workflow::default(),
+    ));
+
+    let mut nav = Navigator::new(Rc::clone(&db));
+
+    let shown = Rc::new(std::cell::RefCell::new(vec![]));
+    let mut prompts = Prompts::new();
+    prompts.message = {
+        let shown = Rc::clone(&shown);
+        Box::new(move |text| shown.borrow_mut().push(text.to_owned()))
+    };
+    nav._set_prompts(prompts);
+
+    nav.handle_action(Action::ShowMessage {
+        text: "Not allowed.".to_owned(),
+    })
+    .unwrap();
+
+    assert_eq!(*shown.borrow(), vec!["Not allowed.".to_owned()]);
+    assert_eq!(nav.page_count(), 1);
 }
@@ -1,4 +1,4 @@
-use cli_jira::ui::pages::page_helpers::get_column_string;
+use cli_jira::ui::pages::page_helpers::{display_width, get_column_string, wrap_to_width};
 
 #[test]
 fn test_get_column_string() {
@@ -34,3 +34,29 @@ fn test_get_column_string() {
     assert_eq!(get_column_string(text3, width), "testme".to_owned());
     assert_eq!(get_column_string(text4, width), "tes...".to_owned());
 }
+
+#[test]
+fn get_column_string_should_measure_display_width() {
+    // accented letters take one column, emoji and CJK characters two
+    assert_eq!(get_column_string("café", 6), "café  ".to_owned());
+    assert_eq!(get_column_string("🚀 launch", 10), "🚀 launch ".to_owned());
+    assert_eq!(
+        get_column_string("日本語のテキスト", 8),
+        "日本... ".to_owned()
+    );
+    assert_eq!(display_width("🚀 launch"), 9);
+}
+
+#[test]
+fn wrap_to_width_should_break_between_words() {
+    assert_eq!(
+        wrap_to_width("log in with an email address", 12),
+        vec!["log in with", "an email", "address"]
+    );
+    assert_eq!(wrap_to_width("abcdefghij", 4), vec!["abcd", "efgh", "ij"]);
+    assert_eq!(
+        wrap_to_width("日本語のテキスト", 5),
+        vec!["日本", "語の", "テキ", "スト"]
+    );
+    assert_eq!(wrap_to_width("", 5), vec![""]);
+}
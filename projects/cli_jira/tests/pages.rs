@@ -1,46 +1,64 @@
+// Asserts compare with `true`/`false` on purpose, as throughout the suite.
+#![allow(clippy::bool_assert_comparison)]
+
 mod common;
 
 use common::MockDB;
 use std::rc::Rc;
 
 use cli_jira::db::JiraDatabase;
-use cli_jira::models::{Action, Epic, Story};
-use cli_jira::ui::{EpicDetail, HomePage, Page, StoryDetail};
+use cli_jira::models::{Action, Epic, ListKind, RecordStatus, Story};
+use cli_jira::ui::{
+    BoardPage, EpicDetail, HomePage, Page, SearchPage, StoryDetail, TrashPage, COMMENTS_PER_PAGE,
+};
+use cli_jira::workflow::Workflow;
 
 mod home_page {
     use super::*;
 
     #[test]
     fn draw_page_should_not_throw_error() {
-        let db = Rc::new(JiraDatabase {
-            database: Box::new(MockDB::new()),
-        });
+        let db = Rc::new(JiraDatabase::with_database(
+            Box::new(MockDB::new()),
+            Workflow::default(),
+        ));
 
-        let page = HomePage { db };
+        let page = HomePage {
+            db,
+            view: Default::default(),
+        };
         assert_eq!(page.draw_page().is_ok(), true);
     }
 
     #[test]
     fn handle_input_should_not_throw_error() {
-        let db = Rc::new(JiraDatabase {
-            database: Box::new(MockDB::new()),
-        });
+        let db = Rc::new(JiraDatabase::with_database(
+            Box::new(MockDB::new()),
+            Workflow::default(),
+        ));
 
-        let page = HomePage { db };
+        let page = HomePage {
+            db,
+            view: Default::default(),
+        };
         assert_eq!(page.handle_input("").is_ok(), true);
     }
 
     #[test]
     fn handle_input_should_return_the_correct_actions() {
-        let db = Rc::new(JiraDatabase {
-            database: Box::new(MockDB::new()),
-        });
+        let db = Rc::new(JiraDatabase::with_database(
+            Box::new(MockDB::new()),
+            Workflow::default(),
+        ));
 
         let epic = Epic::new("".to_owned(), "".to_owned());
 
         let epic_id = db.create_epic(epic).unwrap();
 
-        let page = HomePage { db };
+        let page = HomePage {
+            db,
+            view: Default::default(),
+        };
 
         let q = "q";
         let c = "c";
@@ -52,6 +70,29 @@ mod home_page {
 
         assert_eq!(page.handle_input(q).unwrap(), Some(Action::Exit));
         assert_eq!(page.handle_input(c).unwrap(), Some(Action::CreateEpic));
+        assert_eq!(
+            page.handle_input("t").unwrap(),
+            Some(Action::NavigateToTrash)
+        );
+        assert_eq!(
+            page.handle_input("b").unwrap(),
+            Some(Action::NavigateToBoard { epic_id: None })
+        );
+        assert_eq!(
+            page.handle_input("f").unwrap(),
+            Some(Action::FilterList {
+                list: ListKind::Epics
+            })
+        );
+        assert_eq!(
+            page.handle_input("s").unwrap(),
+            Some(Action::SortList {
+                list: ListKind::Epics
+            })
+        );
+        assert_eq!(page.handle_input("/").unwrap(), Some(Action::Search));
+        assert_eq!(page.handle_input("z").unwrap(), Some(Action::Undo));
+        assert_eq!(page.handle_input("y").unwrap(), Some(Action::Redo));
         assert_eq!(
             page.handle_input(&valid_epic_id).unwrap(),
             Some(Action::NavigateToEpicDetail { epic_id: 1 })
@@ -75,45 +116,61 @@ mod epic_detail_page {
 
     #[test]
     fn draw_page_should_not_throw_error() {
-        let db = Rc::new(JiraDatabase {
-            database: Box::new(MockDB::new()),
-        });
+        let db = Rc::new(JiraDatabase::with_database(
+            Box::new(MockDB::new()),
+            Workflow::default(),
+        ));
         let epic_id = db
             .create_epic(Epic::new("".to_owned(), "".to_owned()))
             .unwrap();
 
-        let page = EpicDetail { epic_id, db };
+        let page = EpicDetail {
+            epic_id,
+            db,
+            view: Default::default(),
+        };
         assert_eq!(page.draw_page().is_ok(), true);
     }
 
     #[test]
     fn handle_input_should_not_throw_error() {
-        let db = Rc::new(JiraDatabase {
-            database: Box::new(MockDB::new()),
-        });
+        let db = Rc::new(JiraDatabase::with_database(
+            Box::new(MockDB::new()),
+            Workflow::default(),
+        ));
         let epic_id = db
             .create_epic(Epic::new("".to_owned(), "".to_owned()))
             .unwrap();
 
-        let page = EpicDetail { epic_id, db };
+        let page = EpicDetail {
+            epic_id,
+            db,
+            view: Default::default(),
+        };
         assert_eq!(page.handle_input("").is_ok(), true);
     }
 
     #[test]
     fn draw_page_should_throw_error_for_invalid_epic_id() {
-        let db = Rc::new(JiraDatabase {
-            database: Box::new(MockDB::new()),
-        });
+        let db = Rc::new(JiraDatabase::with_database(
+            Box::new(MockDB::new()),
+            Workflow::default(),
+        ));
 
-        let page = EpicDetail { epic_id: 999, db };
+        let page = EpicDetail {
+            epic_id: 999,
+            db,
+            view: Default::default(),
+        };
         assert_eq!(page.draw_page().is_err(), true);
     }
 
     #[test]
     fn handle_input_should_return_the_correct_actions() {
-        let db = Rc::new(JiraDatabase {
-            database: Box::new(MockDB::new()),
-        });
+        let db = Rc::new(JiraDatabase::with_database(
+            Box::new(MockDB::new()),
+            Workflow::default(),
+        ));
 
         let epic_id = db
             .create_epic(Epic::new("".to_owned(), "".to_owned()))
@@ -122,10 +179,16 @@ mod epic_detail_page {
             .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
             .unwrap();
 
-        let page = EpicDetail { epic_id, db };
+        let page = EpicDetail {
+            epic_id,
+            db,
+            view: Default::default(),
+        };
 
         let p = "p";
         let u = "u";
+        let e = "e";
+        let l = "l";
         let d = "d";
         let c = "c";
         let invalid_story_id = "999";
@@ -141,6 +204,14 @@ mod epic_detail_page {
             page.handle_input(u).unwrap(),
             Some(Action::UpdateEpicStatus { epic_id: 1 })
         );
+        assert_eq!(
+            page.handle_input(e).unwrap(),
+            Some(Action::EditEpic { epic_id: 1 })
+        );
+        assert_eq!(
+            page.handle_input(l).unwrap(),
+            Some(Action::EditEpicLabels { epic_id: 1 })
+        );
         assert_eq!(
             page.handle_input(d).unwrap(),
             Some(Action::DeleteEpic { epic_id: 1 })
@@ -149,6 +220,24 @@ mod epic_detail_page {
             page.handle_input(c).unwrap(),
             Some(Action::CreateStory { epic_id: 1 })
         );
+        assert_eq!(
+            page.handle_input("b").unwrap(),
+            Some(Action::NavigateToBoard { epic_id: Some(1) })
+        );
+        assert_eq!(
+            page.handle_input("f").unwrap(),
+            Some(Action::FilterList {
+                list: ListKind::Stories
+            })
+        );
+        assert_eq!(
+            page.handle_input("s").unwrap(),
+            Some(Action::SortList {
+                list: ListKind::Stories
+            })
+        );
+        assert_eq!(page.handle_input("z").unwrap(), Some(Action::Undo));
+        assert_eq!(page.handle_input("y").unwrap(), Some(Action::Redo));
         assert_eq!(
             page.handle_input(&story_id.to_string()).unwrap(),
             Some(Action::NavigateToStoryDetail {
@@ -174,9 +263,10 @@ mod story_detail_page {
 
     #[test]
     fn draw_page_should_not_throw_error() {
-        let db = Rc::new(JiraDatabase {
-            database: Box::new(MockDB::new()),
-        });
+        let db = Rc::new(JiraDatabase::with_database(
+            Box::new(MockDB::new()),
+            Workflow::default(),
+        ));
 
         let epic_id = db
             .create_epic(Epic::new("".to_owned(), "".to_owned()))
@@ -189,15 +279,17 @@ mod story_detail_page {
             epic_id,
             story_id,
             db,
+            comment_page: Default::default(),
         };
         assert_eq!(page.draw_page().is_ok(), true);
     }
 
     #[test]
     fn handle_input_should_not_throw_error() {
-        let db = Rc::new(JiraDatabase {
-            database: Box::new(MockDB::new()),
-        });
+        let db = Rc::new(JiraDatabase::with_database(
+            Box::new(MockDB::new()),
+            Workflow::default(),
+        ));
 
         let epic_id = db
             .create_epic(Epic::new("".to_owned(), "".to_owned()))
@@ -210,15 +302,17 @@ mod story_detail_page {
             epic_id,
             story_id,
             db,
+            comment_page: Default::default(),
         };
         assert_eq!(page.handle_input("").is_ok(), true);
     }
 
     #[test]
     fn draw_page_should_throw_error_for_invalid_story_id() {
-        let db = Rc::new(JiraDatabase {
-            database: Box::new(MockDB::new()),
-        });
+        let db = Rc::new(JiraDatabase::with_database(
+            Box::new(MockDB::new()),
+            Workflow::default(),
+        ));
 
         let epic_id = db
             .create_epic(Epic::new("".to_owned(), "".to_owned()))
@@ -231,15 +325,17 @@ mod story_detail_page {
             epic_id,
             story_id: 999,
             db,
+            comment_page: Default::default(),
         };
         assert_eq!(page.draw_page().is_err(), true);
     }
 
     #[test]
     fn handle_input_should_return_the_correct_actions() {
-        let db = Rc::new(JiraDatabase {
-            database: Box::new(MockDB::new()),
-        });
+        let db = Rc::new(JiraDatabase::with_database(
+            Box::new(MockDB::new()),
+            Workflow::default(),
+        ));
 
         let epic_id = db
             .create_epic(Epic::new("".to_owned(), "".to_owned()))
@@ -252,11 +348,15 @@ mod story_detail_page {
             epic_id,
             story_id,
             db,
+            comment_page: Default::default(),
         };
 
         let p = "p";
         let u = "u";
+        let e = "e";
+        let t = "t";
         let d = "d";
+        let m = "m";
         let some_number = "1";
         let junk_input = "j983f2j";
         let junk_input_with_valid_prefix = "p983f2j";
@@ -270,10 +370,33 @@ mod story_detail_page {
             page.handle_input(u).unwrap(),
             Some(Action::UpdateStoryStatus { story_id })
         );
+        assert_eq!(
+            page.handle_input(e).unwrap(),
+            Some(Action::EditStory { story_id })
+        );
+        assert_eq!(
+            page.handle_input(t).unwrap(),
+            Some(Action::EditStoryDetails { story_id })
+        );
         assert_eq!(
             page.handle_input(d).unwrap(),
             Some(Action::DeleteStory { epic_id, story_id })
         );
+        assert_eq!(
+            page.handle_input(m).unwrap(),
+            Some(Action::MoveStory { epic_id, story_id })
+        );
+        assert_eq!(
+            page.handle_input("l").unwrap(),
+            Some(Action::LinkStory { story_id })
+        );
+        assert_eq!(
+            page.handle_input("r").unwrap(),
+            Some(Action::UnlinkStory { story_id })
+        );
+        assert_eq!(page.handle_input("/").unwrap(), Some(Action::Search));
+        assert_eq!(page.handle_input("z").unwrap(), Some(Action::Undo));
+        assert_eq!(page.handle_input("y").unwrap(), Some(Action::Redo));
         assert_eq!(page.handle_input(some_number).unwrap(), None);
         assert_eq!(page.handle_input(junk_input).unwrap(), None);
         assert_eq!(
@@ -285,4 +408,377 @@ mod story_detail_page {
             None
         );
     }
+
+    #[test]
+    fn handle_input_should_return_checklist_actions() {
+        let db = Rc::new(JiraDatabase::with_database(
+            Box::new(MockDB::new()),
+            Workflow::default(),
+        ));
+
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+        db.add_checklist_item(story_id, "write tests".to_owned())
+            .unwrap();
+        db.add_checklist_item(story_id, "review".to_owned())
+            .unwrap();
+
+        let page = StoryDetail {
+            epic_id,
+            story_id,
+            db,
+            comment_page: Default::default(),
+        };
+
+        assert_eq!(page.draw_page().is_ok(), true);
+        assert_eq!(
+            page.handle_input("a").unwrap(),
+            Some(Action::AddChecklistItem { story_id })
+        );
+        assert_eq!(
+            page.handle_input("2").unwrap(),
+            Some(Action::ToggleChecklistItem { story_id, index: 1 })
+        );
+        assert_eq!(
+            page.handle_input("1x").unwrap(),
+            Some(Action::RemoveChecklistItem { story_id, index: 0 })
+        );
+        assert_eq!(page.handle_input("0").unwrap(), None);
+        assert_eq!(page.handle_input("3").unwrap(), None);
+        assert_eq!(page.handle_input("3x").unwrap(), None);
+        assert_eq!(page.handle_input("x").unwrap(), None);
+    }
+
+    #[test]
+    fn comments_should_be_shown_in_pages() {
+        let db = Rc::new(JiraDatabase::with_database(
+            Box::new(MockDB::new()),
+            Workflow::default(),
+        ));
+
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+
+        for number in 1..=(COMMENTS_PER_PAGE * 2 + 2) {
+            db.add_comment(story_id, format!("comment {}", number))
+                .unwrap();
+        }
+
+        let page = StoryDetail {
+            epic_id,
+            story_id,
+            db,
+            comment_page: Default::default(),
+        };
+
+        let texts = |page: &StoryDetail| -> Vec<String> {
+            let (comments, _, _) = page.shown_comments().unwrap();
+            comments.into_iter().map(|comment| comment.text).collect()
+        };
+
+        assert_eq!(page.draw_page().is_ok(), true);
+        assert_eq!(
+            page.handle_input("c").unwrap(),
+            Some(Action::AddComment { story_id })
+        );
+
+        // newest first
+        assert_eq!(texts(&page)[0], "comment 12");
+        assert_eq!(texts(&page).len(), COMMENTS_PER_PAGE);
+        assert_eq!(page.handle_input(">").unwrap(), None);
+        assert_eq!(
+            page.handle_input("<").unwrap(),
+            Some(Action::ShowComments { page: 1 })
+        );
+
+        page.comment_page.set(2);
+        assert_eq!(texts(&page), vec!["comment 2", "comment 1"]);
+        assert_eq!(page.shown_comments().unwrap().1, 2);
+        assert_eq!(page.handle_input("<").unwrap(), None);
+        assert_eq!(
+            page.handle_input(">").unwrap(),
+            Some(Action::ShowComments { page: 1 })
+        );
+
+        // a page past the end shows the oldest comments
+        page.comment_page.set(7);
+        assert_eq!(page.shown_comments().unwrap().1, 2);
+        assert_eq!(page.draw_page().is_ok(), true);
+    }
+}
+
+mod search_page {
+    use super::*;
+
+    fn sample_db() -> Rc<JiraDatabase> {
+        let db = Rc::new(JiraDatabase::with_database(
+            Box::new(MockDB::new()),
+            Workflow::default(),
+        ));
+
+        let epic_id = db
+            .create_epic(Epic::new("Login".to_owned(), "".to_owned()))
+            .unwrap();
+        db.create_story(Story::new("Login page".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+        db.create_story(Story::new("Logout".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+
+        db
+    }
+
+    #[test]
+    fn draw_page_should_not_throw_error() {
+        let page = SearchPage {
+            query: "login".to_owned(),
+            db: sample_db(),
+        };
+
+        assert_eq!(page.draw_page().is_ok(), true);
+    }
+
+    #[test]
+    fn handle_input_should_return_the_correct_actions() {
+        let page = SearchPage {
+            query: "login".to_owned(),
+            db: sample_db(),
+        };
+
+        assert_eq!(
+            page.handle_input("p").unwrap(),
+            Some(Action::NavigateToPreviousPage)
+        );
+        assert_eq!(page.handle_input("/").unwrap(), Some(Action::Search));
+        assert_eq!(page.handle_input("z").unwrap(), Some(Action::Undo));
+        assert_eq!(page.handle_input("y").unwrap(), Some(Action::Redo));
+        assert_eq!(
+            page.handle_input("1").unwrap(),
+            Some(Action::NavigateToEpicDetail { epic_id: 1 })
+        );
+        assert_eq!(
+            page.handle_input("2").unwrap(),
+            Some(Action::NavigateToStoryDetail {
+                epic_id: 1,
+                story_id: 2
+            })
+        );
+        // exists, but doesn't match the query
+        assert_eq!(page.handle_input("3").unwrap(), None);
+        assert_eq!(page.handle_input("j983f2j").unwrap(), None);
+    }
+}
+
+mod trash_page {
+    use super::*;
+
+    #[test]
+    fn draw_page_should_not_throw_error() {
+        let db = Rc::new(JiraDatabase::with_database(
+            Box::new(MockDB::new()),
+            Workflow::default(),
+        ));
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        db.delete_epic(epic_id).unwrap();
+
+        let page = TrashPage { db };
+        assert_eq!(page.draw_page().is_ok(), true);
+    }
+
+    #[test]
+    fn handle_input_should_return_the_correct_actions() {
+        let db = Rc::new(JiraDatabase::with_database(
+            Box::new(MockDB::new()),
+            Workflow::default(),
+        ));
+
+        let page = TrashPage { db };
+
+        assert_eq!(
+            page.handle_input("p").unwrap(),
+            Some(Action::NavigateToPreviousPage)
+        );
+        assert_eq!(
+            page.handle_input("r").unwrap(),
+            Some(Action::RestoreFromTrash)
+        );
+        assert_eq!(
+            page.handle_input("x").unwrap(),
+            Some(Action::PurgeFromTrash)
+        );
+        assert_eq!(page.handle_input("e").unwrap(), Some(Action::EmptyTrash));
+        assert_eq!(page.handle_input("/").unwrap(), Some(Action::Search));
+        assert_eq!(page.handle_input("z").unwrap(), Some(Action::Undo));
+        assert_eq!(page.handle_input("1").unwrap(), None);
+    }
+}
+
+mod board_page {
+    use super::*;
+
+    // epic 1 with stories 2 (Open) and 3 (In progress), epic 4 with story 5 (Closed)
+    fn sample_db() -> Rc<JiraDatabase> {
+        let db = Rc::new(JiraDatabase::with_database(
+            Box::new(MockDB::new()),
+            Workflow::default(),
+        ));
+
+        let epic_id = db
+            .create_epic(Epic::new("Backend".to_owned(), "".to_owned()))
+            .unwrap();
+        db.create_story(Story::new("Login".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("Logout".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+        db.update_story_status(story_id, RecordStatus::InProgress)
+            .unwrap();
+
+        let epic_id = db
+            .create_epic(Epic::new("Mobile".to_owned(), "".to_owned()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("Splash".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+        db.update_story_status(story_id, RecordStatus::Closed)
+            .unwrap();
+
+        db
+    }
+
+    fn story_ids(page: &BoardPage) -> Vec<(RecordStatus, Vec<u32>)> {
+        page.columns()
+            .unwrap()
+            .into_iter()
+            .map(|column| {
+                let ids = column.cards.iter().map(|card| card.story_id).collect();
+                (column.status, ids)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn draw_page_should_not_throw_error() {
+        let db = sample_db();
+
+        let page = BoardPage {
+            epic_id: Some(1),
+            db: db.clone(),
+        };
+        assert_eq!(page.draw_page().is_ok(), true);
+
+        let page = BoardPage { epic_id: None, db };
+        assert_eq!(page.draw_page().is_ok(), true);
+    }
+
+    #[test]
+    fn draw_page_should_throw_error_for_invalid_epic_id() {
+        let page = BoardPage {
+            epic_id: Some(999),
+            db: sample_db(),
+        };
+
+        assert_eq!(page.draw_page().is_err(), true);
+    }
+
+    #[test]
+    fn columns_should_follow_the_workflow() {
+        let db = sample_db();
+
+        let page = BoardPage {
+            epic_id: Some(1),
+            db: db.clone(),
+        };
+        assert_eq!(
+            story_ids(&page),
+            vec![
+                (RecordStatus::Open, vec![2]),
+                (RecordStatus::InProgress, vec![3]),
+                (RecordStatus::Resolved, vec![]),
+                (RecordStatus::Closed, vec![]),
+            ]
+        );
+
+        let page = BoardPage { epic_id: None, db };
+        assert_eq!(
+            story_ids(&page),
+            vec![
+                (RecordStatus::Open, vec![2]),
+                (RecordStatus::InProgress, vec![3]),
+                (RecordStatus::Resolved, vec![]),
+                (RecordStatus::Closed, vec![5]),
+            ]
+        );
+    }
+
+    #[test]
+    fn handle_input_should_return_the_correct_actions() {
+        let db = sample_db();
+
+        let page = BoardPage {
+            epic_id: Some(1),
+            db: db.clone(),
+        };
+
+        assert_eq!(
+            page.handle_input("p").unwrap(),
+            Some(Action::NavigateToPreviousPage)
+        );
+        assert_eq!(page.handle_input("/").unwrap(), Some(Action::Search));
+        assert_eq!(page.handle_input("z").unwrap(), Some(Action::Undo));
+        assert_eq!(page.handle_input("y").unwrap(), Some(Action::Redo));
+        assert_eq!(
+            page.handle_input("3").unwrap(),
+            Some(Action::NavigateToStoryDetail {
+                epic_id: 1,
+                story_id: 3
+            })
+        );
+        assert_eq!(
+            page.handle_input("3>").unwrap(),
+            Some(Action::SetStoryStatus {
+                story_id: 3,
+                status: RecordStatus::Resolved
+            })
+        );
+        assert_eq!(
+            page.handle_input("3<").unwrap(),
+            Some(Action::SetStoryStatus {
+                story_id: 3,
+                status: RecordStatus::Open
+            })
+        );
+        // already in the first column
+        assert_eq!(page.handle_input("2<").unwrap(), None);
+        // not on this epic's board
+        assert_eq!(page.handle_input("5").unwrap(), None);
+        assert_eq!(page.handle_input("j983f2j").unwrap(), None);
+
+        let page = BoardPage { epic_id: None, db };
+
+        assert_eq!(
+            page.handle_input("5").unwrap(),
+            Some(Action::NavigateToStoryDetail {
+                epic_id: 4,
+                story_id: 5
+            })
+        );
+        assert_eq!(page.handle_input("5>").unwrap(), None);
+        // closed stories can only be reopened as In progress
+        assert_eq!(
+            page.handle_input("5<").unwrap(),
+            Some(Action::ShowMessage {
+                text: "Story 5 can't go from Closed to Resolved.".to_owned()
+            })
+        );
+    }
 }
@@ -0,0 +1,79 @@
+// Asserts compare with `true`/`false` on purpose, as throughout the suite.
+#![allow(clippy::bool_assert_comparison)]
+
+use cli_jira::models::{DBState, Epic, Story};
+use cli_jira::search::{search, SearchHit};
+
+use std::collections::HashMap;
+
+fn sample_state() -> DBState {
+    let mut epics = HashMap::new();
+    epics.insert(
+        1,
+        Epic {
+            stories: vec![3, 2],
+            ..Epic::new("Login".to_owned(), "Users can sign in".to_owned())
+        },
+    );
+    epics.insert(
+        4,
+        Epic {
+            stories: vec![5],
+            ..Epic::new("Billing".to_owned(), "".to_owned())
+        },
+    );
+
+    let mut stories = HashMap::new();
+    stories.insert(
+        2,
+        Story::new("Login page".to_owned(), "Form with password".to_owned()),
+    );
+    stories.insert(
+        3,
+        Story::new("OAuth".to_owned(), "Sign in with GitHub".to_owned()),
+    );
+    stories.insert(
+        5,
+        Story::new("Invoices".to_owned(), "Send after login".to_owned()),
+    );
+
+    DBState {
+        last_item_id: 5,
+        epics,
+        stories,
+        history: vec![],
+        comments: vec![],
+        links: vec![],
+    }
+}
+
+fn hit_ids(hits: &[SearchHit]) -> Vec<u32> {
+    hits.iter().map(SearchHit::id).collect()
+}
+
+#[test]
+fn search_should_match_names_and_descriptions_ignoring_case() {
+    let hits = search(&sample_state(), "LOGIN");
+
+    assert_eq!(hit_ids(&hits), vec![1, 2, 5]);
+    assert_eq!(
+        hits[2],
+        SearchHit::Story {
+            epic_id: 4,
+            story_id: 5,
+            story: sample_state().stories[&5].clone(),
+        }
+    );
+}
+
+#[test]
+fn search_should_require_all_terms() {
+    assert_eq!(hit_ids(&search(&sample_state(), "sign github")), vec![3]);
+    assert_eq!(hit_ids(&search(&sample_state(), "sign in")), vec![1, 3]);
+    assert_eq!(search(&sample_state(), "login refunds").is_empty(), true);
+}
+
+#[test]
+fn search_should_return_nothing_for_empty_query() {
+    assert_eq!(search(&sample_state(), "  ").is_empty(), true);
+}
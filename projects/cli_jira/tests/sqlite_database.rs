@@ -0,0 +1,399 @@
+// Asserts compare with `true`/`false` on purpose, as throughout the suite.
+#![allow(clippy::bool_assert_comparison)]
+
+mod common;
+
+use chrono::{TimeZone, Utc};
+use cli_jira::db::{
+    Database, EpicRef, JSONFileDatabase, JiraDatabase, SqliteDatabase, StateDatabase,
+};
+use cli_jira::models::{
+    Change, ChecklistItem, Comment, DBState, Epic, HistoryEntry, LinkKind, Priority, RecordStatus,
+    Story, StoryLink,
+};
+
+use cli_jira::undo::UndoStack;
+use common::MockDB;
+use std::collections::HashMap;
+
+fn sample_state() -> DBState {
+    let mut epics = HashMap::new();
+    epics.insert(
+        1,
+        Epic {
+            name: "epic 1".to_owned(),
+            description: "epic 1".to_owned(),
+            status: RecordStatus::InProgress,
+            stories: vec![3, 2],
+            labels: vec!["backend".to_owned()],
+            created_at: Some(Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap()),
+            updated_at: Some(Utc.with_ymd_and_hms(2024, 2, 3, 4, 5, 6).unwrap()),
+            deleted_at: None,
+        },
+    );
+
+    let mut stories = HashMap::new();
+    stories.insert(2, Story::new("story 2".to_owned(), "story 2".to_owned()));
+    stories.insert(
+        3,
+        Story {
+            name: "story 3".to_owned(),
+            description: "story 3".to_owned(),
+            status: RecordStatus::Closed,
+            priority: Some(Priority::High),
+            assignee: Some("alice".to_owned()),
+            labels: vec!["api".to_owned(), "auth".to_owned()],
+            estimate: Some(5),
+            checklist: vec![
+                ChecklistItem {
+                    text: "write tests".to_owned(),
+                    done: true,
+                },
+                ChecklistItem::new("review".to_owned()),
+            ],
+            created_at: Some(Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap()),
+            updated_at: None,
+            deleted_at: Some(Utc.with_ymd_and_hms(2024, 3, 4, 5, 6, 7).unwrap()),
+        },
+    );
+
+    DBState {
+        last_item_id: 3,
+        epics,
+        stories,
+        history: vec![
+            HistoryEntry {
+                item_id: 3,
+                timestamp: Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap(),
+                author: "alice".to_owned(),
+                change: Change::Created,
+            },
+            HistoryEntry {
+                item_id: 3,
+                timestamp: Utc.with_ymd_and_hms(2024, 1, 3, 3, 4, 5).unwrap(),
+                author: "bob".to_owned(),
+                change: Change::StatusChanged {
+                    from: RecordStatus::Open,
+                    to: RecordStatus::Closed,
+                },
+            },
+        ],
+        comments: vec![Comment {
+            story_id: 3,
+            timestamp: Utc.with_ymd_and_hms(2024, 1, 3, 4, 5, 6).unwrap(),
+            author: "bob".to_owned(),
+            text: "done, see the PR".to_owned(),
+        }],
+        links: vec![
+            StoryLink::new(3, LinkKind::Blocks, 2),
+            StoryLink::new(3, LinkKind::RelatesTo, 2),
+        ],
+    }
+}
+
+#[test]
+fn read_db_should_return_empty_state_for_new_database() {
+    let db = SqliteDatabase::open_in_memory().unwrap();
+
+    let db_state = db.read_db().unwrap();
+
+    assert_eq!(db_state.last_item_id, 0);
+    assert_eq!(db_state.epics.is_empty(), true);
+    assert_eq!(db_state.stories.is_empty(), true);
+}
+
+#[test]
+fn import_from_should_keep_story_order() {
+    let source = MockDB::new();
+    source.save_state(&sample_state()).unwrap();
+
+    let db = SqliteDatabase::open_in_memory().unwrap();
+
+    assert_eq!(db.import_from(&source).is_ok(), true);
+    assert_eq!(db.read_db().unwrap(), sample_state());
+    assert_eq!(db.get_epic(1).unwrap().unwrap().stories, vec![3, 2]);
+    assert_eq!(db.history_of(3).unwrap(), sample_state().history);
+    assert_eq!(db.history_of(2).unwrap().is_empty(), true);
+}
+
+#[test]
+fn entity_operations_should_work() {
+    let db = SqliteDatabase::open_in_memory().unwrap();
+    db.import_from(&{
+        let source = MockDB::new();
+        source.save_state(&sample_state()).unwrap();
+        source
+    })
+    .unwrap();
+
+    assert_eq!(db.next_item_id().unwrap(), 4);
+    db.insert_epic(4, &Epic::new("epic 4".to_owned(), "".to_owned()))
+        .unwrap();
+    db.insert_story(4, 5, &Story::new("story 5".to_owned(), "".to_owned()))
+        .unwrap();
+
+    let mut story = db.get_story(2).unwrap().unwrap();
+    story.status = RecordStatus::Resolved;
+    db.update_story(2, &story).unwrap();
+
+    let mut epic = db.get_epic(1).unwrap().unwrap();
+    epic.name = "renamed".to_owned();
+    db.update_epic(1, &epic).unwrap();
+
+    db.delete_story(3).unwrap();
+    db.move_story(5, 1).unwrap();
+    db.move_story(5, 4).unwrap();
+    db.insert_link(&StoryLink::new(2, LinkKind::Duplicates, 5))
+        .unwrap();
+
+    assert_eq!(db.get_story(2).unwrap(), Some(story));
+    assert_eq!(db.get_story(3).unwrap(), None);
+    assert_eq!(
+        db.list_links().unwrap(),
+        vec![StoryLink::new(2, LinkKind::Duplicates, 5)]
+    );
+    assert_eq!(
+        db.get_epic(1).unwrap(),
+        Some(Epic {
+            stories: vec![2],
+            ..epic
+        })
+    );
+    assert_eq!(
+        db.list_epics()
+            .unwrap()
+            .into_iter()
+            .map(|(epic_id, _)| epic_id)
+            .collect::<Vec<_>>(),
+        vec![1, 4]
+    );
+    assert_eq!(
+        db.list_stories_of_epic(4).unwrap(),
+        vec![(5, Story::new("story 5".to_owned(), "".to_owned()))]
+    );
+
+    assert_eq!(
+        db.delete_link(&StoryLink::new(5, LinkKind::Duplicates, 2))
+            .is_err(),
+        true
+    );
+
+    db.delete_epic(1).unwrap();
+
+    assert_eq!(db.list_links().unwrap(), vec![]);
+    assert_eq!(db.get_epic(1).unwrap(), None);
+    assert_eq!(db.get_story(2).unwrap(), None);
+    assert_eq!(
+        db.update_story(2, &Story::new("".to_owned(), "".to_owned()))
+            .is_err(),
+        true
+    );
+    assert_eq!(db.delete_epic(1).is_err(), true);
+}
+
+#[test]
+fn jira_database_should_work_on_sqlite() {
+    let dir = tempfile::tempdir().unwrap();
+    let file_path = dir.path().join("db.sqlite");
+
+    let db = JiraDatabase::new_sqlite(file_path.to_str().unwrap().to_owned()).unwrap();
+
+    let epic_id = db
+        .create_epic(Epic::new("".to_owned(), "".to_owned()))
+        .unwrap();
+    let story_id = db
+        .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+        .unwrap();
+    db.update_story_status(story_id, RecordStatus::InProgress)
+        .unwrap();
+
+    let reopened = JiraDatabase::new_sqlite(file_path.to_str().unwrap().to_owned()).unwrap();
+    let db_state = reopened.read_db().unwrap();
+
+    assert_eq!(db_state.last_item_id, 2);
+    assert_eq!(db_state.epics[&epic_id].stories, vec![story_id]);
+    assert_eq!(db_state.stories[&story_id].status, RecordStatus::InProgress);
+
+    db.delete_epic(epic_id).unwrap();
+
+    assert_eq!(reopened.list_epics().unwrap().is_empty(), true);
+    assert_eq!(reopened.list_trash().unwrap().len(), 1);
+
+    db.purge_from_trash(epic_id).unwrap();
+
+    let db_state = reopened.read_db().unwrap();
+    assert_eq!(db_state.epics.is_empty(), true);
+    assert_eq!(db_state.stories.is_empty(), true);
+}
+
+#[test]
+fn failed_operations_should_be_rolled_back_on_sqlite() {
+    let db = JiraDatabase::with_database(
+        Box::new(SqliteDatabase::open_in_memory().unwrap()),
+        Default::default(),
+    );
+
+    let result = db.create_items(
+        vec![Epic::new("new".to_owned(), "".to_owned())],
+        vec![
+            (EpicRef::New(0), Story::new("".to_owned(), "".to_owned())),
+            (
+                EpicRef::Existing(99),
+                Story::new("".to_owned(), "".to_owned()),
+            ),
+        ],
+    );
+    assert_eq!(result.is_err(), true);
+
+    let db_state = db.read_db().unwrap();
+    assert_eq!(db_state.last_item_id, 0);
+    assert_eq!(db_state.epics.is_empty(), true);
+    assert_eq!(db_state.history.is_empty(), true);
+
+    let (epic_ids, story_ids) = db
+        .create_items(
+            vec![Epic::new("new".to_owned(), "".to_owned())],
+            vec![(EpicRef::New(0), Story::new("".to_owned(), "".to_owned()))],
+        )
+        .unwrap();
+    assert_eq!(
+        db.list_stories_of_epic(epic_ids[0]).unwrap()[0].0,
+        story_ids[0]
+    );
+}
+
+#[test]
+fn undo_should_work_on_sqlite() {
+    let dir = tempfile::tempdir().unwrap();
+    let file_path = dir.path().join("db.sqlite");
+
+    let db = JiraDatabase::new_sqlite(file_path.to_str().unwrap().to_owned()).unwrap();
+
+    let epic_id = db
+        .create_epic(Epic::new("".to_owned(), "".to_owned()))
+        .unwrap();
+    for _ in 0..2 {
+        db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+    }
+
+    let mut stack = UndoStack::default();
+    let before = db.read_db().unwrap();
+    db.delete_epic(epic_id).unwrap();
+    stack.record(&before, &db.read_db().unwrap());
+
+    stack.undo(&db).unwrap();
+
+    let db_state = db.read_db().unwrap();
+    assert_eq!(db_state.epics, before.epics);
+    assert_eq!(db_state.stories, before.stories);
+
+    let story_id = *db_state.stories.keys().next().unwrap();
+    let before = db.read_db().unwrap();
+    db.add_comment(story_id, "first".to_owned()).unwrap();
+    stack.record(&before, &db.read_db().unwrap());
+
+    stack.undo(&db).unwrap();
+
+    assert_eq!(db.comments_of(story_id).unwrap().is_empty(), true);
+
+    // the links of a purged story come back after the story itself
+    let other_story_id = *db
+        .read_db()
+        .unwrap()
+        .stories
+        .keys()
+        .find(|&&id| id != story_id)
+        .unwrap();
+    db.link_stories(story_id, LinkKind::Blocks, other_story_id)
+        .unwrap();
+    db.delete_story(epic_id, story_id).unwrap();
+
+    let before = db.read_db().unwrap();
+    db.purge_from_trash(story_id).unwrap();
+    stack.record(&before, &db.read_db().unwrap());
+
+    stack.undo(&db).unwrap();
+
+    assert_eq!(db.read_db().unwrap().links, before.links);
+}
+
+#[test]
+fn repair_should_work_on_sqlite() {
+    let dir = tempfile::tempdir().unwrap();
+    let file_path = dir.path().join("db.sqlite");
+
+    let db = JiraDatabase::new_sqlite(file_path.to_str().unwrap().to_owned()).unwrap();
+
+    // story 3 is in no epic, and story 2 shares its id with an epic
+    let mut db_state = sample_state();
+    db_state.epics.get_mut(&1).unwrap().stories = vec![2];
+    db_state
+        .epics
+        .insert(2, Epic::new("epic 2".to_owned(), "".to_owned()));
+    db.database.write_db(&db_state).unwrap();
+
+    assert_eq!(db.check().unwrap().len(), 2);
+    assert_eq!(db.repair().unwrap().len(), 2);
+
+    let db_state = db.read_db().unwrap();
+    assert_eq!(db.check().unwrap(), vec![]);
+    assert_eq!(db_state.last_item_id, 5);
+    assert_eq!(db_state.epics[&1].stories, vec![4]);
+    assert_eq!(db_state.epics[&5].stories, vec![3]);
+}
+
+#[test]
+fn import_from_should_copy_json_database() {
+    let dir = tempfile::tempdir().unwrap();
+    let json_path = dir.path().join("db.json");
+
+    let json_db = JSONFileDatabase::new(json_path.to_str().unwrap().to_owned());
+    json_db.save_state(&sample_state()).unwrap();
+
+    let db = SqliteDatabase::open_in_memory().unwrap();
+
+    assert_eq!(db.import_from(&json_db).is_ok(), true);
+    assert_eq!(db.read_db().unwrap(), sample_state());
+}
+
+#[test]
+fn open_should_migrate_databases_from_older_versions() {
+    let dir = tempfile::tempdir().unwrap();
+    let file_path = dir.path().join("db.sqlite");
+
+    // schema as written before planning fields existed
+    let connection = rusqlite::Connection::open(&file_path).unwrap();
+    connection
+        .execute_batch(
+            "
+            CREATE TABLE meta (key TEXT PRIMARY KEY, value INTEGER NOT NULL);
+            CREATE TABLE epics (
+                id INTEGER PRIMARY KEY, name TEXT NOT NULL,
+                description TEXT NOT NULL, status TEXT NOT NULL
+            );
+            CREATE TABLE stories (
+                id INTEGER PRIMARY KEY,
+                epic_id INTEGER REFERENCES epics(id) ON DELETE CASCADE,
+                position INTEGER NOT NULL DEFAULT 0, name TEXT NOT NULL,
+                description TEXT NOT NULL, status TEXT NOT NULL
+            );
+            INSERT INTO meta VALUES ('last_item_id', 2);
+            INSERT INTO epics VALUES (1, 'epic', '', 'Open');
+            INSERT INTO stories VALUES (2, 1, 0, 'story', '', 'InProgress');
+            ",
+        )
+        .unwrap();
+    drop(connection);
+
+    let db = SqliteDatabase::open(file_path.to_str().unwrap()).unwrap();
+
+    let story = db.get_story(2).unwrap().unwrap();
+    assert_eq!(story.status, RecordStatus::InProgress);
+    assert_eq!(story.priority, None);
+    assert_eq!(story.labels.is_empty(), true);
+    assert_eq!(story.created_at, None);
+    assert_eq!(db.history_of(2).unwrap().is_empty(), true);
+    assert_eq!(db.get_epic(1).unwrap().unwrap().stories, vec![2]);
+    assert_eq!(db.next_item_id().unwrap(), 3);
+}
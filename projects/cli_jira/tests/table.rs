@@ -0,0 +1,62 @@
+// Asserts compare with `true`/`false` on purpose, as throughout the suite.
+#![allow(clippy::bool_assert_comparison)]
+
+use cli_jira::ui::pages::page_helpers::display_width;
+use cli_jira::ui::{Column, Table};
+
+fn sample_table() -> Table {
+    let mut table = Table::new(vec![
+        Column::fixed("id", 4),
+        Column::fill("name", 6),
+        Column::fill("description", 8).wrapped(),
+    ]);
+
+    table.add_row(vec![
+        "1".to_owned(),
+        "Café ☕".to_owned(),
+        "Everything about the coffee machine".to_owned(),
+    ]);
+    table.add_row(vec!["2".to_owned(), "日本語".to_owned()]);
+
+    table
+}
+
+#[test]
+fn render_should_grow_fill_columns_to_their_content() {
+    let lines = sample_table().render(80);
+
+    assert_eq!(
+        lines,
+        vec![
+            " id  |  name   |             description",
+            "1    | Café ☕ | Everything about the coffee machine",
+            "2    | 日本語",
+        ]
+    );
+}
+
+#[test]
+fn render_should_fit_narrow_terminals() {
+    let lines = sample_table().render(30);
+
+    assert_eq!(
+        lines,
+        vec![
+            " id  |  name   |  description",
+            "1    | Café ☕ | Everything",
+            "     |         | about the",
+            "     |         | coffee",
+            "     |         | machine",
+            "2    | 日本語",
+        ]
+    );
+    assert_eq!(lines.iter().all(|line| display_width(line) <= 30), true);
+}
+
+#[test]
+fn render_should_truncate_below_the_minimum_width() {
+    let mut table = Table::new(vec![Column::fixed("id", 4), Column::fill("name", 6)]);
+    table.add_row(vec!["12345".to_owned(), "🚀🚀🚀🚀".to_owned()]);
+
+    assert_eq!(table.render(10), vec![" id  |  name", "1... | 🚀...",]);
+}
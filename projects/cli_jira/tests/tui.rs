@@ -0,0 +1,277 @@
+// Asserts compare with `true`/`false` on purpose, as throughout the suite.
+#![allow(clippy::bool_assert_comparison)]
+
+mod common;
+
+use cli_jira::db::JiraDatabase;
+use cli_jira::models::{Epic, RecordStatus, Story};
+use cli_jira::tui::App;
+use cli_jira::workflow::Workflow;
+use common::MockDB;
+use ratatui::backend::TestBackend;
+use ratatui::buffer::Buffer;
+use ratatui::crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use ratatui::Terminal;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+type Events = Rc<RefCell<VecDeque<Event>>>;
+
+fn key(code: KeyCode) -> Event {
+    Event::Key(KeyEvent::new(code, KeyModifiers::NONE))
+}
+
+fn press(events: &Events, codes: impl IntoIterator<Item = KeyCode>) {
+    events.borrow_mut().extend(codes.into_iter().map(key));
+}
+
+fn type_text(events: &Events, text: &str) {
+    press(events, text.chars().map(KeyCode::Char));
+}
+
+fn screen_text(buffer: &Buffer) -> String {
+    buffer
+        .content()
+        .chunks(buffer.area.width as usize)
+        .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn sample_app() -> (App<TestBackend>, Rc<JiraDatabase>, Events) {
+    let db = Rc::new(JiraDatabase::with_database(
+        Box::new(MockDB::new()),
+        Workflow::default(),
+    ));
+    let events: Events = Rc::default();
+    let terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+
+    let app = App::new(db.clone(), terminal, Box::new(events.clone()));
+
+    (app, db, events)
+}
+
+#[test]
+fn should_create_and_open_items_with_modal_forms() {
+    let (mut app, db, events) = sample_app();
+
+    press(&events, [KeyCode::Char('c')]);
+    type_text(&events, "Backend");
+    press(&events, [KeyCode::Tab]);
+    type_text(&events, "api work");
+    press(&events, [KeyCode::Enter]);
+    app.run().unwrap();
+
+    let epic = db.get_epic(1).unwrap().unwrap();
+    assert_eq!(epic.name, "Backend");
+    assert_eq!(epic.description, "api work");
+    assert_eq!(screen_text(&app.last_frame()).contains("Backend"), true);
+
+    // esc cancels without creating anything
+    press(
+        &events,
+        [KeyCode::Char('c'), KeyCode::Char('x'), KeyCode::Esc],
+    );
+    app.run().unwrap();
+    assert_eq!(db.list_epics().unwrap().len(), 1);
+
+    press(&events, [KeyCode::Enter, KeyCode::Char('c')]);
+    type_text(&events, "Login");
+    press(&events, [KeyCode::Enter]);
+    app.run().unwrap();
+
+    let screen = screen_text(&app.last_frame());
+    assert_eq!(screen.contains("EPIC 1: Backend"), true);
+    assert_eq!(screen.contains("Login"), true);
+
+    // open the story and pick the second status the workflow allows
+    press(
+        &events,
+        [
+            KeyCode::Enter,
+            KeyCode::Char('u'),
+            KeyCode::Down,
+            KeyCode::Enter,
+        ],
+    );
+    app.run().unwrap();
+
+    assert_eq!(
+        db.get_story(2).unwrap().unwrap().status,
+        RecordStatus::Resolved
+    );
+    assert_eq!(
+        screen_text(&app.last_frame()).contains("STORY 2: Login"),
+        true
+    );
+}
+
+#[test]
+fn delete_should_only_be_confirmed_with_y() {
+    let (mut app, db, events) = sample_app();
+    db.create_epic(cli_jira::models::Epic::new(
+        "Backend".to_owned(),
+        "".to_owned(),
+    ))
+    .unwrap();
+
+    press(
+        &events,
+        [KeyCode::Enter, KeyCode::Char('d'), KeyCode::Enter],
+    );
+    press(&events, [KeyCode::Char('n')]);
+    app.run().unwrap();
+    assert_eq!(db.get_epic(1).unwrap().is_some(), true);
+
+    press(&events, [KeyCode::Char('d'), KeyCode::Char('y')]);
+    app.run().unwrap();
+    assert_eq!(db.get_epic(1).unwrap(), None);
+    assert_eq!(screen_text(&app.last_frame()).contains("EPICS"), true);
+
+    // back on the home page, esc closes the app
+    press(&events, [KeyCode::Esc, KeyCode::Char('c')]);
+    app.run().unwrap();
+    assert_eq!(db.list_epics().unwrap().is_empty(), true);
+}
+
+#[test]
+fn errors_should_be_shown_instead_of_closing_the_app() {
+    let (mut app, db, events) = sample_app();
+
+    press(&events, [KeyCode::Char('c')]);
+    type_text(&events, "Backend");
+    press(&events, [KeyCode::Enter]);
+    app.run().unwrap();
+
+    // changed behind the app's back, so the create can't be undone
+    db.update_epic(1, "Renamed".to_owned(), "".to_owned())
+        .unwrap();
+
+    press(&events, [KeyCode::Char('z')]);
+    app.run().unwrap();
+
+    let screen = screen_text(&app.last_frame());
+    assert_eq!(screen.contains("failed to undo"), true);
+    assert_eq!(db.get_epic(1).unwrap().unwrap().name, "Renamed");
+}
+
+#[test]
+fn board_should_move_the_selected_story_between_columns() {
+    let (mut app, db, events) = sample_app();
+
+    let epic_id = db
+        .create_epic(Epic::new("Backend".to_owned(), "".to_owned()))
+        .unwrap();
+    db.create_story(Story::new("Login".to_owned(), "".to_owned()), epic_id)
+        .unwrap();
+    let story_id = db
+        .create_story(Story::new("Logout".to_owned(), "".to_owned()), epic_id)
+        .unwrap();
+
+    press(
+        &events,
+        [KeyCode::Char('b'), KeyCode::Down, KeyCode::Char('>')],
+    );
+    app.run().unwrap();
+
+    assert_eq!(
+        db.get_story(story_id).unwrap().unwrap().status,
+        RecordStatus::InProgress
+    );
+    let screen = screen_text(&app.last_frame());
+    assert_eq!(screen.contains("Open (1)"), true);
+    assert_eq!(screen.contains("In progress (1)"), true);
+
+    // the first column has no left neighbour
+    press(&events, [KeyCode::Up, KeyCode::Char('<')]);
+    app.run().unwrap();
+    assert_eq!(
+        db.get_story(story_id - 1).unwrap().unwrap().status,
+        RecordStatus::Open
+    );
+}
+
+#[test]
+fn story_should_tick_and_remove_the_selected_checklist_item() {
+    let (mut app, db, events) = sample_app();
+
+    let epic_id = db
+        .create_epic(Epic::new("Backend".to_owned(), "".to_owned()))
+        .unwrap();
+    let story_id = db
+        .create_story(Story::new("Login".to_owned(), "".to_owned()), epic_id)
+        .unwrap();
+    db.add_checklist_item(story_id, "write tests".to_owned())
+        .unwrap();
+
+    // open the story and add a second item in the dialog
+    press(
+        &events,
+        [KeyCode::Enter, KeyCode::Enter, KeyCode::Char('a')],
+    );
+    type_text(&events, "review");
+    press(&events, [KeyCode::Enter, KeyCode::Down, KeyCode::Enter]);
+    app.run().unwrap();
+
+    let checklist = db.get_story(story_id).unwrap().unwrap().checklist;
+    assert_eq!(checklist.len(), 2);
+    assert_eq!(checklist[1].text, "review");
+    assert_eq!(checklist[1].done, true);
+    assert_eq!(screen_text(&app.last_frame()).contains("[x]"), true);
+
+    press(&events, [KeyCode::Up, KeyCode::Char('x')]);
+    app.run().unwrap();
+
+    let checklist = db.get_story(story_id).unwrap().unwrap().checklist;
+    assert_eq!(checklist.len(), 1);
+    assert_eq!(checklist[0].text, "review");
+}
+
+#[test]
+fn story_should_be_linked_and_warn_when_started_while_blocked() {
+    let (mut app, db, events) = sample_app();
+
+    let epic_id = db
+        .create_epic(Epic::new("Backend".to_owned(), "".to_owned()))
+        .unwrap();
+    let story_id = db
+        .create_story(Story::new("Login".to_owned(), "".to_owned()), epic_id)
+        .unwrap();
+    let blocker_id = db
+        .create_story(Story::new("Schema".to_owned(), "".to_owned()), epic_id)
+        .unwrap();
+
+    // open the story and pick "blocked by" in the dialog
+    press(
+        &events,
+        [
+            KeyCode::Enter,
+            KeyCode::Enter,
+            KeyCode::Char('l'),
+            KeyCode::Down,
+            KeyCode::Enter,
+        ],
+    );
+    type_text(&events, &blocker_id.to_string());
+    press(&events, [KeyCode::Enter]);
+    app.run().unwrap();
+
+    assert_eq!(db.links_of(story_id).unwrap().len(), 1);
+    assert_eq!(
+        screen_text(&app.last_frame()).contains(&format!("links: blocked by {}", blocker_id)),
+        true
+    );
+
+    // moving it to in progress asks first, and "n" keeps it open
+    press(
+        &events,
+        [KeyCode::Char('u'), KeyCode::Enter, KeyCode::Char('n')],
+    );
+    app.run().unwrap();
+
+    assert_eq!(
+        db.get_story(story_id).unwrap().unwrap().status,
+        RecordStatus::Open
+    );
+}
@@ -0,0 +1,265 @@
+// Asserts compare with `true`/`false` on purpose, as throughout the suite.
+#![allow(clippy::bool_assert_comparison)]
+
+mod common;
+
+use cli_jira::db::JiraDatabase;
+use cli_jira::models::{Change, Epic, LinkKind, RecordStatus, Story};
+use cli_jira::undo::{Revision, UndoStack};
+use cli_jira::workflow::Workflow;
+use common::MockDB;
+
+fn sample_db() -> (JiraDatabase, u32, u32, u32) {
+    let db = JiraDatabase::with_database(Box::new(MockDB::new()), Workflow::default());
+
+    let epic_id = db
+        .create_epic(Epic::new("Backend".to_owned(), "".to_owned()))
+        .unwrap();
+    let other_epic_id = db
+        .create_epic(Epic::new("Frontend".to_owned(), "".to_owned()))
+        .unwrap();
+    let story_id = db
+        .create_story(Story::new("Login".to_owned(), "".to_owned()), epic_id)
+        .unwrap();
+
+    (db, epic_id, other_epic_id, story_id)
+}
+
+#[test]
+fn revision_should_only_contain_changed_items() {
+    let (db, epic_id, _, story_id) = sample_db();
+
+    let before = db.read_db().unwrap();
+    db.update_story_status(story_id, RecordStatus::InProgress)
+        .unwrap();
+    let after = db.read_db().unwrap();
+
+    let revision = Revision::between(&before, &after).unwrap();
+
+    assert_eq!(revision.before.epics.is_empty(), true);
+    assert_eq!(
+        revision.before.stories[&story_id],
+        Some((epic_id, before.stories[&story_id].clone()))
+    );
+    assert_eq!(
+        revision.after.stories[&story_id],
+        Some((epic_id, after.stories[&story_id].clone()))
+    );
+
+    assert_eq!(Revision::between(&after, &after), None);
+}
+
+#[test]
+fn undo_and_redo_should_revert_deleting_an_epic_with_its_stories() {
+    let (db, epic_id, _, story_id) = sample_db();
+    let mut stack = UndoStack::default();
+
+    let before = db.read_db().unwrap();
+    db.delete_epic(epic_id).unwrap();
+    stack.record(&before, &db.read_db().unwrap());
+
+    assert_eq!(stack.undo(&db).unwrap(), true);
+
+    let db_state = db.read_db().unwrap();
+    assert_eq!(db_state.epics, before.epics);
+    assert_eq!(db_state.stories, before.stories);
+    assert_eq!(
+        db.history_of(epic_id).unwrap().last().unwrap().change,
+        Change::Undone
+    );
+
+    assert_eq!(stack.can_undo(), false);
+    assert_eq!(stack.redo(&db).unwrap(), true);
+
+    assert_eq!(db.get_epic(epic_id).unwrap(), None);
+    assert_eq!(db.get_story(story_id).unwrap(), None);
+    assert_eq!(stack.redo(&db).unwrap(), false);
+}
+
+#[test]
+fn undo_should_revert_creates_edits_and_moves() {
+    let (db, epic_id, other_epic_id, story_id) = sample_db();
+    let mut stack = UndoStack::default();
+    let original = db.read_db().unwrap();
+
+    let before = db.read_db().unwrap();
+    let new_story_id = db
+        .create_story(Story::new("".to_owned(), "".to_owned()), other_epic_id)
+        .unwrap();
+    stack.record(&before, &db.read_db().unwrap());
+
+    let before = db.read_db().unwrap();
+    db.update_epic(epic_id, "Renamed".to_owned(), "".to_owned())
+        .unwrap();
+    stack.record(&before, &db.read_db().unwrap());
+
+    let before = db.read_db().unwrap();
+    db.move_story(story_id, epic_id, other_epic_id).unwrap();
+    stack.record(&before, &db.read_db().unwrap());
+
+    while stack.undo(&db).unwrap() {}
+
+    let db_state = db.read_db().unwrap();
+    assert_eq!(db_state.epics, original.epics);
+    assert_eq!(db_state.stories, original.stories);
+    assert_eq!(db.get_story(new_story_id).unwrap(), None);
+}
+
+#[test]
+fn undo_should_fail_if_the_items_changed_in_the_meantime() {
+    let (db, _, _, story_id) = sample_db();
+    let mut stack = UndoStack::default();
+
+    let before = db.read_db().unwrap();
+    db.update_story(story_id, "Sign in".to_owned(), "".to_owned())
+        .unwrap();
+    stack.record(&before, &db.read_db().unwrap());
+
+    // e.g. from another process, without going through the stack
+    db.update_story(story_id, "Log in".to_owned(), "".to_owned())
+        .unwrap();
+
+    assert_eq!(stack.undo(&db).is_err(), true);
+    assert_eq!(db.get_story(story_id).unwrap().unwrap().name, "Log in");
+    assert_eq!(stack.can_undo(), true);
+}
+
+#[test]
+fn new_changes_should_clear_redo() {
+    let (db, epic_id, _, _) = sample_db();
+    let mut stack = UndoStack::default();
+
+    let before = db.read_db().unwrap();
+    db.update_epic(epic_id, "Renamed".to_owned(), "".to_owned())
+        .unwrap();
+    stack.record(&before, &db.read_db().unwrap());
+    stack.undo(&db).unwrap();
+
+    assert_eq!(stack.can_redo(), true);
+
+    let before = db.read_db().unwrap();
+    db.update_epic_labels(epic_id, vec!["api".to_owned()])
+        .unwrap();
+    stack.record(&before, &db.read_db().unwrap());
+
+    assert_eq!(stack.can_redo(), false);
+}
+
+#[test]
+fn undo_and_redo_should_revert_adding_a_comment() {
+    let (db, _, _, story_id) = sample_db();
+    let mut stack = UndoStack::default();
+
+    let before = db.read_db().unwrap();
+    let comment = db.add_comment(story_id, "Looks good".to_owned()).unwrap();
+    stack.record(&before, &db.read_db().unwrap());
+
+    assert_eq!(stack.undo(&db).unwrap(), true);
+    assert_eq!(db.comments_of(story_id).unwrap().is_empty(), true);
+    assert_eq!(
+        db.history_of(story_id).unwrap().last().unwrap().change,
+        Change::Undone
+    );
+
+    assert_eq!(stack.redo(&db).unwrap(), true);
+    assert_eq!(db.comments_of(story_id).unwrap(), vec![comment]);
+}
+
+#[test]
+fn undo_and_redo_should_revert_linking_stories() {
+    let (db, epic_id, _, story_id) = sample_db();
+    let other_story_id = db
+        .create_story(Story::new("Logout".to_owned(), "".to_owned()), epic_id)
+        .unwrap();
+    let mut stack = UndoStack::default();
+
+    let before = db.read_db().unwrap();
+    db.link_stories(story_id, LinkKind::Blocks, other_story_id)
+        .unwrap();
+    stack.record(&before, &db.read_db().unwrap());
+
+    let before = db.read_db().unwrap();
+    db.unlink_stories(other_story_id, LinkKind::BlockedBy, story_id)
+        .unwrap();
+    stack.record(&before, &db.read_db().unwrap());
+
+    assert_eq!(stack.undo(&db).unwrap(), true);
+    assert_eq!(db.links_of(story_id).unwrap().len(), 1);
+    assert_eq!(
+        db.history_of(other_story_id)
+            .unwrap()
+            .last()
+            .unwrap()
+            .change,
+        Change::Undone
+    );
+
+    assert_eq!(stack.undo(&db).unwrap(), true);
+    assert_eq!(db.links_of(story_id).unwrap().is_empty(), true);
+
+    assert_eq!(stack.redo(&db).unwrap(), true);
+    assert_eq!(db.links_of(other_story_id).unwrap()[0].story_id, story_id);
+}
+
+#[test]
+fn undo_should_bring_back_the_links_of_a_purged_story() {
+    let (db, epic_id, _, story_id) = sample_db();
+    let other_story_id = db
+        .create_story(Story::new("Logout".to_owned(), "".to_owned()), epic_id)
+        .unwrap();
+    db.link_stories(story_id, LinkKind::RelatesTo, other_story_id)
+        .unwrap();
+    db.delete_story(epic_id, story_id).unwrap();
+    let mut stack = UndoStack::default();
+
+    let before = db.read_db().unwrap();
+    db.purge_from_trash(story_id).unwrap();
+    stack.record(&before, &db.read_db().unwrap());
+
+    assert_eq!(db.read_db().unwrap().links.is_empty(), true);
+    assert_eq!(stack.undo(&db).unwrap(), true);
+
+    let db_state = db.read_db().unwrap();
+    assert_eq!(db_state.stories, before.stories);
+    assert_eq!(db_state.links, before.links);
+}
+
+#[test]
+fn journal_should_record_the_same_revision_as_comparing_the_boards() {
+    let (db, epic_id, other_epic_id, story_id) = sample_db();
+    let other_story_id = db
+        .create_story(
+            Story::new("Logout".to_owned(), "".to_owned()),
+            other_epic_id,
+        )
+        .unwrap();
+
+    let assert_journal_matches = |change: &dyn Fn()| {
+        let before = db.read_db().unwrap();
+        db.start_journal();
+        change();
+
+        assert_eq!(
+            db.take_revision().unwrap(),
+            Revision::between(&before, &db.read_db().unwrap())
+        );
+    };
+
+    assert_journal_matches(&|| {
+        db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+    });
+    assert_journal_matches(&|| db.move_story(story_id, epic_id, other_epic_id).unwrap());
+    assert_journal_matches(&|| {
+        db.add_comment(story_id, "Looks good".to_owned()).unwrap();
+    });
+    assert_journal_matches(&|| {
+        db.link_stories(story_id, LinkKind::Blocks, other_story_id)
+            .unwrap()
+    });
+    assert_journal_matches(&|| db.delete_epic(other_epic_id).unwrap());
+    assert_journal_matches(&|| db.purge_from_trash(other_epic_id).unwrap());
+
+    db.start_journal();
+    assert_eq!(db.take_revision().unwrap(), None);
+}
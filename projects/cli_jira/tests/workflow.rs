@@ -0,0 +1,209 @@
+// Asserts compare with `true`/`false` on purpose, as throughout the suite.
+#![allow(clippy::bool_assert_comparison)]
+
+use cli_jira::models::RecordStatus;
+use cli_jira::workflow::Workflow;
+
+use std::collections::BTreeMap;
+
+fn review() -> RecordStatus {
+    RecordStatus::Custom("Review".to_owned())
+}
+
+fn review_workflow() -> Workflow {
+    Workflow {
+        statuses: vec![RecordStatus::Open, review(), RecordStatus::Closed],
+        transitions: BTreeMap::from([
+            (RecordStatus::Open, vec![review()]),
+            (review(), vec![RecordStatus::Closed, RecordStatus::Open]),
+        ]),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn default_workflow_should_be_valid() {
+    assert_eq!(Workflow::default().validate().is_ok(), true);
+    assert_eq!(Workflow::default().initial_status(), RecordStatus::Open);
+}
+
+#[test]
+fn default_workflow_should_not_reopen_closed_items() {
+    let workflow = Workflow::default();
+
+    assert_eq!(
+        workflow.next_statuses(&RecordStatus::Closed),
+        vec![RecordStatus::InProgress]
+    );
+    assert_eq!(
+        workflow
+            .check_transition(&RecordStatus::Closed, &RecordStatus::Open)
+            .is_err(),
+        true
+    );
+    assert_eq!(
+        workflow
+            .check_transition(&RecordStatus::Closed, &RecordStatus::Closed)
+            .is_ok(),
+        true
+    );
+}
+
+#[test]
+fn next_statuses_should_follow_transitions_in_workflow_order() {
+    let workflow = review_workflow();
+
+    assert_eq!(
+        workflow.next_statuses(&review()),
+        vec![RecordStatus::Open, RecordStatus::Closed]
+    );
+    // no entry means the status is final
+    assert_eq!(workflow.next_statuses(&RecordStatus::Closed), vec![]);
+    // unknown statuses, e.g. from an older workflow, may move anywhere
+    assert_eq!(
+        workflow.next_statuses(&RecordStatus::Resolved),
+        workflow.statuses
+    );
+}
+
+#[test]
+fn workflow_without_transitions_should_allow_every_change() {
+    let workflow = Workflow {
+        transitions: BTreeMap::new(),
+        ..review_workflow()
+    };
+
+    assert_eq!(
+        workflow.next_statuses(&RecordStatus::Closed),
+        vec![RecordStatus::Open, review()]
+    );
+}
+
+#[test]
+fn check_transition_should_explain_illegal_changes() {
+    let workflow = review_workflow();
+
+    let error = workflow
+        .check_transition(&RecordStatus::Open, &RecordStatus::Closed)
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "cannot change status from Open to Closed, allowed: Review"
+    );
+
+    let error = workflow
+        .check_transition(&RecordStatus::Closed, &RecordStatus::Open)
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Closed is a final status and can't be changed"
+    );
+
+    let error = workflow
+        .check_transition(&RecordStatus::Open, &RecordStatus::InProgress)
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "unknown status 'In progress', expected one of: Open, Review, Closed"
+    );
+}
+
+#[test]
+fn resolve_should_match_custom_statuses_ignoring_case() {
+    let workflow = review_workflow();
+
+    assert_eq!(
+        workflow.resolve(RecordStatus::Custom("review".to_owned())),
+        review()
+    );
+    assert_eq!(
+        workflow.resolve(RecordStatus::Custom("done".to_owned())),
+        RecordStatus::Custom("done".to_owned())
+    );
+}
+
+#[test]
+fn validate_should_reject_broken_workflows() {
+    let empty = Workflow {
+        statuses: vec![],
+        transitions: BTreeMap::new(),
+        ..Default::default()
+    };
+    let duplicate = Workflow {
+        statuses: vec![RecordStatus::Open, RecordStatus::Open],
+        transitions: BTreeMap::new(),
+        ..Default::default()
+    };
+    let unknown_target = Workflow {
+        statuses: vec![RecordStatus::Open],
+        transitions: BTreeMap::from([(RecordStatus::Open, vec![review()])]),
+        ..Default::default()
+    };
+
+    let unknown_done = Workflow {
+        statuses: vec![RecordStatus::Open, RecordStatus::Closed],
+        transitions: BTreeMap::new(),
+        done: vec![review()],
+        ..Default::default()
+    };
+
+    assert_eq!(empty.validate().is_err(), true);
+    assert_eq!(duplicate.validate().is_err(), true);
+    assert_eq!(unknown_target.validate().is_err(), true);
+    assert_eq!(unknown_done.validate().is_err(), true);
+}
+
+#[test]
+fn done_statuses_should_default_to_those_of_the_workflow() {
+    let workflow: Workflow =
+        toml::from_str(r#"statuses = ["Open", "InProgress", "Review", "Closed"]"#).unwrap();
+
+    assert_eq!(workflow.done, vec![RecordStatus::Closed]);
+    assert_eq!(workflow.validate().is_ok(), true);
+
+    let workflow: Workflow = toml::from_str(
+        r#"
+        statuses = ["Open", "Closed"]
+        done = ["Resolved"]
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(workflow.validate().is_err(), true);
+}
+
+#[test]
+fn statuses_should_serialize_by_name() {
+    let statuses = vec![RecordStatus::InProgress, review()];
+
+    let json = serde_json::to_string(&statuses).unwrap();
+
+    assert_eq!(json, r#"["InProgress","Review"]"#);
+    assert_eq!(
+        serde_json::from_str::<Vec<RecordStatus>>(&json).unwrap(),
+        statuses
+    );
+    assert_eq!(serde_json::from_str::<RecordStatus>(r#""""#).is_err(), true);
+}
+
+#[test]
+fn auto_resolve_status_should_pick_first_reachable_done_status() {
+    let workflow = Workflow::default();
+
+    assert_eq!(
+        workflow.auto_resolve_status(&RecordStatus::Open),
+        Some(RecordStatus::Resolved)
+    );
+    assert_eq!(workflow.is_done(&RecordStatus::Closed), true);
+
+    let workflow = Workflow {
+        done: vec![RecordStatus::Closed],
+        ..review_workflow()
+    };
+
+    assert_eq!(workflow.auto_resolve_status(&RecordStatus::Open), None);
+    assert_eq!(
+        workflow.auto_resolve_status(&review()),
+        Some(RecordStatus::Closed)
+    );
+}